@@ -1,12 +1,16 @@
 use std::{collections::HashMap, net::SocketAddr, time::Instant};
 
-use yourcontrols_net::{Payloads, SenderReceiver};
+use yourcontrols_net::{Payloads, SenderReceiver, RELAY_CAPABILITIES};
 
 pub const SERVER_NAME: &str = "SERVER";
 
 pub struct ClientConnection {
     pub addr: SocketAddr,
     pub is_observer: bool,
+    // Joined to watch only, never takes control
+    pub is_spectator: bool,
+    pub version: String,
+    pub aircraft: Option<String>,
 }
 
 pub struct ServerState {
@@ -36,9 +40,25 @@ impl ServerState {
             .find(|client| client.addr == addr)
     }
 
-    pub fn add_client(&mut self, name: String, addr: SocketAddr, is_observer: bool) {
-        self.clients
-            .insert(name, ClientConnection { addr, is_observer });
+    pub fn add_client(
+        &mut self,
+        name: String,
+        addr: SocketAddr,
+        is_observer: bool,
+        is_spectator: bool,
+        version: String,
+        aircraft: Option<String>,
+    ) {
+        self.clients.insert(
+            name,
+            ClientConnection {
+                addr,
+                is_observer,
+                is_spectator,
+                version,
+                aircraft,
+            },
+        );
     }
 
     pub fn remove_client(&mut self, name: &str) {
@@ -118,12 +138,31 @@ impl ServerState {
             | Payloads::RequestHosting { .. }
             | Payloads::PeerEstablished { .. }
             | Payloads::ConnectionDenied { .. }
+            | Payloads::ServerCapabilities { .. }
             | Payloads::Heartbeat
             | Payloads::SetSelfObserver { .. }
+            | Payloads::LeaveSelfObserver { .. }
             | Payloads::PlayerLeft { .. } => return,
             // Used
-            Payloads::AircraftDefinition { .. } | Payloads::Update { .. } => {}
-            Payloads::InitHandshake { name, version } => {
+            Payloads::AircraftDefinition { .. }
+            | Payloads::Update { .. }
+            | Payloads::ReadyStatus { .. }
+            | Payloads::SeatAssigned { .. }
+            | Payloads::ChatMessage { .. }
+            | Payloads::RequiredAircraft { .. } => {}
+            Payloads::AircraftChanging { name, to } => {
+                if let Some(client) = self.clients.get_mut(name) {
+                    client.aircraft = Some(to.clone());
+                }
+            }
+            // Passwords are only checked by directly hosted servers
+            Payloads::InitHandshake {
+                name,
+                version,
+                spectator,
+                aircraft,
+                ..
+            } => {
                 let server_version = dotenv::var("APP_VERSION").unwrap();
 
                 if *version != server_version {
@@ -145,14 +184,35 @@ impl ServerState {
                             in_control: self.in_control == *name,
                             is_server: self.hoster == *name,
                             is_observer: info.is_observer,
+                            is_spectator: info.is_spectator,
+                            version: Some(info.version.clone()),
+                            aircraft: info.aircraft.clone(),
                         },
                         addr,
                     )
                     .ok();
                 }
 
+                net.send_message(
+                    Payloads::ServerCapabilities {
+                        capabilities: RELAY_CAPABILITIES
+                            .iter()
+                            .map(|capability| capability.to_string())
+                            .collect(),
+                    },
+                    addr,
+                )
+                .ok();
+
                 // Add client
-                self.add_client(name.clone(), addr, true);
+                self.add_client(
+                    name.clone(),
+                    addr,
+                    true,
+                    *spectator,
+                    version.clone(),
+                    aircraft.clone(),
+                );
 
                 // If the client is the first one to connect, give them control and have them "host"
                 if self.in_control == SERVER_NAME {
@@ -165,6 +225,9 @@ impl ServerState {
                         in_control: false,
                         is_server: false,
                         is_observer: true,
+                        is_spectator: *spectator,
+                        version: Some(version.clone()),
+                        aircraft: aircraft.clone(),
                     },
                     Some(&addr),
                     net,
@@ -173,13 +236,21 @@ impl ServerState {
                 return;
             }
             Payloads::TransferControl { to, .. } => {
+                // Spectators can't be given control
+                if self
+                    .clients
+                    .get(to)
+                    .is_some_and(|client| client.is_spectator)
+                {
+                    return;
+                }
                 self.in_control.clone_from(to);
             }
             Payloads::SetObserver {
                 to, is_observer, ..
             } => {
                 if let Some(client) = self.clients.get_mut(to) {
-                    client.is_observer = *is_observer;
+                    client.is_observer = *is_observer || client.is_spectator;
                 }
             }
             Payloads::Ready => {
@@ -28,6 +28,9 @@ use yourcontrols_types::Error;
 struct TransferStruct {
     name: String,
     version: String,
+    spectator: bool,
+    password: Option<String>,
+    aircraft: Option<String>,
     // Internally receive data to send to clients
     client_rx: ClientReceiver,
     // Send data to app to receive client data
@@ -60,14 +63,21 @@ impl TransferStruct {
             Payloads::RequestHosting {..} |
             Payloads::Ready |
             Payloads::SetSelfObserver { .. }|
+            Payloads::LeaveSelfObserver { .. }|
             // No futher handling required
             Payloads::AircraftDefinition { .. } |
             Payloads::TransferControl { ..} |
             Payloads::SetObserver { .. } |
+            Payloads::ReadyStatus { .. } |
+            Payloads::SeatAssigned { .. } |
+            Payloads::ChatMessage { .. } |
+            Payloads::AircraftChanging { .. } |
+            Payloads::RequiredAircraft { .. } |
             Payloads::PlayerJoined { .. } |
             Payloads::PlayerLeft { .. } |
             Payloads::Update { .. } |
             Payloads::ConnectionDenied { .. } |
+            Payloads::ServerCapabilities { .. } |
             Payloads::SetHost |
             Payloads::AttemptHosterConnection {..} |
             Payloads::Heartbeat => {}
@@ -94,6 +104,9 @@ impl TransferStruct {
                 self.net.send_message(Payloads::InitHandshake {
                     name: self.name.clone(),
                     version: self.version.clone(),
+                    spectator: self.spectator,
+                    password: self.password.clone(),
+                    aircraft: self.aircraft.clone(),
                 }, addr).ok();
 
                 info!("[NETWORK] Established connection with port {} on {}!", addr.port(), session_id);
@@ -207,6 +220,12 @@ pub struct Client {
     version: String,
     timeout: u64,
     is_host: bool,
+    // Join only to watch, set before starting
+    spectator: bool,
+    // Password for the session, set before starting
+    password: Option<String>,
+    // Loaded aircraft sent with the handshake, set before starting
+    aircraft: Option<String>,
 }
 
 impl Client {
@@ -225,9 +244,24 @@ impl Client {
             username,
             version,
             is_host: false,
+            spectator: false,
+            password: None,
+            aircraft: None,
         }
     }
 
+    pub fn set_spectator(&mut self, spectator: bool) {
+        self.spectator = spectator;
+    }
+
+    pub fn set_password(&mut self, password: Option<String>) {
+        self.password = password;
+    }
+
+    pub fn set_aircraft(&mut self, aircraft: Option<String>) {
+        self.aircraft = aircraft;
+    }
+
     fn get_socket(&self, is_ipv6: bool) -> Result<Socket, laminar::ErrorKind> {
         Socket::bind_with_config(
             get_bind_address(is_ipv6, None),
@@ -294,6 +328,9 @@ impl Client {
             // State
             name: self.get_server_name().to_string(),
             version: self.version.clone(),
+            spectator: self.spectator,
+            password: self.password.clone(),
+            aircraft: self.aircraft.clone(),
             should_stop: self.should_stop.clone(),
             heartbeat_instant: Instant::now(),
         };
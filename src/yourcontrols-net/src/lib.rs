@@ -7,6 +7,8 @@ pub use client::Client;
 pub use messages::{Message, Payloads, SenderReceiver};
 pub use server::Server;
 pub use util::{
-    get_addr_from_hostname_and_port, get_rendezvous_server, get_socket_config, get_socket_duplex,
-    is_actually_ipv4, Event, ReceiveMessage, TransferClient,
+    find_free_port, get_addr_from_hostname_and_port, get_rendezvous_hostname,
+    get_rendezvous_server, get_seconds, get_socket_config, get_socket_duplex, is_actually_ipv4,
+    Event, ReceiveMessage, TransferClient, CAPABILITY_CHAT, CAPABILITY_SEATS,
+    CAPABILITY_SESSION_LOCK, CAPABILITY_SPECTATORS, RELAY_CAPABILITIES, SERVER_CAPABILITIES,
 };
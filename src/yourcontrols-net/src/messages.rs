@@ -25,11 +25,23 @@ pub enum Payloads {
     ConnectionDenied {
         reason: String,
     },
+    // Features the server supports, sent to each client on joining
+    ServerCapabilities {
+        capabilities: Vec<String>,
+    },
     PlayerJoined {
         name: String,
         in_control: bool,
         is_server: bool,
         is_observer: bool,
+        // Joined to watch only, never takes control
+        #[serde(default)]
+        is_spectator: bool,
+        // App version and loaded aircraft, None when the peer didn't send them
+        #[serde(default)]
+        version: Option<String>,
+        #[serde(default)]
+        aircraft: Option<String>,
     },
     PlayerLeft {
         name: String,
@@ -43,6 +55,15 @@ pub enum Payloads {
     InitHandshake {
         name: String,
         version: String,
+        // Defaulted so older clients' handshakes still decode and get InvalidVersion
+        #[serde(default)]
+        spectator: bool,
+        // Session password, None when joining without one
+        #[serde(default)]
+        password: Option<String>,
+        // Aircraft definition the client has loaded, shown in everyone's roster
+        #[serde(default)]
+        aircraft: Option<String>,
     },
     TransferControl {
         from: String,
@@ -56,6 +77,31 @@ pub enum Payloads {
     SetSelfObserver {
         name: String,
     },
+    // Asks to stop observing, spectators stay observers
+    LeaveSelfObserver {
+        name: String,
+    },
+    ReadyStatus {
+        name: String,
+        is_ready: bool,
+    },
+    // Seat someone holds in a multi-crew aircraft, None when they leave it
+    SeatAssigned {
+        name: String,
+        seat: Option<String>,
+    },
+    ChatMessage {
+        from: String,
+        text: String,
+    },
+    AircraftChanging {
+        name: String,
+        to: String,
+    },
+    // Aircraft the host is flying, which everyone should load
+    RequiredAircraft {
+        name: String,
+    },
     // Ready to receive data
     Ready,
     // Hole punching payloads
@@ -109,14 +155,21 @@ fn get_packet_for_message(
         Payloads::InvalidVersion {..} |
         Payloads::Heartbeat {..} |
         Payloads::SetSelfObserver { .. } |
+        Payloads::LeaveSelfObserver { .. } |
         Payloads::InvalidName {..} => Packet::reliable_unordered(target, payload_bytes),
         Payloads::PeerEstablished {..} |
         Payloads::RendezvousHandshake  {..} |
         Payloads::Handshake {..} => Packet::unreliable(target, payload_bytes),
         Payloads::InitHandshake {..} |
         Payloads::PlayerJoined {..} |
+        Payloads::ServerCapabilities {..} |
         Payloads::PlayerLeft {..} |
         Payloads::SetObserver {..} |
+        Payloads::ReadyStatus {..} |
+        Payloads::SeatAssigned {..} |
+        Payloads::ChatMessage {..} |
+        Payloads::AircraftChanging {..} |
+        Payloads::RequiredAircraft {..} |
         Payloads::Ready |
         Payloads::TransferControl {..} |
         Payloads::AircraftDefinition {..}  |
@@ -22,7 +22,9 @@ use crate::util::{
     ClientReceiver, ClientSender, Event, ReceiveMessage, ServerReceiver, ServerSender,
     TransferClient,
 };
-use crate::util::{HEARTBEAT_INTERVAL_MANUAL_SECS, LOOP_SLEEP_TIME_MS, MAX_PUNCH_RETRIES};
+use crate::util::{
+    HEARTBEAT_INTERVAL_MANUAL_SECS, LOOP_SLEEP_TIME_MS, MAX_PUNCH_RETRIES, SERVER_CAPABILITIES,
+};
 use crate::{
     get_socket_duplex,
     util::{get_bind_address, get_local_ip_address, get_rendezvous_server, get_socket_config},
@@ -37,6 +39,9 @@ use yourcontrols_types::Error;
 struct Client {
     addr: SocketAddr,
     is_observer: bool,
+    is_spectator: bool,
+    version: String,
+    aircraft: Option<String>,
 }
 
 struct TransferStruct {
@@ -52,10 +57,19 @@ struct TransferStruct {
     client_rx: ClientReceiver,
     // State
     in_control: String,
+    // Who holds each seat of a multi-crew aircraft
+    seats: HashMap<String, String>,
+    // Reject new joiners
+    locked: bool,
+    max_clients: Option<usize>,
+    // Joiners must send this password when set
+    password: Option<String>,
     should_stop: Arc<AtomicBool>,
     number_connections: Arc<AtomicU16>,
     username: String,
     version: String,
+    // Aircraft the host has loaded, sent to joiners
+    aircraft: Option<String>,
     heartbeat_instant: Instant,
     // Metrics
     metrics: HashMap<SocketAddr, Metrics>,
@@ -63,6 +77,14 @@ struct TransferStruct {
 }
 
 impl TransferStruct {
+    // A seat has one holder and a holder one seat, so moving replaces both
+    fn assign_seat(&mut self, name: &str, seat: Option<&String>) {
+        self.seats.retain(|_, holder| holder != name);
+        if let Some(seat) = seat {
+            self.seats.insert(seat.clone(), name.to_string());
+        }
+    }
+
     fn send_to_all(&mut self, except: Option<&SocketAddr>, payload: Payloads) {
         let mut to_send = Vec::new();
 
@@ -140,6 +162,8 @@ impl TransferStruct {
             | Payloads::RequestHosting { .. }
             | Payloads::AircraftDefinition { .. }
             | Payloads::ConnectionDenied { .. }
+            | Payloads::ServerCapabilities { .. }
+            | Payloads::RequiredAircraft { .. }
             | Payloads::Heartbeat
             | Payloads::SetHost
             | Payloads::RendezvousHandshake { .. }
@@ -147,11 +171,27 @@ impl TransferStruct {
             // No processing needed
             Payloads::Update { .. } => {}
             Payloads::Ready => {}
-            Payloads::SetSelfObserver { .. } => {
+            Payloads::ReadyStatus { .. } => {}
+            Payloads::SeatAssigned { name, seat } => {
+                self.assign_seat(name, seat.as_ref());
+            }
+            Payloads::ChatMessage { .. } => {}
+            Payloads::AircraftChanging { name, to } => {
+                if let Some(client) = self.clients.get_mut(name) {
+                    client.aircraft = Some(to.clone());
+                }
+            }
+            Payloads::SetSelfObserver { .. } | Payloads::LeaveSelfObserver { .. } => {
                 should_relay = false;
             }
             // Used
-            Payloads::InitHandshake { name, version } => {
+            Payloads::InitHandshake {
+                name,
+                version,
+                spectator,
+                password,
+                aircraft,
+            } => {
                 // Version check
                 if *version != self.version {
                     self.net
@@ -182,6 +222,48 @@ impl TransferStruct {
                     return;
                 }
 
+                if self.password.is_some() && *password != self.password {
+                    info!("[NETWORK] Rejected {}, wrong password", name);
+                    self.net
+                        .send_message(
+                            Payloads::ConnectionDenied {
+                                reason: "wrong password".to_string(),
+                            },
+                            addr,
+                        )
+                        .ok();
+                    return;
+                }
+
+                if self
+                    .max_clients
+                    .is_some_and(|max| self.clients.len() >= max)
+                {
+                    info!("[NETWORK] Rejected {}, session is full", name);
+                    self.net
+                        .send_message(
+                            Payloads::ConnectionDenied {
+                                reason: "session full".to_string(),
+                            },
+                            addr,
+                        )
+                        .ok();
+                    return;
+                }
+
+                if self.locked {
+                    info!("[NETWORK] Rejected {}, session is locked", name);
+                    self.net
+                        .send_message(
+                            Payloads::ConnectionDenied {
+                                reason: "session locked".to_string(),
+                            },
+                            addr,
+                        )
+                        .ok();
+                    return;
+                }
+
                 // Send all connected clients to new player
                 for (name, client) in self.clients.iter() {
                     self.net
@@ -191,6 +273,9 @@ impl TransferStruct {
                                 in_control: self.in_control == *name,
                                 is_server: false,
                                 is_observer: client.is_observer,
+                                is_spectator: client.is_spectator,
+                                version: Some(client.version.clone()),
+                                aircraft: client.aircraft.clone(),
                             },
                             addr,
                         )
@@ -204,6 +289,32 @@ impl TransferStruct {
                             in_control: self.in_control == self.username,
                             is_server: true,
                             is_observer: false,
+                            is_spectator: false,
+                            version: Some(self.version.clone()),
+                            aircraft: self.aircraft.clone(),
+                        },
+                        addr,
+                    )
+                    .ok();
+                // Send taken seats
+                for (seat, holder) in self.seats.iter() {
+                    self.net
+                        .send_message(
+                            Payloads::SeatAssigned {
+                                name: holder.clone(),
+                                seat: Some(seat.clone()),
+                            },
+                            addr,
+                        )
+                        .ok();
+                }
+                self.net
+                    .send_message(
+                        Payloads::ServerCapabilities {
+                            capabilities: SERVER_CAPABILITIES
+                                .iter()
+                                .map(|capability| capability.to_string())
+                                .collect(),
                         },
                         addr,
                     )
@@ -213,7 +324,10 @@ impl TransferStruct {
                     name.clone(),
                     Client {
                         addr,
-                        is_observer: false,
+                        is_observer: *spectator,
+                        is_spectator: *spectator,
+                        version: version.clone(),
+                        aircraft: aircraft.clone(),
                     },
                 );
 
@@ -223,7 +337,10 @@ impl TransferStruct {
                     name: name.clone(),
                     in_control: false,
                     is_server: false,
-                    is_observer: false,
+                    is_observer: *spectator,
+                    is_spectator: *spectator,
+                    version: Some(version.clone()),
+                    aircraft: aircraft.clone(),
                 };
 
                 self.send_to_all(Some(&addr), empty_new_player.clone());
@@ -235,6 +352,15 @@ impl TransferStruct {
             }
 
             Payloads::TransferControl { from: _, to } => {
+                // Spectators can't be given control
+                if self
+                    .clients
+                    .get(to)
+                    .is_some_and(|client| client.is_spectator)
+                {
+                    info!("[NETWORK] Ignoring transfer of control to spectator {}", to);
+                    return;
+                }
                 self.in_control.clone_from(to);
             }
 
@@ -314,6 +440,12 @@ impl TransferStruct {
             if let Payloads::TransferControl { from: _, to } = &payload {
                 self.in_control.clone_from(to);
             }
+            if let Payloads::SeatAssigned { name, seat } = &payload {
+                self.assign_seat(name, seat.as_ref());
+            }
+            if let Payloads::AircraftChanging { to, .. } = &payload {
+                self.aircraft = Some(to.clone());
+            }
 
             if let Some(target) = target {
                 if let Some(client) = self.clients.get(&target) {
@@ -371,6 +503,7 @@ impl TransferStruct {
         );
 
         if let Some(name) = removed_client_name {
+            self.assign_seat(&name, None);
             let player_left_payload = Payloads::PlayerLeft { name };
 
             self.send_to_all(None, player_left_payload.clone());
@@ -424,6 +557,9 @@ pub struct Server {
     username: String,
     version: String,
     timeout: u64,
+    max_clients: Option<usize>,
+    password: Option<String>,
+    aircraft: Option<String>,
 }
 
 impl Server {
@@ -444,9 +580,27 @@ impl Server {
             username,
             version,
             timeout,
+            max_clients: None,
+            password: None,
+            aircraft: None,
         }
     }
 
+    // Must be called before starting the server
+    pub fn set_max_clients(&mut self, max_clients: Option<usize>) {
+        self.max_clients = max_clients;
+    }
+
+    // Must be called before starting the server
+    pub fn set_password(&mut self, password: Option<String>) {
+        self.password = password;
+    }
+
+    // Must be called before starting the server
+    pub fn set_aircraft(&mut self, aircraft: Option<String>) {
+        self.aircraft = aircraft;
+    }
+
     fn port_forward(&self, port: u16) -> Result<(), Error> {
         let local_addr: Ipv4Addr = match get_local_ip_address(false) {
             Some(IpAddr::V4(ip)) => ip,
@@ -484,7 +638,7 @@ impl Server {
 
     pub fn start(&mut self, is_ipv6: bool, port: u16, upnp: bool) -> Result<(), Error> {
         let socket =
-            Socket::from_udp_socket(get_socket_duplex(port), get_socket_config(self.timeout))?;
+            Socket::from_udp_socket(get_socket_duplex(port)?, get_socket_config(self.timeout))?;
         // Attempt to port forward
         if upnp && !is_ipv6 {
             self.last_port_forward_result = Some(self.port_forward(port));
@@ -493,6 +647,10 @@ impl Server {
         self.run(socket, None)
     }
 
+    pub fn get_last_port_forward_result(&self) -> Option<&Result<(), Error>> {
+        self.last_port_forward_result.as_ref()
+    }
+
     pub fn start_with_hole_punching(&mut self, is_ipv6: bool) -> Result<(), Error> {
         let socket = Socket::bind_with_config(
             get_bind_address(is_ipv6, None),
@@ -520,11 +678,16 @@ impl Server {
             net: SenderReceiver::from_socket(socket),
             // State
             in_control: self.username.clone(),
+            seats: HashMap::new(),
+            locked: false,
+            max_clients: self.max_clients,
+            password: self.password.clone(),
             clients: HashMap::new(),
             should_stop: self.should_stop.clone(),
             number_connections: self.number_connections.clone(),
             username: self.username.clone(),
             version: self.version.clone(),
+            aircraft: self.aircraft.clone(),
             heartbeat_instant: Instant::now(),
             metrics_instant: Instant::now(),
             metrics: HashMap::new(),
@@ -675,6 +838,14 @@ impl TransferClient for Server {
         None
     }
 
+    fn set_session_locked(&self, locked: bool) -> bool {
+        if let Some(transfer) = self.transfer.as_ref() {
+            transfer.lock().unwrap().locked = locked;
+            return true;
+        }
+        false
+    }
+
     fn stop(&mut self, reason: String) {
         self.should_stop.store(true, SeqCst);
         self.server_tx
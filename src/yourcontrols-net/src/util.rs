@@ -19,6 +19,21 @@ pub const MAX_PUNCH_RETRIES: u8 = 5;
 pub const LOOP_SLEEP_TIME_MS: u64 = 5;
 pub const HEARTBEAT_INTERVAL_MANUAL_SECS: f32 = 0.5;
 
+// Features a server can advertise with Payloads::ServerCapabilities
+pub const CAPABILITY_CHAT: &str = "chat";
+pub const CAPABILITY_SPECTATORS: &str = "spectators";
+pub const CAPABILITY_SESSION_LOCK: &str = "session_lock";
+pub const CAPABILITY_SEATS: &str = "seats";
+// Everything a directly hosted Server supports
+pub const SERVER_CAPABILITIES: &[&str] = &[
+    CAPABILITY_CHAT,
+    CAPABILITY_SPECTATORS,
+    CAPABILITY_SESSION_LOCK,
+    CAPABILITY_SEATS,
+];
+// Cloud relays don't handle session locking or seats, which need a direct host
+pub const RELAY_CAPABILITIES: &[&str] = &[CAPABILITY_CHAT, CAPABILITY_SPECTATORS];
+
 const HEARTBEAT_INTERVAL_MS: u64 = 1000;
 const RENDEZVOUS_SERVER_HOSTNAME: &str = dotenv!("SERVER_HOSTNAME");
 const RENDEZVOUS_PORT: &str = dotenv!("SERVER_PORT");
@@ -67,6 +82,10 @@ pub fn get_rendezvous_server(is_ipv6: bool) -> Result<SocketAddr, Error> {
     )
 }
 
+pub fn get_rendezvous_hostname() -> &'static str {
+    RENDEZVOUS_SERVER_HOSTNAME
+}
+
 pub fn get_socket_config(timeout: u64) -> laminar::Config {
     laminar::Config {
         heartbeat_interval: Some(Duration::from_millis(HEARTBEAT_INTERVAL_MS)),
@@ -78,18 +97,23 @@ pub fn get_socket_config(timeout: u64) -> laminar::Config {
     }
 }
 
-pub fn get_socket_duplex(port: u16) -> UdpSocket {
-    let socket = Socket::new(Domain::IPV6, Type::DGRAM, None).unwrap();
+pub fn get_socket_duplex(port: u16) -> Result<UdpSocket, Error> {
+    let socket = Socket::new(Domain::IPV6, Type::DGRAM, None)?;
     socket.set_only_v6(false).ok();
+
+    let addr = get_bind_address(true, Some(port));
     socket
-        .bind(
-            &format!("[::]:{}", port)
-                .parse::<SocketAddr>()
-                .unwrap()
-                .into(),
-        )
-        .unwrap();
-    socket.into()
+        .bind(&addr.into())
+        .map_err(|e| Error::BindFailed(addr, e))?;
+
+    Ok(socket.into())
+}
+
+/// Next port after `port` that can currently be bound, for suggesting an alternative when it's taken.
+/// Binds the same dual stack socket a server would
+pub fn find_free_port(port: u16) -> Option<u16> {
+    (port.saturating_add(1)..=port.saturating_add(20))
+        .find(|candidate| get_socket_duplex(*candidate).is_ok())
 }
 
 pub fn get_seconds() -> f64 {
@@ -155,6 +179,11 @@ pub trait TransferClient {
     // Application specific functions
     fn stop(&mut self, reason: String);
 
+    // Returns false if this side can't enforce a lock
+    fn set_session_locked(&self, _locked: bool) -> bool {
+        false
+    }
+
     fn update(&self, data: AllNeedSync, is_unreliable: bool) {
         self.get_transmitter()
             .try_send((
@@ -211,6 +240,17 @@ pub trait TransferClient {
             .ok();
     }
 
+    fn leave_self_observer(&self) {
+        self.get_transmitter()
+            .try_send((
+                Payloads::LeaveSelfObserver {
+                    name: self.get_server_name().to_string(),
+                },
+                None,
+            ))
+            .ok();
+    }
+
     fn set_observer(&self, target: String, is_observer: bool) {
         self.get_transmitter()
             .try_send((
@@ -224,6 +264,58 @@ pub trait TransferClient {
             .ok();
     }
 
+    fn assign_seat(&self, name: String, seat: Option<String>) {
+        let message = Payloads::SeatAssigned { name, seat };
+        self.get_transmitter()
+            .try_send((message.clone(), None))
+            .ok();
+        self.get_server_transmitter()
+            .try_send(ReceiveMessage::Payload(message))
+            .ok();
+    }
+
+    fn set_ready_status(&self, is_ready: bool) {
+        self.get_transmitter()
+            .try_send((
+                Payloads::ReadyStatus {
+                    name: self.get_server_name().to_string(),
+                    is_ready,
+                },
+                None,
+            ))
+            .ok();
+    }
+
+    fn send_chat_message(&self, text: String) {
+        self.get_transmitter()
+            .try_send((
+                Payloads::ChatMessage {
+                    from: self.get_server_name().to_string(),
+                    text,
+                },
+                None,
+            ))
+            .ok();
+    }
+
+    fn send_aircraft_changing(&self, to: String) {
+        self.get_transmitter()
+            .try_send((
+                Payloads::AircraftChanging {
+                    name: self.get_server_name().to_string(),
+                    to,
+                },
+                None,
+            ))
+            .ok();
+    }
+
+    fn send_required_aircraft(&self, name: String, target: Option<String>) {
+        self.get_transmitter()
+            .try_send((Payloads::RequiredAircraft { name }, target))
+            .ok();
+    }
+
     fn send_ready(&self) {
         self.get_transmitter()
             .try_send((Payloads::Ready, None))
@@ -8,7 +8,9 @@ use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 use std::thread::sleep;
 use std::time::{Duration, Instant};
-use yourcontrols_net::{get_socket_config, get_socket_duplex, Message, Payloads, SenderReceiver};
+use yourcontrols_net::{
+    get_socket_config, get_socket_duplex, Message, Payloads, SenderReceiver, RELAY_CAPABILITIES,
+};
 use yourcontrols_types::Error;
 
 pub const SERVER_NAME: &str = "SERVER";
@@ -56,6 +58,7 @@ fn process_payload(
         | Payloads::RequestHosting { .. }
         | Payloads::PeerEstablished { .. }
         | Payloads::ConnectionDenied { .. }
+        | Payloads::ServerCapabilities { .. }
         | Payloads::Heartbeat
         | Payloads::PlayerLeft { .. } => return,
         // Used
@@ -63,8 +66,26 @@ fn process_payload(
             state.aircraft_definition = Some(bytes.clone());
             return;
         }
-        Payloads::Update { .. } => {}
-        Payloads::InitHandshake { name, version } => {
+        Payloads::RequiredAircraft { name } => {
+            state.required_aircraft = Some(name.clone());
+        }
+        Payloads::Update { .. }
+        | Payloads::ReadyStatus { .. }
+        | Payloads::SeatAssigned { .. }
+        | Payloads::ChatMessage { .. } => {}
+        Payloads::AircraftChanging { name, to } => {
+            if let Some(client) = state.clients.get_mut(name) {
+                client.aircraft = Some(to.clone());
+            }
+        }
+        // Passwords are only checked by directly hosted servers
+        Payloads::InitHandshake {
+            name,
+            version,
+            spectator,
+            aircraft,
+            ..
+        } => {
             if let Ok(version) = Version::from_str(version) {
                 let server_version =
                     Version::from_str(&dotenv::var("MINIMUM_VERSION").unwrap()).unwrap();
@@ -94,14 +115,33 @@ fn process_payload(
                         in_control: state.in_control == *name,
                         is_server: info.is_host,
                         is_observer: info.is_observer,
+                        is_spectator: info.is_spectator,
+                        version: info.version.clone(),
+                        aircraft: info.aircraft.clone(),
                     },
                     addr,
                 )
                 .ok();
             }
 
+            net.send_message(
+                Payloads::ServerCapabilities {
+                    capabilities: RELAY_CAPABILITIES
+                        .iter()
+                        .map(|capability| capability.to_string())
+                        .collect(),
+                },
+                addr,
+            )
+            .ok();
+
             // Add client
-            state.clients.insert(name.clone(), Client::new(addr));
+            let mut client = Client::new(addr);
+            client.is_observer = *spectator;
+            client.is_spectator = *spectator;
+            client.version = Some(version.clone());
+            client.aircraft.clone_from(aircraft);
+            state.clients.insert(name.clone(), client);
 
             // If the client is the first one to connect, give them control and have them "host"
             if state.in_control == SERVER_NAME {
@@ -113,7 +153,10 @@ fn process_payload(
                     name: name.clone(),
                     in_control: false,
                     is_server: false,
-                    is_observer: false,
+                    is_observer: *spectator,
+                    is_spectator: *spectator,
+                    version: Some(version.clone()),
+                    aircraft: aircraft.clone(),
                 },
                 Some(&addr),
                 state,
@@ -131,11 +174,29 @@ fn process_payload(
                 .ok();
             }
 
+            if let Some(aircraft) = state.required_aircraft.as_ref() {
+                net.send_message(
+                    Payloads::RequiredAircraft {
+                        name: aircraft.clone(),
+                    },
+                    addr,
+                )
+                .ok();
+            }
+
             info!("{} connected to hoster.", name);
 
             return;
         }
         Payloads::TransferControl { from: _, to } => {
+            // Spectators can't be given control
+            if state
+                .clients
+                .get(to)
+                .is_some_and(|client| client.is_spectator)
+            {
+                return;
+            }
             state.in_control.clone_from(to);
         }
         Payloads::SetObserver {
@@ -144,7 +205,7 @@ fn process_payload(
             is_observer,
         } => {
             if let Some(client) = state.clients.get_mut(to) {
-                client.is_observer = *is_observer;
+                client.is_observer = *is_observer || client.is_spectator;
             }
         }
         Payloads::SetSelfObserver { name } => {
@@ -163,6 +224,26 @@ fn process_payload(
             }
             return;
         }
+        Payloads::LeaveSelfObserver { name } => {
+            if let Some(client) = state.clients.get_mut(name) {
+                // Spectators joined to watch and can't start flying
+                if client.is_spectator {
+                    return;
+                }
+                client.is_observer = false;
+                send_to_all(
+                    Payloads::SetObserver {
+                        from: "SERVER".to_string(),
+                        to: name.clone(),
+                        is_observer: false,
+                    },
+                    None,
+                    state,
+                    net,
+                );
+            }
+            return;
+        }
         Payloads::Ready => {
             // Tell "host" to do a full sync
             if let Some(client) = state.clients.get(&state.in_control) {
@@ -232,8 +313,9 @@ fn cleanup(servers: &mut Servers) {
 }
 
 pub fn run_hoster(servers: Arc<Mutex<Servers>>, port: u16) {
-    let socket = Socket::from_udp_socket(get_socket_duplex(port), get_socket_config(5))
-        .expect("Failed to bind!");
+    let udp_socket = get_socket_duplex(port).expect("Failed to bind!");
+    let socket = Socket::from_udp_socket(udp_socket, get_socket_config(5))
+        .expect("Failed to create socket!");
 
     info!(
         "Hoster started on {}! Connect hostname {}",
@@ -200,8 +200,9 @@ fn process_message(
 }
 
 pub fn run_rendezvous(servers: Arc<Mutex<Servers>>, port: u16) {
-    let socket = Socket::from_udp_socket(get_socket_duplex(port), get_socket_config(3))
-        .expect("Failed to bind!");
+    let udp_socket = get_socket_duplex(port).expect("Failed to bind!");
+    let socket = Socket::from_udp_socket(udp_socket, get_socket_config(3))
+        .expect("Failed to create socket!");
     info!("Server started on {}!", socket.local_addr().unwrap());
 
     let mut net = SenderReceiver::from_socket(socket);
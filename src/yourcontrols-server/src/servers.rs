@@ -10,6 +10,10 @@ pub struct Client {
     pub addr: SocketAddr,
     pub is_observer: bool,
     pub is_host: bool,
+    // Joined to watch only, never takes control
+    pub is_spectator: bool,
+    pub version: Option<String>,
+    pub aircraft: Option<String>,
 }
 
 impl Client {
@@ -18,6 +22,9 @@ impl Client {
             addr,
             is_observer: false,
             is_host: false,
+            is_spectator: false,
+            version: None,
+            aircraft: None,
         }
     }
 }
@@ -25,6 +32,8 @@ impl Client {
 pub struct ServerState {
     pub clients: HashMap<String, Client>,
     pub aircraft_definition: Option<Box<[u8]>>,
+    // Host's aircraft, told to everyone who joins
+    pub required_aircraft: Option<String>,
     pub in_control: String,
     pub heartbeat_instant: Instant,
     pub started_at: Instant,
@@ -36,6 +45,7 @@ impl ServerState {
             clients: HashMap::new(),
             in_control: "SERVER".to_string(),
             aircraft_definition: None,
+            required_aircraft: None,
             heartbeat_instant: Instant::now(),
             started_at: Instant::now(),
         }
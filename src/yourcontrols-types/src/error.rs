@@ -1,4 +1,4 @@
-use std::{fmt::Display, io};
+use std::{fmt::Display, io, net::SocketAddr};
 
 use crossbeam_channel::TryRecvError;
 
@@ -6,6 +6,7 @@ use crossbeam_channel::TryRecvError;
 pub enum Error {
     // Net
     IOError(io::Error),
+    BindFailed(SocketAddr, io::Error),
     MismatchingIpVersion,
 
     SocketError(laminar::ErrorKind),
@@ -42,6 +43,7 @@ impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Error::IOError(e) => write!(f, "An IO error occured: {}", e),
+            Error::BindFailed(addr, e) => write!(f, "Couldn't bind {}: {}", addr, e),
             Error::MismatchingIpVersion => {
                 write!(f, "No hostname IPs matched the requested IP version.")
             }
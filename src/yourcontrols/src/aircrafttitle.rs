@@ -0,0 +1,87 @@
+use simconnect::SimConnector;
+
+const TITLE_LENGTH: usize = 256;
+
+// Reads the TITLE of the aircraft loaded in the sim, so the UI can tell when the selected definitions are for a different plane
+pub struct AircraftTitle {
+    define_id: u32,
+    request_id: u32,
+    current: Option<String>,
+}
+
+impl AircraftTitle {
+    pub fn new(define_id: u32, request_id: u32) -> Self {
+        Self {
+            define_id,
+            request_id,
+            current: None,
+        }
+    }
+
+    pub fn on_connected(&mut self, conn: &SimConnector) {
+        self.current = None;
+
+        conn.clear_data_definition(self.define_id);
+        conn.add_data_definition(
+            self.define_id,
+            "TITLE",
+            "",
+            simconnect::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING256,
+            u32::MAX,
+            0.0,
+        );
+        // Changes when the user loads a different aircraft
+        conn.request_data_on_sim_object(
+            self.request_id,
+            self.define_id,
+            0,
+            simconnect::SIMCONNECT_PERIOD_SIMCONNECT_PERIOD_SECOND,
+            simconnect::SIMCONNECT_CLIENT_DATA_REQUEST_FLAG_CHANGED,
+            0,
+            0,
+            0,
+        );
+    }
+
+    /// Returns the title if it is new
+    pub fn process_sim_object_data(
+        &mut self,
+        data: &simconnect::SIMCONNECT_RECV_SIMOBJECT_DATA,
+    ) -> Option<&str> {
+        if self.define_id != data.dwDefineID {
+            return None;
+        }
+
+        let bytes = unsafe {
+            std::slice::from_raw_parts(std::ptr::addr_of!(data.dwData) as *const u8, TITLE_LENGTH)
+        };
+        let title = parse_title(bytes);
+
+        if self.current.as_ref() == Some(&title) {
+            return None;
+        }
+
+        self.current = Some(title);
+        self.current.as_deref()
+    }
+}
+
+fn parse_title(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_title() {
+        let mut bytes = [0u8; TITLE_LENGTH];
+        bytes[..26].copy_from_slice(b"Cessna Skyhawk G1000 Asobo");
+        assert_eq!(parse_title(&bytes), "Cessna Skyhawk G1000 Asobo");
+
+        assert_eq!(parse_title(&[0u8; 4]), "");
+        assert_eq!(parse_title(b"No terminator "), "No terminator");
+    }
+}
@@ -1,9 +1,12 @@
 use anyhow::{bail, Result};
 use rodio::{Decoder, OutputStream, OutputStreamHandle, Source};
-use std::{fs::File, io::BufReader};
+use std::{collections::HashSet, fs::File, io::BufReader};
+
+use crate::simconfig::SoundEvent;
 
 pub struct AudioManager {
     volume: f32,
+    events: HashSet<SoundEvent>,
     stream: Option<OutputStream>,
     handle: Option<OutputStreamHandle>,
 }
@@ -12,6 +15,7 @@ impl AudioManager {
     pub fn new() -> Self {
         Self {
             volume: 0.75,
+            events: HashSet::new(),
             stream: None,
             handle: None,
         }
@@ -25,22 +29,67 @@ impl AudioManager {
     }
 
     fn play_file(&self, path: &str) -> Result<()> {
-        if let Some(handle) = &self.handle {
-            return Ok(handle.play_raw(
-                Decoder::new(BufReader::new(File::open(path)?))?
-                    .amplify(self.volume)
-                    .convert_samples(),
-            )?);
-        }
+        // Decoded first so a bad file is reported the same with or without an output device
+        let source = Decoder::new(BufReader::new(File::open(path)?))?;
 
-        bail!("No audio stream available")
+        match &self.handle {
+            Some(handle) => Ok(handle.play_raw(source.amplify(self.volume).convert_samples())?),
+            None => bail!("No audio stream available"),
+        }
     }
 
     pub fn mute(&mut self, muted: bool) {
         self.volume = if muted { 0.0 } else { 0.75 };
     }
 
-    pub fn play_disconnected(&self) -> Result<()> {
+    pub fn set_events(&mut self, events: HashSet<SoundEvent>) {
+        self.events = events;
+    }
+
+    /// Plays the sound for an event, doing nothing if muted or the event is turned off
+    pub fn play_sound(&self, event: SoundEvent) -> Result<()> {
+        if self.volume == 0.0 || !self.events.contains(&event) {
+            return Ok(());
+        }
+
+        // Only one sound ships for now
         self.play_file("assets/disconnected.mp3")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_play_sound_respects_settings() {
+        let mut audio = AudioManager::new();
+        audio.set_events([SoundEvent::Error].iter().copied().collect());
+
+        // Skipped events never open the file or an output stream
+        assert!(audio.play_sound(SoundEvent::NewConnection).is_ok());
+
+        audio.mute(true);
+        assert!(audio.play_sound(SoundEvent::Error).is_ok());
+    }
+
+    #[test]
+    fn test_play_file_errors() {
+        let audio = AudioManager::new();
+
+        let missing = audio.play_file("assets/missing.mp3").unwrap_err();
+        assert_eq!(
+            missing.downcast_ref::<std::io::Error>().map(|e| e.kind()),
+            Some(std::io::ErrorKind::NotFound)
+        );
+
+        let path =
+            std::env::temp_dir().join(format!("yourcontrols_invalid_{}.mp3", std::process::id()));
+        std::fs::write(&path, b"not audio").unwrap();
+        let invalid = audio.play_file(path.to_str().unwrap()).unwrap_err();
+        std::fs::remove_file(&path).ok();
+        assert!(invalid
+            .downcast_ref::<rodio::decoder::DecoderError>()
+            .is_some());
+    }
+}
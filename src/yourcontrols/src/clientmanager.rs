@@ -4,6 +4,7 @@ use std::collections::HashMap;
 pub struct Client {
     pub observer_mode: bool,
     pub is_server: bool,
+    pub is_spectator: bool,
 }
 
 pub struct ClientManager {
@@ -61,15 +62,47 @@ impl ClientManager {
 
         if let Some(next_control) = self.next_control.as_ref() {
             if next_control == name {
-                self.next_control = self.clients.keys().next().cloned();
+                self.next_control = self.first_controllable_client();
             }
         }
     }
 
+    fn first_controllable_client(&self) -> Option<String> {
+        self.clients
+            .iter()
+            .find(|(_, client)| !client.is_spectator)
+            .map(|(name, _)| name.clone())
+    }
+
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    pub fn has_client(&self, name: &str) -> bool {
+        self.clients.contains_key(name)
+    }
+
     pub fn get_next_client_for_control(&self) -> Option<&String> {
         self.next_control.as_ref()
     }
 
+    // Who gets control after `current` when rotating, in name order so everyone flying gets a turn
+    pub fn next_in_rotation(&self, current: &str) -> Option<&String> {
+        let mut names: Vec<&String> = self
+            .clients
+            .iter()
+            .filter(|(_, client)| !client.observer_mode && !client.is_spectator)
+            .map(|(name, _)| name)
+            .collect();
+        names.sort();
+
+        names
+            .iter()
+            .find(|name| name.as_str() > current)
+            .or_else(|| names.first())
+            .copied()
+    }
+
     pub fn is_observer(&self, name: &str) -> bool {
         if let Some(client) = self.clients.get(name) {
             return client.observer_mode;
@@ -83,6 +116,25 @@ impl ClientManager {
         }
     }
 
+    // Spectators never get control, so they can't be next in line either
+    pub fn set_spectator(&mut self, name: &str) {
+        if let Some(client) = self.clients.get_mut(name) {
+            client.is_spectator = true;
+            client.observer_mode = true;
+        }
+
+        if self.next_control.as_deref() == Some(name) {
+            self.next_control = self.first_controllable_client();
+        }
+    }
+
+    pub fn is_spectator(&self, name: &str) -> bool {
+        if let Some(client) = self.clients.get(name) {
+            return client.is_spectator;
+        }
+        false
+    }
+
     pub fn set_server(&mut self, name: &str, is_server: bool) {
         if let Some(client) = self.clients.get_mut(name) {
             client.is_server = is_server;
@@ -101,3 +153,24 @@ impl ClientManager {
         self.current_control = None;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_in_rotation() {
+        let mut clients = ClientManager::new();
+        assert_eq!(clients.next_in_rotation("Bob"), None);
+
+        for name in ["Dave", "Alice", "Carol", "Eve"] {
+            clients.add_client(name.to_string());
+        }
+        clients.set_observer("Carol", true);
+        clients.set_spectator("Eve");
+
+        assert_eq!(clients.next_in_rotation("Bob").unwrap(), "Dave");
+        // Wraps around after the last name
+        assert_eq!(clients.next_in_rotation("Dave").unwrap(), "Alice");
+    }
+}
@@ -7,7 +7,7 @@ use std::fmt::{Debug, Display};
 use std::fs::File;
 use std::mem::swap;
 use std::path::Path;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use crate::sync::gaugecommunicator::{GetResult, InterpolateData, InterpolationType};
 use crate::sync::jscommunicator::{JSCommunicator, JSPayloads};
@@ -22,6 +22,11 @@ use crate::{corrector::Corrector, syncdefs::LocalVarProxy};
 
 use yourcontrols_types::{AllNeedSync, Error, Event, EventData, VarMap, VarReaderTypes};
 
+// How far a local value can be from the received one and still count as synced
+const DESYNC_TOLERANCE: f64 = 0.01;
+// Time a written var gets to reach the sim before it can count as desynced
+const DESYNC_SETTLE_TIME: Duration = Duration::from_secs(3);
+
 // Checks if a field in a Value exists, otherwise will return an error with the name of the field
 macro_rules! check_and_return_field {
     ($field_name:expr, $var:ident, str) => {
@@ -487,6 +492,8 @@ pub struct Definitions {
     interpolate_vars: HashSet<String>,
     // For indicating that an event has been triggered and the control should be transferred to the next person
     pending_action: Option<ProgramAction>,
+    // Known sync limitations listed by the definition files
+    caveats: Vec<String>,
 }
 
 fn get_category_from_string(category: &str) -> Result<Category, Error> {
@@ -534,6 +541,7 @@ impl Definitions {
             interpolate_vars: HashSet::new(),
 
             pending_action: None,
+            caveats: Vec::new(),
         }
     }
 
@@ -1172,6 +1180,13 @@ impl Definitions {
                         }
                     }
                 }
+            } else if key == "caveats" {
+                for caveat in value {
+                    if let Some(text) = caveat.as_str() {
+                        self.caveats.push(text.to_string());
+                    }
+                    self.add_to_buffer(key.clone(), caveat);
+                }
             } else if key == "ignore" {
                 for ignore_value in value {
                     self.do_not_sync
@@ -1203,6 +1218,33 @@ impl Definitions {
         self.parse_yaml(yaml)
     }
 
+    // Reads only the caveats of a definition file and its includes, without mapping anything
+    pub fn read_caveats(path: impl AsRef<Path> + Display) -> Result<Vec<String>, Error> {
+        let path_string = path.to_string();
+
+        let file = File::open(path).map_err(Error::IOError)?;
+
+        let yaml: IndexMap<String, Vec<Value>> =
+            serde_yaml::from_reader(file).map_err(|e| Error::YamlError(e, path_string))?;
+
+        let mut caveats = Vec::new();
+        for (key, value) in yaml {
+            if key == "include" {
+                for include_file in value.iter().filter_map(Value::as_str) {
+                    caveats.extend(Self::read_caveats(include_file)?);
+                }
+            } else if key == "caveats" {
+                caveats.extend(value.iter().filter_map(Value::as_str).map(str::to_string));
+            }
+        }
+
+        Ok(caveats)
+    }
+
+    pub fn get_caveats(&self) -> &[String] {
+        &self.caveats
+    }
+
     pub fn load_config_from_bytes(&mut self, bytes: Box<[u8]>) -> Result<(), Error> {
         let yaml: IndexMap<String, Vec<Value>> = rmp_serde::from_slice(&bytes)?;
 
@@ -1655,6 +1697,12 @@ impl Definitions {
         Ok(())
     }
 
+    // Tells the gauge how much longer to spread interpolated values over
+    pub fn set_smoothing(&mut self, conn: &SimConnector, ms: u32) {
+        self.lvarstransfer
+            .set(conn, "L:YourControlsSmoothing", &ms.to_string());
+    }
+
     // To be called when SimConnect connects
     pub fn on_connected(&mut self, conn: &SimConnector) -> Result<(), ()> {
         self.avarstransfer.on_connected(conn);
@@ -1710,6 +1758,60 @@ impl Definitions {
         }
     }
 
+    // Remembers the values a sender last sent for the vars we take from them, to compare against later
+    pub fn record_expected(
+        &self,
+        expected: &mut AllNeedSync,
+        data: &AllNeedSync,
+        sync_permission: &SyncPermission,
+    ) {
+        let checked = |name: &String| {
+            self.can_sync(name, sync_permission)
+                && !self.interpolate_vars.contains(name)
+                && !self.unreliable_vars.contains(name)
+                && !self.do_not_sync.contains(name)
+        };
+
+        for (name, value) in data.avars.iter().filter(|(name, _)| checked(name)) {
+            expected.avars.insert(name.clone(), *value);
+        }
+        for (name, value) in data.lvars.iter().filter(|(name, _)| checked(name)) {
+            expected.lvars.insert(name.clone(), *value);
+        }
+    }
+
+    // Vars still different locally from what a sender last sent, once they've had time to be written
+    pub fn desynced_vars(&self, expected: &AllNeedSync) -> Vec<String> {
+        let current = self.get_all_current();
+        let differs = |local: Option<&VarReaderTypes>, value: &VarReaderTypes| {
+            local.map_or(false, |local| {
+                (local.get_as_f64() - value.get_as_f64()).abs() > DESYNC_TOLERANCE
+            })
+        };
+        let settled = |name: &String| {
+            self.last_written
+                .get(name)
+                .map_or(true, |written| written.elapsed() >= DESYNC_SETTLE_TIME)
+        };
+
+        let mut vars: Vec<String> = expected
+            .avars
+            .iter()
+            .filter(|(name, value)| differs(current.avars.get(*name), value))
+            .chain(
+                expected
+                    .lvars
+                    .iter()
+                    .filter(|(name, value)| differs(current.lvars.get(*name), value)),
+            )
+            .map(|(name, _)| name)
+            .filter(|name| settled(name))
+            .cloned()
+            .collect();
+        vars.sort();
+        vars
+    }
+
     pub fn reset_sync(&mut self) {
         self.current_sync.clear();
         self.last_written.clear();
@@ -1,6 +1,7 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 #![allow(non_snake_case)]
 
+mod aircrafttitle;
 mod audio;
 mod clientmanager;
 mod corrector;
@@ -13,29 +14,39 @@ mod update;
 mod util;
 mod varreader;
 
+use aircrafttitle::AircraftTitle;
 use audio::AudioManager;
 use clientmanager::ClientManager;
 use definitions::{Definitions, ProgramAction, SyncPermission};
 use log::{error, info, warn};
-use simconfig::Config;
+use simconfig::{Config, SoundEvent, MAX_SMOOTHING_MS, MAX_UPDATE_RATE, MIN_UPDATE_RATE};
 use simconnect::{DispatchResult, SimConnector};
-use ui::{ActiveBackend, AppMessage, ConnectionMethod, UIBackend};
+use ui::deeplink::DEEPLINK_SCHEME;
+use ui::recording::RecordingBackend;
+use ui::{AppBackend, AppMessage, ConnectionMethod, UIBackend};
 
 use spin_sleep::sleep;
 use std::{
+    collections::HashMap,
     env,
     fs::{read_dir, File},
-    io,
-    net::IpAddr,
-    path::PathBuf,
+    io, mem,
+    net::{IpAddr, SocketAddr},
+    path::{Path, PathBuf},
     time::Duration,
     time::Instant,
 };
-use update::Updater;
-use yourcontrols_net::{Client, Event, Payloads, ReceiveMessage, Server, TransferClient};
-use yourcontrols_types::AllNeedSync;
+use update::{Updater, UPDATED_ARG};
+use yourcontrols_net::{
+    find_free_port, get_rendezvous_hostname, get_rendezvous_server, get_seconds, Client, Event,
+    Payloads, ReceiveMessage, Server, TransferClient, SERVER_CAPABILITIES,
+};
+use yourcontrols_types::{AllNeedSync, Error};
 
-use crate::util::get_hostname_ip;
+use crate::util::{
+    aircraft_matches_config, get_hostname_ip, get_position, get_public_ip, probe_udp,
+    sanitize_text, show_error_box, sync_value_pairs,
+};
 
 use control::*;
 use sync::*;
@@ -44,6 +55,34 @@ const LOG_FILENAME: &str = "log.txt";
 const CONFIG_FILENAME: &str = "config.json";
 const AIRCRAFT_DEFINITIONS_PATH: &str = "definitions/aircraft/";
 
+const MAX_CHAT_LENGTH: usize = 200;
+
+// How long Test Connection waits for the host to answer or refuse
+const CONNECTION_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+// Packet loss hysteresis for sending updates less often
+const REDUCED_QUALITY_LOSS: f64 = 0.1;
+const RECOVERED_QUALITY_LOSS: f64 = 0.05;
+const REDUCED_SYNC_INTERVAL: Duration = Duration::from_millis(100);
+// Waited after joining before asking for the full state, lets lvars be processed
+const CONNECTION_DELAY: Duration = Duration::from_secs(3);
+// How often to check whether the sim is running while not in a session
+const SIM_PROBE_INTERVAL: Duration = Duration::from_secs(2);
+// How often other aircraft positions are passed on to the minimap
+const POSITION_INTERVAL: Duration = Duration::from_millis(250);
+// How often the synced variable values are shown in the inspector
+const SYNC_VALUES_INTERVAL: Duration = Duration::from_millis(500);
+// How often control inputs are reported to the UI for idle detection
+const ACTIVITY_INTERVAL: Duration = Duration::from_secs(5);
+// How long before a rotation hands over control the countdown is shown
+const CONTROL_ROTATION_COUNTDOWN: Duration = Duration::from_secs(10);
+// How often what senders sent is compared against the sim to find desynced vars
+const DESYNC_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+// How long the controller can go without sending updates before being flagged as stalled
+const CONTROLLER_STALL_TIMEOUT: Duration = Duration::from_secs(10);
+// How often each client's clock offset is reported to the UI
+const CLOCK_OFFSET_INTERVAL: Duration = Duration::from_secs(2);
+
 const LOOP_SLEEP_TIME: Duration = Duration::from_millis(10);
 
 fn get_aircraft_configs() -> io::Result<Vec<String>> {
@@ -61,6 +100,8 @@ fn get_aircraft_configs() -> io::Result<Vec<String>> {
         )
     }
 
+    filenames.sort();
+
     Ok(filenames)
 }
 
@@ -85,8 +126,14 @@ fn start_client(
     hostname: Option<String>,
     port: Option<u16>,
     method: ConnectionMethod,
+    spectator: bool,
+    password: Option<String>,
+    aircraft: Option<String>,
 ) -> Result<Client, String> {
     let mut client = Client::new(username, version, timeout);
+    client.set_spectator(spectator);
+    client.set_password(password);
+    client.set_aircraft(aircraft);
 
     let client_result = match method {
         ConnectionMethod::Direct => {
@@ -112,6 +159,98 @@ fn start_client(
     }
 }
 
+// Resolves where a connect would go and probes it, returning whether it looks reachable
+fn test_connection(
+    isipv6: bool,
+    ip: Option<IpAddr>,
+    hostname: Option<String>,
+    port: Option<u16>,
+    method: ConnectionMethod,
+) -> (bool, String) {
+    let target = match method {
+        ConnectionMethod::Direct => {
+            let ip = match hostname {
+                Some(hostname) => get_hostname_ip(&hostname, isipv6).map_err(|e| e.to_string()),
+                None => ip.ok_or_else(|| "No IP address entered".to_string()),
+            };
+            ip.and_then(|ip| {
+                port.map(|port| SocketAddr::new(ip, port))
+                    .ok_or_else(|| "No port entered".to_string())
+            })
+        }
+        // Both go through the cloud server first
+        ConnectionMethod::CloudServer | ConnectionMethod::Relay => {
+            get_rendezvous_server(isipv6).map_err(|e| e.to_string())
+        }
+    };
+
+    let target = match target {
+        Ok(target) => target,
+        Err(e) => return (false, format!("Couldn't resolve address: {}", e)),
+    };
+
+    match probe_udp(target, CONNECTION_PROBE_TIMEOUT) {
+        Ok(true) => (true, format!("{} answered", target)),
+        // A session host doesn't answer a bare probe, so silence proves nothing either way
+        Ok(false) => (
+            false,
+            format!("{} resolved, but it's unverified: nothing answered", target),
+        ),
+        Err(e) => (false, format!("Couldn't reach {}: {}", target, e)),
+    }
+}
+
+// Name other clients know an aircraft config by, without the folder or extension
+fn aircraft_name(config_file_name: &str) -> String {
+    Path::new(config_file_name)
+        .file_stem()
+        .map_or(config_file_name.to_string(), |stem| {
+            stem.to_string_lossy().to_string()
+        })
+}
+
+// Aircraft to announce when joining or hosting, None before one is loaded
+fn loaded_aircraft(config_to_load: &str) -> Option<String> {
+    if config_to_load.is_empty() {
+        None
+    } else {
+        Some(aircraft_name(config_to_load))
+    }
+}
+
+// Caveats to show for a selected definition, a broken file is reported when it's loaded
+fn read_sync_caveats(path: PathBuf) -> Vec<String> {
+    Definitions::read_caveats(path.to_string_lossy().to_string()).unwrap_or_else(|e| {
+        warn!(
+            "[DEFINITIONS] Could not read caveats from {}: {}",
+            path.display(),
+            e
+        );
+        Vec::new()
+    })
+}
+
+// Shows the sim's aircraft, warning when there's no definition for it
+fn report_detected_aircraft(app_interface: &impl UIBackend, title: &str) {
+    app_interface.set_detected_aircraft(title);
+
+    let supported = get_aircraft_configs().map_or(true, |configs| {
+        configs
+            .iter()
+            .any(|config| aircraft_matches_config(title, config))
+    });
+    if !supported {
+        warn!("[SIM] No aircraft definition found for {}", title);
+        app_interface.unsupported_aircraft(title);
+    }
+}
+
+fn play_sound(audio: &AudioManager, event: SoundEvent) {
+    if let Err(e) = audio.play_sound(event) {
+        warn!("[AUDIO] Error playing audio: {}", e);
+    }
+}
+
 fn write_update_data(
     data: (Option<AllNeedSync>, Option<AllNeedSync>),
     client: &mut Box<dyn TransferClient>,
@@ -133,6 +272,15 @@ fn write_update_data(
 }
 
 fn main() {
+    // The window needs the main thread there, so the app runs beside it
+    #[cfg(all(target_os = "macos", feature = "egui-ui"))]
+    ui::egui_backend::run_with_main_thread(run_app);
+
+    #[cfg(not(all(target_os = "macos", feature = "egui-ui")))]
+    run_app();
+}
+
+fn run_app() {
     let is_dev_build = cfg!(debug_assertions);
 
     if !is_dev_build {
@@ -171,22 +319,97 @@ fn main() {
         error!("[AUDIO] Could not initialize audio! Reason: {}", e);
     }
     audio.mute(config.sound_muted);
+    audio.set_events(config.sound_events.clone());
 
     let mut updater = Updater::new();
     let mut installer_spawned = false;
+    // Installer was downloaded and the user was asked whether to run it
+    let mut update_awaiting_confirm = false;
 
     // Set up sim connect
     let mut observing = false;
+    // Joined only to watch, so we never take control
+    let mut spectating = false;
+    // Whether we told the others we're ready to go
+    let mut is_ready = false;
+    // Cloud server connection state, for reporting the NAT traversal outcome
+    let mut hole_punching = false;
+    let mut using_relay = false;
+    // Public IP while hosting direct, and whether we're still waiting on a client to prove the port is open
+    let mut direct_public_ip: Option<String> = None;
+    let mut port_check_pending = false;
+    // Public IP of a direct server being looked up, with its port and UPnP result
+    let mut public_ip_lookup: Option<(
+        crossbeam_channel::Receiver<Option<IpAddr>>,
+        u16,
+        Option<bool>,
+    )> = None;
+    // Username, session code and IPv6 to retry a direct connection with through the cloud server
+    let mut relay_fallback: Option<(String, String, bool)> = None;
+    // Password the session was joined with, reused when the client is restarted
+    let mut join_password: Option<String> = None;
+    // Most clients allowed to join our server
+    let mut client_limit = None;
+    // Remote aircraft state is being interpolated by the gauge
+    let mut predicting = false;
+    // Sending updates less often because of packet loss
+    let mut quality_reduced = false;
+    // User paused sending our aircraft state, the connection stays up
+    let mut sync_paused = false;
+    let mut last_sync_sent = Instant::now();
+    let mut last_sync_values_sent = Instant::now();
+    // Only read out for the Sync Inspector while it's open
+    let mut sync_values_watched = false;
+    // Last update received from whoever is in control, and who was flagged for going quiet
+    let mut controller_last_update = Instant::now();
+    let mut controller_stalled: Option<String> = None;
+    // Last round trip time in ms, used to take latency out of clock offsets
+    let mut round_trip_ms = 0.0;
+    // When each client's clock offset was last reported
+    let mut clock_offset_sent: HashMap<String, Instant> = HashMap::new();
+    // When our current turn in control started, and the countdown second last shown for it
+    let mut control_turn_start: Option<Instant> = None;
+    let mut rotation_countdown: Option<u32> = None;
+    // What each sender last sent us, and the desynced vars last shown for them
+    let mut expected_state: HashMap<String, AllNeedSync> = HashMap::new();
+    let mut desync_shown: HashMap<String, Vec<String>> = HashMap::new();
+    let mut last_desync_check = Instant::now();
+    // When each client's position was last shown on the minimap
+    let mut position_sent: HashMap<String, Instant> = HashMap::new();
+    // Waiting on the full state after joining or asking for a resync
+    let mut syncing = false;
+    // Sim is running, so a session can be started
+    let mut sim_connected = false;
+    let mut last_sim_probe: Option<Instant> = None;
+    // The probe's connection is kept open while idle and reused by the next session
+    let mut sim_probe_open = false;
+    // Updates sent since the rate was last reported
+    let mut syncs_sent: u32 = 0;
+    let mut rate_window_start = Instant::now();
+    let mut last_activity_report: Option<Instant> = None;
     // Client stopped, need to stop transfer client
     let mut should_set_none_client = false;
+    // Result of a Test Connection running in the background
+    let mut connection_test: Option<crossbeam_channel::Receiver<(bool, String)>> = None;
+
+    // Join link the app was launched with, handed to the UI once it's ready
+    let deeplink = env::args()
+        .skip(1)
+        .find(|arg| arg.starts_with(DEEPLINK_SCHEME));
+    // Started again by the installer after updating
+    let just_updated = env::args().skip(1).any(|arg| arg == UPDATED_ARG);
 
-    let app_interface = ActiveBackend::setup(format!("YourControls v{}", updater.get_version()));
+    let app_interface =
+        RecordingBackend::<AppBackend>::setup(format!("YourControls v{}", updater.get_version()));
+    app_interface.set_recording(config.record_session, config.streamer_mode);
 
     // Transfer
     let mut transfer_client: Option<Box<dyn TransferClient>> = None;
 
     // Update rate counter
     let mut definitions = Definitions::new();
+    // Which aircraft the local user has loaded
+    let mut aircraft_title = AircraftTitle::new(3, 5830);
 
     let mut ready_to_process_data = false;
 
@@ -224,11 +447,11 @@ fn main() {
         true
     };
 
-    let connect_to_sim = |conn: &mut SimConnector, definitions: &mut Definitions| {
+    let connect_to_sim = |conn: &mut SimConnector, definitions: &mut Definitions, open: bool| {
         // Connect to simconnect
         *definitions = Definitions::new();
         #[cfg(not(feature = "skip_sim_connect"))]
-        let connected = conn.connect("YourControls");
+        let connected = open || conn.connect("YourControls");
         #[cfg(feature = "skip_sim_connect")]
         let connected = true;
         if connected {
@@ -251,6 +474,9 @@ fn main() {
                 match message {
                     DispatchResult::SimObjectData(data) => {
                         definitions.process_sim_object_data(data);
+                        if let Some(title) = aircraft_title.process_sim_object_data(data) {
+                            report_detected_aircraft(&app_interface, title);
+                        }
                     }
                     // Exception occured
                     DispatchResult::Exception(data) => {
@@ -269,9 +495,22 @@ fn main() {
                     }
                     DispatchResult::Event(data) => {
                         definitions.process_event_data(data);
+
+                        let report_due = last_activity_report
+                            .map_or(true, |last| last.elapsed() >= ACTIVITY_INTERVAL);
+                        if control.has_control() && report_due {
+                            last_activity_report = Some(Instant::now());
+                            app_interface.note_activity();
+                        }
                     }
                     DispatchResult::Quit(_) => {
-                        client.stop("Sim closed.".to_string());
+                        info!("[SIM] Simulator closed.");
+                        sim_connected = false;
+                        app_interface.sim_disconnected();
+
+                        if config.auto_disconnect_on_sim_exit {
+                            client.stop("Sim closed.".to_string());
+                        }
                     }
                     _ => {}
                 }
@@ -310,16 +549,71 @@ fn main() {
                                 );
                             }
 
+                            // The full state from whoever is in control completes the join
+                            if !is_unreliable
+                                && ready_to_process_data
+                                && syncing
+                                && clients.client_has_control(&from)
+                            {
+                                syncing = false;
+                                app_interface.syncing(false);
+                            }
+
+                            if clients.client_has_control(&from) {
+                                controller_last_update = Instant::now();
+                                if controller_stalled.as_ref() == Some(&from) {
+                                    controller_stalled = None;
+                                    app_interface.set_client_input_active(&from, true);
+                                }
+                            }
+
+                            // Updates are stamped with the sender's clock, so how far it's ahead of ours
+                            // is the stamp's age minus the one way latency
+                            if clock_offset_sent
+                                .get(&from)
+                                .map_or(true, |sent| sent.elapsed() >= CLOCK_OFFSET_INTERVAL)
+                            {
+                                clock_offset_sent.insert(from.clone(), Instant::now());
+                                let offset_ms =
+                                    ((time - get_seconds()) * 1000.0) as f32 + round_trip_ms / 2.0;
+                                app_interface.set_clock_offset(&from, offset_ms);
+                            }
+
+                            // Unreliable updates carry the interpolated vars
+                            if is_unreliable && !predicting && !control.has_control() {
+                                predicting = true;
+                                app_interface.set_prediction(true);
+                            }
+
+                            if position_sent
+                                .get(&from)
+                                .map_or(true, |sent| sent.elapsed() >= POSITION_INTERVAL)
+                            {
+                                if let Some((lat, lon, heading)) = get_position(&data.avars) {
+                                    position_sent.insert(from.clone(), Instant::now());
+                                    app_interface.set_client_position(&from, lat, lon, heading);
+                                }
+                            }
+
                             if !clients.is_observer(&from) && ready_to_process_data {
+                                let sync_permission = SyncPermission {
+                                    is_server: clients.client_is_server(&from),
+                                    is_master: clients.client_has_control(&from),
+                                    is_init: true,
+                                };
+                                if !is_unreliable {
+                                    definitions.record_expected(
+                                        expected_state.entry(from.clone()).or_default(),
+                                        &data,
+                                        &sync_permission,
+                                    );
+                                }
+
                                 match definitions.on_receive_data(
                                     &conn,
                                     data,
                                     time,
-                                    &SyncPermission {
-                                        is_server: clients.client_is_server(&from),
-                                        is_master: clients.client_has_control(&from),
-                                        is_init: true,
-                                    },
+                                    &sync_permission,
                                 ) {
                                     Ok(_) => {}
                                     Err(e) => {
@@ -329,12 +623,33 @@ fn main() {
                             }
                         }
                         Payloads::TransferControl { from, to } => {
+                            let previous = if control.has_control() {
+                                Some(client.get_server_name().to_string())
+                            } else {
+                                clients.get_client_in_control().cloned()
+                            };
+                            app_interface.control_changed(&to, previous.as_deref());
+                            controller_last_update = Instant::now();
+                            // What the previous controller sent no longer describes the aircraft
+                            for (sender, shown) in desync_shown.drain() {
+                                if !shown.is_empty() {
+                                    app_interface.set_desync_vars(&sender, &[]);
+                                }
+                            }
+                            expected_state.clear();
+                            if let Some(stalled) = controller_stalled.take() {
+                                app_interface.set_client_input_active(&stalled, true);
+                            }
                             // Someone is transferring controls to us
                             definitions.reset_sync();
                             if to == client.get_server_name() {
                                 info!("[CONTROL] Taking control from {}", from);
                                 control.take_control(&conn, &definitions.lvarstransfer.transfer);
                                 app_interface.gain_control();
+                                play_sound(&audio, SoundEvent::ControlGained);
+                                if config.notifications_enabled {
+                                    app_interface.request_attention();
+                                }
                                 clients.set_no_control();
                             // Someone else has controls, if we have controls we let go and listen for their messages
                             } else {
@@ -353,6 +668,9 @@ fn main() {
                             in_control,
                             mut is_observer,
                             is_server,
+                            is_spectator,
+                            version,
+                            aircraft,
                         } => {
                             info!(
                                 "[NETWORK] {} connected. In control: {}, observing: {}, server: {}",
@@ -367,6 +685,12 @@ fn main() {
                                     definitions.get_buffer_bytes().into_boxed_slice(),
                                     name.clone(),
                                 );
+                                if !config_to_load.is_empty() {
+                                    client.send_required_aircraft(
+                                        aircraft_name(&config_to_load),
+                                        Some(name.clone()),
+                                    );
+                                }
 
                                 if config.instructor_mode {
                                     is_observer = true;
@@ -374,10 +698,35 @@ fn main() {
                                 }
                             }
 
-                            app_interface.new_connection(&name);
+                            app_interface.new_connection_detailed(
+                                &name,
+                                aircraft.as_deref(),
+                                version.as_deref(),
+                            );
+                            // Someone reached our direct server, so the port must be open
+                            if port_check_pending {
+                                port_check_pending = false;
+                                app_interface.public_ip_check(direct_public_ip.clone(), Some(true));
+                            }
+                            play_sound(&audio, SoundEvent::NewConnection);
+                            if client.is_host() {
+                                app_interface
+                                    .set_capacity(clients.len(), client_limit.unwrap_or(0));
+                            }
+                            // Late joiners don't know about earlier ready statuses
+                            if is_ready {
+                                client.set_ready_status(true);
+                            }
+                            if client.is_host() && config.notifications_enabled {
+                                app_interface.request_attention();
+                            }
                             app_interface.set_observing(&name, is_observer);
                             clients.set_server(&name, is_server);
                             clients.set_observer(&name, is_observer);
+                            if is_spectator {
+                                clients.set_spectator(&name);
+                                app_interface.set_spectator(&name);
+                            }
 
                             if in_control {
                                 app_interface.set_incontrol(&name);
@@ -396,6 +745,8 @@ fn main() {
                         }
                         Payloads::PlayerLeft { name } => {
                             info!("[NETWORK] {} lost connection.", name);
+                            expected_state.remove(&name);
+                            desync_shown.remove(&name);
 
                             clients.remove_client(&name);
                             // User may have been in control
@@ -405,6 +756,7 @@ fn main() {
                                 if client.is_host() {
                                     info!("[CONTROL] {} had control, taking control back.", name);
                                     app_interface.gain_control();
+                                    play_sound(&audio, SoundEvent::ControlGained);
 
                                     control
                                         .take_control(&conn, &definitions.lvarstransfer.transfer);
@@ -413,6 +765,10 @@ fn main() {
                             }
 
                             app_interface.lost_connection(&name);
+                            if client.is_host() {
+                                app_interface
+                                    .set_capacity(clients.len(), client_limit.unwrap_or(0));
+                            }
                         }
                         Payloads::SetObserver {
                             from: _,
@@ -421,18 +777,42 @@ fn main() {
                         } => {
                             if to == client.get_server_name() {
                                 info!("[CONTROL] Server set us to observing? {}", is_observer);
-                                observing = is_observer;
-                                app_interface.observing(is_observer);
+                                // Spectators stay observers no matter what
+                                observing = is_observer || spectating;
+                                app_interface.observing(observing);
 
                                 if !observing {
                                     definitions.reset_sync();
                                 }
-                            } else {
+                            } else if !clients.is_spectator(&to) {
                                 info!("[CONTROL] {} is observing? {}", to, is_observer);
                                 clients.set_observer(&to, is_observer);
                                 app_interface.set_observing(&to, is_observer);
                             }
                         }
+                        Payloads::ReadyStatus {
+                            name,
+                            is_ready: ready,
+                        } => {
+                            app_interface.set_client_ready(&name, ready);
+                        }
+                        Payloads::SeatAssigned { name, seat } => {
+                            app_interface.set_client_seat(&name, seat.as_deref());
+                        }
+                        Payloads::ChatMessage { from, text } => {
+                            let text = sanitize_text(&text, MAX_CHAT_LENGTH);
+                            if !text.is_empty() {
+                                app_interface.chat_message(&from, &text);
+                            }
+                        }
+                        Payloads::AircraftChanging { name, to } => {
+                            info!("[DEFINITIONS] {} is switching to {}", name, to);
+                            app_interface.client_changing_aircraft(&name, &to);
+                        }
+                        Payloads::RequiredAircraft { name } => {
+                            info!("[DEFINITIONS] Session requires {}", name);
+                            app_interface.set_required_aircraft(&name);
+                        }
                         Payloads::SetHost => {
                             app_interface.set_host();
                             // Host was set which means successfully established connection to hoster, need to send definitions
@@ -440,17 +820,30 @@ fn main() {
                                 definitions.get_buffer_bytes().into_boxed_slice(),
                                 client.get_server_name().to_string(),
                             );
+                            if !config_to_load.is_empty() {
+                                client.send_required_aircraft(
+                                    aircraft_name(&config_to_load),
+                                    Some(client.get_server_name().to_string()),
+                                );
+                            }
                         }
                         Payloads::ConnectionDenied { reason } => {
                             client.stop(format!("Connection Denied: {}", reason));
                         }
+                        Payloads::ServerCapabilities { capabilities } => {
+                            info!("[NETWORK] Server supports {:?}", capabilities);
+                            app_interface.set_server_capabilities(&capabilities);
+                        }
                         Payloads::AircraftDefinition { bytes } => {
                             match definitions.load_config_from_bytes(bytes) {
                                 Ok(_) => {
                                     info!("[DEFINITIONS] Loaded and mapped {} aircraft vars, {} local vars, and {} events from the server", definitions.get_number_avars(), definitions.get_number_lvars(), definitions.get_number_events());
+                                    app_interface.set_sync_caveats(definitions.get_caveats());
                                     control.on_connected(&conn);
+                                    aircraft_title.on_connected(&conn);
 
                                     let def_connect_result = definitions.on_connected(&conn);
+                                    definitions.set_smoothing(&conn, config.smoothing());
                                     if let Err(()) = def_connect_result {
                                         client.stop("Error starting WS server. Do you have another YourControls open?".to_string())
                                     }
@@ -464,8 +857,13 @@ fn main() {
                             }
                             // Start the connection timer to wait to send the ready payload
                             connection_time = Some(Instant::now());
+                            syncing = true;
+                            app_interface.syncing(true);
                         }
                         Payloads::AttemptHosterConnection { peer } => {
+                            // Session is hosted on the relay rather than by a peer
+                            using_relay = true;
+                            app_interface.set_relay_region(get_rendezvous_hostname(), None);
                             match start_client(
                                 config.conn_timeout,
                                 client.get_server_name().to_string(),
@@ -476,6 +874,9 @@ fn main() {
                                 None,
                                 Some(peer.port()),
                                 ConnectionMethod::Direct,
+                                spectating,
+                                join_password.clone(),
+                                loaded_aircraft(&config_to_load),
                             ) {
                                 Ok(new_client) => {
                                     info!(
@@ -485,6 +886,7 @@ fn main() {
                                 }
                                 Err(e) => {
                                     app_interface.client_fail(e.to_string().as_str());
+                                    play_sound(&audio, SoundEvent::Error);
                                     error!(
                                         "[NETWORK] Could not start new hoster client! Reason: {}",
                                         e
@@ -499,9 +901,17 @@ fn main() {
                                 client.set_observer(name, true);
                             }
                         }
+                        Payloads::LeaveSelfObserver { name } => {
+                            if client.is_host() && !clients.is_spectator(&name) {
+                                clients.set_observer(&name, false);
+                                app_interface.set_observing(&name, false);
+                                client.set_observer(name, false);
+                            }
+                        }
                     },
                     ReceiveMessage::Event(e) => match e {
                         Event::ConnectionEstablished => {
+                            relay_fallback = None;
                             if client.is_host() {
                                 // Display server started message
                                 app_interface.server_started();
@@ -517,6 +927,10 @@ fn main() {
                                 // Display connected message
                                 app_interface.connected();
                                 app_interface.lose_control();
+
+                                if hole_punching {
+                                    app_interface.nat_result(!using_relay, using_relay);
+                                }
                             }
                         }
                         Event::ConnectionLost(reason) => {
@@ -525,24 +939,116 @@ fn main() {
                             control.take_control(&conn, &definitions.lvarstransfer.transfer);
 
                             clients.reset();
+                            controller_stalled = None;
                             observing = false;
+                            spectating = false;
+                            port_check_pending = false;
+                            public_ip_lookup = None;
+                            control_turn_start = None;
+                            rotation_countdown = None;
+                            expected_state.clear();
+                            desync_shown.clear();
+                            is_ready = false;
                             should_set_none_client = true;
 
-                            if let Err(e) = audio.play_disconnected() {
-                                warn!("[AUDIO] Error playing audio: {}", e);
+                            if quality_reduced {
+                                quality_reduced = false;
+                                app_interface.quality_reduced(false, "");
                             }
 
+                            if predicting {
+                                predicting = false;
+                                app_interface.set_prediction(false);
+                            }
+
+                            syncing = false;
+
+                            if sync_paused {
+                                sync_paused = false;
+                                app_interface.sync_paused(false);
+                            }
+
+                            play_sound(&audio, SoundEvent::Disconnected);
+
                             app_interface.client_fail(&reason);
                         }
-                        Event::UnablePunchthrough => app_interface.client_fail(
-                            "Could not connect to host! Please port forward or use 'Cloud Host'!",
-                        ),
+                        Event::UnablePunchthrough => {
+                            if let Some((username, session_id, isipv6)) = relay_fallback.take() {
+                                info!("[NETWORK] Direct connection failed, trying cloud server.");
+                                app_interface.relay_fallback();
+                                hole_punching = true;
 
-                        Event::SessionIdFetchFailed => app_interface
-                            .server_fail("Could not connect to Cloud Server to fetch session ID."),
+                                match start_client(
+                                    config.conn_timeout,
+                                    username,
+                                    Some(session_id),
+                                    updater.get_version().to_string(),
+                                    isipv6,
+                                    None,
+                                    None,
+                                    None,
+                                    ConnectionMethod::CloudServer,
+                                    spectating,
+                                    join_password.clone(),
+                                    loaded_aircraft(&config_to_load),
+                                ) {
+                                    Ok(new_client) => {
+                                        *client = Box::new(new_client);
+                                        continue;
+                                    }
+                                    Err(e) => {
+                                        error!("[NETWORK] Relay fallback failed! Reason: {}", e);
+                                    }
+                                }
+                            }
+
+                            app_interface.nat_result(false, false);
+                            app_interface.client_fail(
+                                "Could not connect to host! Please port forward or use 'Cloud Host'!",
+                            );
+                            play_sound(&audio, SoundEvent::Error);
+                        }
+
+                        Event::SessionIdFetchFailed => {
+                            app_interface.server_fail(
+                                "Could not connect to Cloud Server to fetch session ID.",
+                            );
+                            play_sound(&audio, SoundEvent::Error);
+                        }
 
                         Event::Metrics(metrics) => {
+                            round_trip_ms = metrics.rtt;
+                            let packet_loss = f64::from(metrics.packet_loss);
+                            if !quality_reduced && packet_loss > REDUCED_QUALITY_LOSS {
+                                quality_reduced = true;
+                                info!("[NETWORK] High packet loss, reducing update rate.");
+                                app_interface.quality_reduced(true, "high packet loss");
+                            } else if quality_reduced && packet_loss < RECOVERED_QUALITY_LOSS {
+                                quality_reduced = false;
+                                info!("[NETWORK] Packet loss recovered, restoring update rate.");
+                                app_interface.quality_reduced(false, "");
+                            }
+
+                            if using_relay {
+                                app_interface.set_relay_region(
+                                    get_rendezvous_hostname(),
+                                    Some(metrics.rtt / 2.0),
+                                );
+                            }
+
                             app_interface.send_network(&metrics);
+                            app_interface.send_network_full(&metrics);
+                            app_interface.set_queue_depth(
+                                client.get_transmitter().len(),
+                                client.get_receiver().len(),
+                            );
+
+                            let elapsed = rate_window_start.elapsed().as_secs_f32();
+                            if elapsed > 0.0 {
+                                app_interface.set_update_rate(syncs_sent as f32 / elapsed);
+                            }
+                            syncs_sent = 0;
+                            rate_window_start = Instant::now();
                         }
                     },
                 }
@@ -552,6 +1058,12 @@ fn main() {
                 client.stop(e.to_string());
             }
 
+            // We fly the aircraft ourselves once in control
+            if predicting && control.has_control() {
+                predicting = false;
+                app_interface.set_prediction(false);
+            }
+
             // Handle specific program triggered actions
             if let Some(pending_action) = definitions.get_next_pending_action() {
                 match pending_action {
@@ -564,7 +1076,9 @@ fn main() {
                         }
                     }
                     ProgramAction::TransferControls => {
-                        if control.has_control() {
+                        if spectating {
+                            info!("[CONTROL] Spectating, ignoring control transfer keybind.");
+                        } else if control.has_control() {
                             if let Some(next_control) = clients.get_next_client_for_control() {
                                 client.transfer_control(next_control.clone())
                             }
@@ -576,10 +1090,102 @@ fn main() {
                 }
             }
 
+            if sync_values_watched
+                && ready_to_process_data
+                && last_sync_values_sent.elapsed() >= SYNC_VALUES_INTERVAL
+            {
+                last_sync_values_sent = Instant::now();
+                app_interface.set_sync_values(&sync_value_pairs(&definitions.get_all_current()));
+            }
+
+            if ready_to_process_data
+                && controller_stalled.is_none()
+                && !control.has_control()
+                && controller_last_update.elapsed() >= CONTROLLER_STALL_TIMEOUT
+            {
+                if let Some(in_control) = clients.get_client_in_control() {
+                    info!(
+                        "[CONTROL] No updates from {} in {} seconds.",
+                        in_control,
+                        CONTROLLER_STALL_TIMEOUT.as_secs()
+                    );
+                    app_interface.set_client_input_active(in_control, false);
+                    controller_stalled = Some(in_control.clone());
+                }
+            }
+
+            // Vars that stayed different from what their sender sent, an empty list once they match again
+            if ready_to_process_data && last_desync_check.elapsed() >= DESYNC_CHECK_INTERVAL {
+                last_desync_check = Instant::now();
+                for (sender, expected) in &expected_state {
+                    let vars = definitions.desynced_vars(expected);
+                    let changed = desync_shown
+                        .get(sender)
+                        .map_or(!vars.is_empty(), |shown| *shown != vars);
+                    if changed {
+                        app_interface.set_desync_vars(sender, &vars);
+                        desync_shown.insert(sender.clone(), vars);
+                    }
+                }
+            }
+
+            // Hand control to the next pilot once our turn is over, counting down the last seconds
+            match config.control_rotation_mins {
+                Some(mins) if ready_to_process_data && control.has_control() => {
+                    let turn = Duration::from_secs(u64::from(mins) * 60);
+                    let elapsed = control_turn_start
+                        .get_or_insert_with(Instant::now)
+                        .elapsed();
+                    let remaining = turn.saturating_sub(elapsed);
+                    let next = clients.next_in_rotation(client.get_server_name()).cloned();
+
+                    match next {
+                        Some(next) if remaining.is_zero() => {
+                            info!("[CONTROL] Turn over, giving control to {}", next);
+                            client.transfer_control(next.clone());
+                            app_interface.control_countdown(0, &next);
+                            control_turn_start = None;
+                            rotation_countdown = None;
+                        }
+                        Some(next) if remaining <= CONTROL_ROTATION_COUNTDOWN => {
+                            // Rounded up so the last second shows 1 rather than 0
+                            let seconds = remaining.as_secs_f32().ceil() as u32;
+                            if rotation_countdown != Some(seconds) {
+                                rotation_countdown = Some(seconds);
+                                app_interface.control_countdown(seconds, &next);
+                            }
+                        }
+                        // Nobody to hand over to, keep flying until someone is
+                        None => {
+                            if rotation_countdown.take().is_some() {
+                                app_interface.control_countdown(0, "");
+                            }
+                        }
+                        Some(_) => {}
+                    }
+                }
+                _ => {
+                    control_turn_start = None;
+                    if rotation_countdown.take().is_some() {
+                        app_interface.control_countdown(0, "");
+                    }
+                }
+            }
+
             // Handle initial 3 second connection delay, allows lvars to be processed
-            if let Some(true) = connection_time.map(|t| t.elapsed().as_secs() >= 3) {
+            if let Some(true) = connection_time.map(|t| t.elapsed() >= CONNECTION_DELAY) {
                 // Do not let server send initial data - wait for data to get cleared on the previous loop
-                if !observing && ready_to_process_data {
+                // Changes keep accumulating between sends
+                let mut sync_interval =
+                    Duration::from_secs_f64(1.0 / f64::from(config.update_rate()));
+                if quality_reduced {
+                    sync_interval = sync_interval.max(REDUCED_SYNC_INTERVAL);
+                }
+                let sync_due = last_sync_sent.elapsed() >= sync_interval;
+
+                if !observing && !sync_paused && ready_to_process_data && sync_due {
+                    last_sync_sent = Instant::now();
+                    syncs_sent += 1;
                     let permission = SyncPermission {
                         is_server: client.is_host(),
                         is_master: control.has_control(),
@@ -592,6 +1198,7 @@ fn main() {
                 // Tell server we're ready to receive data after 3 seconds
                 if !ready_to_process_data {
                     ready_to_process_data = true;
+                    controller_last_update = Instant::now();
                     definitions.reset_sync();
 
                     if !client.is_host() {
@@ -601,6 +1208,30 @@ fn main() {
             }
         }
 
+        if let Some(Ok((success, detail))) = connection_test.as_ref().map(|rx| rx.try_recv()) {
+            info!("[NETWORK] Connection test: {}", detail);
+            app_interface.test_connection_result(success, &detail);
+            connection_test = None;
+        }
+
+        if let Some((Ok(public_ip), port, upnp_mapped)) = public_ip_lookup
+            .as_ref()
+            .map(|(rx, port, upnp_mapped)| (rx.try_recv(), *port, *upnp_mapped))
+        {
+            let endpoint = public_ip.map(|ip| SocketAddr::new(ip, port).to_string());
+            app_interface.set_public_endpoint(endpoint.as_deref().unwrap_or_default());
+
+            direct_public_ip = public_ip.map(|ip| ip.to_string());
+            // Someone may have already joined while looking it up
+            let reachable = if port_check_pending {
+                upnp_mapped
+            } else {
+                Some(true)
+            };
+            app_interface.public_ip_check(direct_public_ip.clone(), reachable);
+            public_ip_lookup = None;
+        }
+
         // GUI
         if let Ok(msg) = app_interface.get_next_message() {
             match msg {
@@ -610,8 +1241,11 @@ fn main() {
                     is_ipv6,
                     method,
                     use_upnp,
+                    max_clients,
+                    password,
                 } => {
-                    let connected = connect_to_sim(&mut conn, &mut definitions);
+                    let connected =
+                        connect_to_sim(&mut conn, &mut definitions, mem::take(&mut sim_probe_open));
 
                     if config_to_load.is_empty() {
                         app_interface.server_fail("Select an aircraft config first!");
@@ -621,9 +1255,14 @@ fn main() {
                         );
                     } else if connected {
                         definitions.on_connected(&conn).ok();
+                        definitions.set_smoothing(&conn, config.smoothing());
                         control.on_connected(&conn);
+                        aircraft_title.on_connected(&conn);
                         // Display attempting to start server
                         app_interface.attempt();
+                        app_interface.set_self_name(&username);
+
+                        using_relay = method == ConnectionMethod::Relay;
 
                         match method {
                             ConnectionMethod::Direct | ConnectionMethod::CloudServer => {
@@ -632,6 +1271,10 @@ fn main() {
                                     updater.get_version().to_string(),
                                     config.conn_timeout,
                                 ));
+                                server.set_max_clients(max_clients);
+                                server.set_password(password);
+                                server.set_aircraft(loaded_aircraft(&config_to_load));
+                                client_limit = max_clients;
 
                                 let result = match method {
                                     ConnectionMethod::Direct => {
@@ -645,31 +1288,90 @@ fn main() {
 
                                 match result {
                                     Ok(_) => {
+                                        // A UPnP mapping is the best guess at reachability until someone connects
+                                        let upnp_mapped = match server
+                                            .get_last_port_forward_result()
+                                        {
+                                            Some(Ok(_)) => {
+                                                app_interface.upnp_result(true, Some(port));
+                                                Some(true)
+                                            }
+                                            Some(Err(e)) => {
+                                                warn!("[NETWORK] UPnP port forward failed: {}", e);
+                                                app_interface.upnp_result(false, None);
+                                                Some(false)
+                                            }
+                                            None => None,
+                                        };
+
                                         // Assign server as transfer client
                                         transfer_client = Some(server);
                                         info!("[NETWORK] Server started");
+                                        app_interface.set_server_capabilities(
+                                            &SERVER_CAPABILITIES
+                                                .iter()
+                                                .map(|capability| capability.to_string())
+                                                .collect::<Vec<String>>(),
+                                        );
+
+                                        if method == ConnectionMethod::Direct {
+                                            // Waiting on the lookup would stall the session
+                                            let (tx, rx) = crossbeam_channel::unbounded();
+                                            std::thread::spawn(move || {
+                                                tx.send(get_public_ip(is_ipv6)).ok();
+                                            });
+                                            public_ip_lookup = Some((rx, port, upnp_mapped));
+                                            direct_public_ip = None;
+                                            port_check_pending = true;
+                                        }
+                                    }
+                                    Err(Error::BindFailed(addr, e)) => {
+                                        let reason = if e.kind() == std::io::ErrorKind::AddrInUse {
+                                            "port in use".to_string()
+                                        } else {
+                                            e.to_string()
+                                        };
+                                        app_interface.server_bind_failed(
+                                            &addr.to_string(),
+                                            &reason,
+                                            find_free_port(port),
+                                        );
+                                        play_sound(&audio, SoundEvent::Error);
+                                        info!("[NETWORK] Could not bind {}! Reason: {}", addr, e);
                                     }
                                     Err(e) => {
                                         app_interface.server_fail(&e.to_string());
+                                        play_sound(&audio, SoundEvent::Error);
                                         info!("[NETWORK] Could not start server! Reason: {}", e);
                                     }
                                 }
                             }
                             ConnectionMethod::Relay => {
+                                // The relay doesn't enforce a limit or password
+                                client_limit = None;
+                                if password.is_some() {
+                                    warn!(
+                                        "[NETWORK] Cloud Host sessions can't be password protected"
+                                    );
+                                }
                                 let mut client = Box::new(Client::new(
                                     username.clone(),
                                     updater.get_version().to_string(),
                                     config.conn_timeout,
                                 ));
+                                client.set_aircraft(loaded_aircraft(&config_to_load));
 
                                 match client.start_with_relay(is_ipv6) {
                                     Ok(_) => {
                                         transfer_client = Some(client);
                                         info!("[NETWORK] Hosting started");
+                                        app_interface
+                                            .set_relay_region(get_rendezvous_hostname(), None);
                                     }
                                     Err(e) => {
                                         info!("[NETWORK] Hosting could not start! Reason: {}", e);
                                         app_interface.server_fail(&e.to_string());
+                                        play_sound(&audio, SoundEvent::Error);
                                     }
                                 }
                             }
@@ -688,12 +1390,35 @@ fn main() {
                     port,
                     isipv6,
                     hostname,
+                    spectator,
+                    relay_session_id,
+                    password,
                 } => {
-                    let connected = connect_to_sim(&mut conn, &mut definitions);
+                    let connected =
+                        connect_to_sim(&mut conn, &mut definitions, mem::take(&mut sim_probe_open));
 
                     if connected {
                         // Display attempting to start server
                         app_interface.attempt();
+                        app_interface.set_self_name(&username);
+
+                        hole_punching = method == ConnectionMethod::CloudServer;
+                        using_relay = false;
+                        spectating = spectator;
+                        observing = spectator;
+                        relay_fallback = match relay_session_id {
+                            Some(session_id)
+                                if method == ConnectionMethod::Direct
+                                    && config.auto_fallback_relay =>
+                            {
+                                Some((username.clone(), session_id, isipv6))
+                            }
+                            _ => None,
+                        };
+                        if spectator {
+                            app_interface.observing(true);
+                        }
+                        join_password.clone_from(&password);
 
                         match start_client(
                             config.conn_timeout,
@@ -705,6 +1430,9 @@ fn main() {
                             hostname,
                             port,
                             method,
+                            spectator,
+                            password,
+                            loaded_aircraft(&config_to_load),
                         ) {
                             Ok(client) => {
                                 info!("[NETWORK] Client started.");
@@ -712,6 +1440,7 @@ fn main() {
                             }
                             Err(e) => {
                                 app_interface.client_fail(e.to_string().as_str());
+                                play_sound(&audio, SoundEvent::Error);
                                 error!("[NETWORK] Could not start client! Reason: {}", e);
                             }
                         }
@@ -727,6 +1456,23 @@ fn main() {
                         write_configuration(&config);
                     }
                 }
+                AppMessage::TestConnection { .. } if connection_test.is_some() => {}
+                AppMessage::TestConnection {
+                    isipv6,
+                    ip,
+                    hostname,
+                    port,
+                    method,
+                    ..
+                } => {
+                    // Resolving and waiting on the probe would stall syncing
+                    let (tx, rx) = crossbeam_channel::unbounded();
+                    std::thread::spawn(move || {
+                        tx.send(test_connection(isipv6, ip, hostname, port, method))
+                            .ok();
+                    });
+                    connection_test = Some(rx);
+                }
                 AppMessage::Disconnect => {
                     info!("[NETWORK] Request to disconnect.");
                     if let Some(client) = transfer_client.as_mut() {
@@ -735,19 +1481,61 @@ fn main() {
                 }
                 AppMessage::TransferControl { target } => {
                     if let Some(client) = transfer_client.as_ref() {
-                        info!("[CONTROL] Giving control to {}", target);
-                        // Send server message, will send a loopback Payloads::TransferControl
-                        client.transfer_control(target.clone());
+                        if !clients.has_client(&target) {
+                            app_interface.control_transfer_failed(&target, "they disconnected");
+                        } else if clients.is_observer(&target) {
+                            app_interface.control_transfer_failed(&target, "they are observing");
+                        } else {
+                            info!("[CONTROL] Giving control to {}", target);
+                            // Send server message, will send a loopback Payloads::TransferControl
+                            client.transfer_control(target.clone());
+                        }
+                    }
+                }
+                AppMessage::TransferControlSeat { target, seat } => {
+                    if let Some(client) = transfer_client.as_ref() {
+                        if !clients.has_client(&target) {
+                            app_interface.control_transfer_failed(&target, "they disconnected");
+                        } else if clients.is_observer(&target) {
+                            app_interface.control_transfer_failed(&target, "they are observing");
+                        } else {
+                            info!("[CONTROL] Giving control to {} as {}", target, seat);
+                            client.assign_seat(target.clone(), Some(seat));
+                            client.transfer_control(target);
+                        }
                     }
                 }
                 AppMessage::SetObserver {
                     target,
                     is_observer,
                 } => {
-                    clients.set_observer(&target, is_observer);
+                    if !is_observer && clients.is_spectator(&target) {
+                        info!(
+                            "[CONTROL] {} is spectating and must stay an observer.",
+                            target
+                        );
+                    } else {
+                        clients.set_observer(&target, is_observer);
+                        if let Some(client) = transfer_client.as_ref() {
+                            info!("[CONTROL] Setting {} as observer. {}", target, is_observer);
+                            client.set_observer(target, is_observer);
+                        }
+                    }
+                }
+                AppMessage::SetReady { ready } => {
+                    if let Some(client) = transfer_client.as_ref() {
+                        is_ready = ready;
+                        client.set_ready_status(ready);
+                    }
+                }
+                AppMessage::BroadcastMessage { text } => {
                     if let Some(client) = transfer_client.as_ref() {
-                        info!("[CONTROL] Setting {} as observer. {}", target, is_observer);
-                        client.set_observer(target, is_observer);
+                        let text = sanitize_text(&text, MAX_CHAT_LENGTH);
+                        if !text.is_empty() {
+                            // Messages aren't looped back, echo our own
+                            app_interface.chat_message(client.get_server_name(), &text);
+                            client.send_chat_message(text);
+                        }
                     }
                 }
                 AppMessage::GoObserver => {
@@ -756,6 +1544,11 @@ fn main() {
                         client.set_self_observer();
                     }
                 }
+                AppMessage::LeaveObserver => {
+                    if let Some(client) = transfer_client.as_ref() {
+                        client.leave_self_observer();
+                    }
+                }
                 AppMessage::LoadAircraft { config_file_name } => {
                     // Load config
                     info!(
@@ -763,23 +1556,77 @@ fn main() {
                         config_file_name
                     );
                     config_to_load.clone_from(&config_file_name);
+                    app_interface
+                        .set_sync_caveats(&read_sync_caveats(get_config_path(&config_file_name)));
+
+                    // Warn the others their sync may be off for a moment
+                    if let Some(client) = transfer_client.as_ref() {
+                        let aircraft = aircraft_name(&config_file_name);
+                        // Everyone should follow the host's new aircraft
+                        if client.is_host() {
+                            client.send_required_aircraft(aircraft.clone(), None);
+                        }
+                        client.send_aircraft_changing(aircraft);
+                    }
+
+                    config.push_recent_aircraft(&config_file_name);
+                    write_configuration(&config);
                 }
+                AppMessage::LoadAircraftPath { path } => match std::fs::canonicalize(&path) {
+                    Ok(full_path) => {
+                        let full_path_string = full_path.to_string_lossy().to_string();
+                        // Reading it catches a missing or broken file before it's selected
+                        match Definitions::read_caveats(full_path_string.clone()) {
+                            Ok(caveats) => {
+                                info!("[DEFINITIONS] {} aircraft config selected.", path);
+                                // Pushing an absolute path onto the definitions path replaces it
+                                config_to_load = full_path_string;
+                                app_interface.set_sync_caveats(&caveats);
+                                if let Some(file_name) = full_path.file_name() {
+                                    app_interface.aircraft_loaded(&file_name.to_string_lossy());
+                                }
+                            }
+                            Err(e) => {
+                                error!("[DEFINITIONS] Could not read {}: {}", path, e);
+                                app_interface.error(&format!("Could not read {}: {}", path, e));
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        error!("[DEFINITIONS] Could not find {}: {}", path, e);
+                        app_interface.error(&format!("Could not find {}", path));
+                    }
+                },
                 AppMessage::Startup => {
                     // List aircraft
-                    if let Ok(configs) = get_aircraft_configs() {
-                        info!(
-                            "[DEFINITIONS] Found {} configuration file(s).",
-                            configs.len()
-                        );
-
-                        for aircraft_config in configs {
-                            app_interface.add_aircraft(&aircraft_config);
-                        }
+                    app_interface.scanning_aircraft(true);
+                    let configs = get_aircraft_configs().unwrap_or_default();
+                    info!(
+                        "[DEFINITIONS] Found {} configuration file(s).",
+                        configs.len()
+                    );
+                    if configs.is_empty() {
+                        let searched = env::current_dir()
+                            .map(|dir| dir.join(AIRCRAFT_DEFINITIONS_PATH))
+                            .unwrap_or_else(|_| PathBuf::from(AIRCRAFT_DEFINITIONS_PATH));
+                        app_interface.no_aircraft_found(&searched.display().to_string());
+                    }
+                    for aircraft_config in configs {
+                        app_interface.add_aircraft(&aircraft_config);
                     }
+                    app_interface.scanning_aircraft(false);
 
                     app_interface.send_config(&config.get_json_string());
+                    // After the config so the link's port wins
+                    if let Some(url) = deeplink.as_deref() {
+                        info!("[PROGRAM] Launched with join link {}", url);
+                        app_interface.handle_deeplink(url);
+                    }
                     // Update version
                     let app_version = updater.get_version();
+                    if just_updated {
+                        app_interface.update_ready(&app_version.to_string());
+                    }
                     if let Ok(newest_version) = updater.get_latest_version() {
                         if *newest_version > app_version
                             && (newest_version.pre.is_empty()
@@ -795,22 +1642,119 @@ fn main() {
                         info!("[UPDATER] Version {} in use.", app_version)
                     }
                 }
-                AppMessage::RunUpdater => {
-                    match updater.run_installer() {
-                        Ok(_) => {
-                            // Terminate self
-                            installer_spawned = true
-                        }
-                        Err(e) => {
-                            error!("[UPDATER] Downloading installer failed. Reason: {}", e);
-                            app_interface.update_failed();
+                // Nothing is downloaded until the user has seen what the release contains
+                AppMessage::RunUpdater => match updater.update_contents() {
+                    Ok(contents) => {
+                        update_awaiting_confirm = true;
+                        app_interface.update_confirm(&contents.files, contents.size);
+                    }
+                    Err(e) => {
+                        error!("[UPDATER] Reading release info failed. Reason: {}", e);
+                        app_interface.update_failed_detail(&e.to_string(), e.is_retryable());
+                    }
+                },
+                // Answers without a pending update are ignored
+                AppMessage::ConfirmUpdate { accepted } if update_awaiting_confirm => {
+                    update_awaiting_confirm = false;
+
+                    if !accepted {
+                        info!("[UPDATER] Update declined.");
+                    } else {
+                        match updater.run_installer() {
+                            Ok(_) => {
+                                // Terminate self
+                                info!("[UPDATER] Installer started.");
+                                installer_spawned = true
+                            }
+                            Err(e) => {
+                                error!("[UPDATER] Downloading installer failed. Reason: {}", e);
+                                app_interface
+                                    .update_failed_detail(&e.to_string(), e.is_retryable());
+                            }
+                        };
+                    }
+                }
+                AppMessage::ConfirmUpdate { .. } => {}
+                AppMessage::UpdateConfig { new_config } => match new_config.validate() {
+                    Ok(_) => {
+                        audio.mute(new_config.sound_muted);
+                        audio.set_events(new_config.sound_events.clone());
+                        app_interface
+                            .set_recording(new_config.record_session, new_config.streamer_mode);
+                        config = new_config;
+                        write_configuration(&config);
+                    }
+                    Err(errors) => {
+                        warn!("[PROGRAM] Rejected invalid configuration: {:?}", errors);
+                        app_interface.config_errors(&errors);
+                    }
+                },
+                AppMessage::SetSessionLocked { locked } => {
+                    if let Some(client) = transfer_client.as_ref() {
+                        if client.set_session_locked(locked) {
+                            info!("[NETWORK] Session locked: {}", locked);
+                            app_interface.session_locked(locked);
+                        } else {
+                            warn!("[NETWORK] Session lock is not supported for this connection");
+                            app_interface.session_locked(false);
                         }
-                    };
+                    }
+                }
+                AppMessage::SetSyncPaused { paused } => {
+                    if transfer_client.is_some() {
+                        info!("[PROGRAM] Sync paused: {}", paused);
+                        sync_paused = paused;
+                    }
+                    app_interface.sync_paused(sync_paused);
+                }
+                AppMessage::SetUpdateRate { hz } => {
+                    config.update_rate_hz = hz.clamp(MIN_UPDATE_RATE, MAX_UPDATE_RATE);
+                    info!("[PROGRAM] Update rate set to {} Hz", config.update_rate_hz);
+                    app_interface.set_update_rate(config.update_rate_hz as f32);
+                    write_configuration(&config);
                 }
-                AppMessage::UpdateConfig { new_config: config } => {
-                    audio.mute(config.sound_muted);
+                AppMessage::SetSmoothing { ms } => {
+                    config.smoothing_ms = ms.min(MAX_SMOOTHING_MS);
+                    info!("[PROGRAM] Smoothing set to {} ms", config.smoothing_ms);
+                    // Otherwise it's applied once definitions are loaded for the next session
+                    if transfer_client.is_some() && ready_to_process_data {
+                        definitions.set_smoothing(&conn, config.smoothing_ms);
+                    }
+                    app_interface.smoothing_changed(config.smoothing_ms);
                     write_configuration(&config);
                 }
+                AppMessage::RequestResync => {
+                    if let Some(client) = transfer_client.as_ref() {
+                        if control.has_control() {
+                            info!("[PROGRAM] In control, nothing to resync");
+                        } else if ready_to_process_data {
+                            info!("[PROGRAM] Requesting a full resync");
+                            // Whoever is in control answers Ready with their full state
+                            client.send_ready();
+                            syncing = true;
+                            app_interface.syncing(true);
+                        }
+                    }
+                }
+                AppMessage::WatchSyncValues { watching } => {
+                    sync_values_watched = watching;
+                }
+                AppMessage::InstructorTakeControl { target } => {
+                    if let Some(client) = transfer_client.as_ref() {
+                        if !config.instructor_mode || !client.is_host() {
+                            warn!(
+                                "[CONTROL] Ignoring instructor take control from a non instructor"
+                            );
+                        } else if clients.get_client_in_control() != Some(&target) {
+                            warn!("[CONTROL] {} is not in control", target);
+                        } else {
+                            info!("[CONTROL] Instructor taking control from {}", target);
+                            //Will send a loopback Payloads::TransferControl
+                            client.take_control(target);
+                        }
+                    }
+                }
+                AppMessage::ForceTakeControl if spectating => {}
                 AppMessage::ForceTakeControl => {
                     if let Some(client) = transfer_client.as_ref() {
                         if let Some(client_name) = clients.get_client_in_control() {
@@ -831,12 +1775,69 @@ fn main() {
             conn.close();
         }
 
+        // Once the probe connects, watch the loaded aircraft and the sim closing instead of reconnecting
+        if transfer_client.is_none() && sim_probe_open {
+            while let Ok(message) = conn.get_next_message() {
+                match message {
+                    DispatchResult::SimObjectData(data) => {
+                        if let Some(title) = aircraft_title.process_sim_object_data(data) {
+                            report_detected_aircraft(&app_interface, title);
+                        }
+                    }
+                    DispatchResult::Quit(_) => {
+                        info!("[SIM] Simulator closed.");
+                        conn.close();
+                        sim_probe_open = false;
+                        sim_connected = false;
+                        app_interface.sim_connected(false);
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        // Let the UI know when starting a session can work
+        if transfer_client.is_none()
+            && !sim_probe_open
+            && last_sim_probe.map_or(true, |probed| probed.elapsed() >= SIM_PROBE_INTERVAL)
+        {
+            last_sim_probe = Some(Instant::now());
+
+            #[cfg(not(feature = "skip_sim_connect"))]
+            let connected = {
+                sim_probe_open = conn.connect("YourControls");
+                // Lets the UI show the aircraft and check for a definition before a session
+                if sim_probe_open {
+                    aircraft_title.on_connected(&conn);
+                }
+                sim_probe_open
+            };
+            #[cfg(feature = "skip_sim_connect")]
+            let connected = true;
+
+            if connected != sim_connected {
+                info!("[SIM] Simulator available: {}", connected);
+                sim_connected = connected;
+                app_interface.sim_connected(connected);
+            }
+        }
+
         if timer.elapsed().as_millis() < 10 {
             sleep(LOOP_SLEEP_TIME)
         };
         // Attempt Simconnect connection
-        if app_interface.exited() || installer_spawned {
+        if !app_interface.is_alive() || installer_spawned {
             break;
         }
     }
+
+    // Closing normally leaves nothing to report
+    if let Some(reason) = app_interface.setup_error() {
+        error!("[UI] The interface could not start: {}", reason);
+        show_error_box(
+            "YourControls",
+            &format!("YourControls could not start its interface: {}", reason),
+        );
+    }
 }
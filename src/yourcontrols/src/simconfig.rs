@@ -1,15 +1,87 @@
+use crate::ui::ConnectionMethod;
 use derive_more::{Display, From};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::io::Write;
 use std::{convert::AsRef, fs::File, io};
 
+const MAX_RECENT_AIRCRAFT: usize = 5;
+pub const MAX_CONN_TIMEOUT: u64 = 600;
+const MAX_NAME_LENGTH: usize = 32;
+pub const MIN_UPDATE_RATE: u32 = 10;
+pub const MAX_UPDATE_RATE: u32 = 60;
+pub const MAX_SMOOTHING_MS: u32 = 500;
+
 #[derive(From, Display)]
 pub enum ConfigLoadError {
     FileError(io::Error),
     SerializeError(serde_json::Error),
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum BandwidthUnit {
+    KilobytesPerSec,
+    Megabits,
+}
+
+/// Things that can play a sound when they happen
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum SoundEvent {
+    NewConnection,
+    ControlGained,
+    Disconnected,
+    Error,
+}
+
+impl SoundEvent {
+    pub const ALL: [SoundEvent; 4] = [
+        SoundEvent::NewConnection,
+        SoundEvent::ControlGained,
+        SoundEvent::Disconnected,
+        SoundEvent::Error,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SoundEvent::NewConnection => "Someone connects",
+            SoundEvent::ControlGained => "You gain control",
+            SoundEvent::Disconnected => "Connection lost",
+            SoundEvent::Error => "Connection errors",
+        }
+    }
+}
+
+/// Saved details for joining a server again
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ServerProfile {
+    pub name: String,
+    pub method: ConnectionMethod,
+    // Used for direct connections
+    pub ip: String,
+    pub port: u16,
+    // Used for cloud connections
+    pub session_code: String,
+    pub isipv6: bool,
+}
+
+impl Default for ServerProfile {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            method: ConnectionMethod::CloudServer,
+            ip: String::new(),
+            port: 25071,
+            session_code: String::new(),
+            isipv6: false,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
 pub struct Config {
     pub conn_timeout: u64,
     pub check_for_betas: bool,
@@ -20,6 +92,51 @@ pub struct Config {
     pub streamer_mode: bool,
     pub instructor_mode: bool,
     pub sound_muted: bool,
+    // Events that play a sound unless muted
+    pub sound_events: HashSet<SoundEvent>,
+    pub ui_compact_mode: bool,
+    // Borderless, see-through window kept on top of the sim
+    pub ui_overlay_mode: bool,
+    pub overlay_opacity: f32,
+    // Where the overlay was last dragged to
+    pub overlay_position: Option<[f32; 2]>,
+    // Blue/orange instead of green/red for status indicators
+    pub ui_colorblind_mode: bool,
+    pub notifications_enabled: bool,
+    // Closing the window hides it in the tray instead of quitting
+    pub minimize_to_tray: bool,
+    pub auto_disconnect_on_sim_exit: bool,
+    // Retry a failed direct connection through the cloud server
+    pub auto_fallback_relay: bool,
+    // Copy the session code to the clipboard when hosting starts
+    pub auto_copy_code: bool,
+    // Offer to go observer after this long in control without any input
+    pub afk_observer_mins: Option<u32>,
+    // Hand control to the next pilot after this long in control, for training rotations
+    pub control_rotation_mins: Option<u32>,
+    // Most aircraft state updates sent per second
+    pub update_rate_hz: u32,
+    // Extra time interpolated values are spread over, trading latency for smoothness
+    pub smoothing_ms: u32,
+    // Write every UI message to a file for bug reports
+    pub record_session: bool,
+    pub bandwidth_unit: BandwidthUnit,
+    // Warn when average usage in KB/s goes above this
+    pub bandwidth_warn_kbps: Option<f32>,
+    // Show the getting started overlay on launch
+    pub first_run: bool,
+    // Most recently selected aircraft configs, newest first
+    pub recent_aircraft: VecDeque<String>,
+    // Groups of aircraft configs close enough to hand control between
+    pub aircraft_families: Vec<Vec<String>>,
+    pub profiles: Vec<ServerProfile>,
+    // Chat messages sent with the F1-F4 keys
+    pub quick_messages: Vec<String>,
+    // Toggles observer mode, e.g. "Ctrl+O"
+    pub observer_shortcut: String,
+    // Keys this version doesn't know about, kept so they survive a load/save round trip
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 impl Default for Config {
@@ -34,6 +151,37 @@ impl Default for Config {
             streamer_mode: false,
             instructor_mode: false,
             sound_muted: false,
+            sound_events: [SoundEvent::ControlGained, SoundEvent::Disconnected]
+                .iter()
+                .copied()
+                .collect(),
+            ui_compact_mode: false,
+            ui_overlay_mode: false,
+            overlay_opacity: 0.8,
+            overlay_position: None,
+            ui_colorblind_mode: false,
+            notifications_enabled: true,
+            minimize_to_tray: false,
+            auto_disconnect_on_sim_exit: true,
+            auto_fallback_relay: true,
+            auto_copy_code: false,
+            afk_observer_mins: None,
+            control_rotation_mins: None,
+            update_rate_hz: MAX_UPDATE_RATE,
+            smoothing_ms: 0,
+            record_session: false,
+            bandwidth_unit: BandwidthUnit::KilobytesPerSec,
+            bandwidth_warn_kbps: None,
+            first_run: true,
+            recent_aircraft: VecDeque::new(),
+            aircraft_families: Vec::new(),
+            profiles: Vec::new(),
+            quick_messages: ["Ready", "Taking off", "Your control", "Hold"]
+                .iter()
+                .map(|text| text.to_string())
+                .collect(),
+            observer_shortcut: "Ctrl+O".to_string(),
+            extra: HashMap::new(),
         }
     }
 }
@@ -56,7 +204,216 @@ impl Config {
         Ok(config)
     }
 
+    /// Update rate kept within the supported range
+    pub fn update_rate(&self) -> u32 {
+        self.update_rate_hz.clamp(MIN_UPDATE_RATE, MAX_UPDATE_RATE)
+    }
+
+    /// Interpolation smoothing kept within the supported range
+    pub fn smoothing(&self) -> u32 {
+        self.smoothing_ms.min(MAX_SMOOTHING_MS)
+    }
+
+    /// Checks values are in range, returning every problem found
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        if self.port == 0 {
+            errors.push("Port must be between 1 and 65535".to_string());
+        }
+
+        if let Err(e) = validate_conn_timeout(self.conn_timeout) {
+            errors.push(e);
+        }
+
+        if self.name.chars().count() > MAX_NAME_LENGTH {
+            errors.push(format!(
+                "Username must be at most {} characters",
+                MAX_NAME_LENGTH
+            ));
+        }
+
+        if !self.ip.is_empty() && self.ip.parse::<std::net::IpAddr>().is_err() {
+            errors.push(format!("{} is not a valid IP address", self.ip));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn push_recent_aircraft(&mut self, config_file_name: &str) {
+        self.recent_aircraft.retain(|name| name != config_file_name);
+        self.recent_aircraft
+            .push_front(config_file_name.to_string());
+        self.recent_aircraft.truncate(MAX_RECENT_AIRCRAFT);
+    }
+
+    /// Adds the profile, replacing any with the same name
+    pub fn save_profile(&mut self, profile: ServerProfile) {
+        match self.profiles.iter_mut().find(|p| p.name == profile.name) {
+            Some(existing) => *existing = profile,
+            None => self.profiles.push(profile),
+        }
+    }
+
+    pub fn remove_profile(&mut self, name: &str) {
+        self.profiles.retain(|profile| profile.name != name);
+    }
+
     pub fn get_json_string(&self) -> String {
         serde_json::to_value(self).unwrap().to_string()
     }
 }
+
+/// Connection timeout in seconds, shared by the config checks and the settings field
+pub fn validate_conn_timeout(secs: u64) -> Result<u64, String> {
+    if secs == 0 || secs > MAX_CONN_TIMEOUT {
+        Err(format!(
+            "Connection timeout must be between 1 and {} seconds",
+            MAX_CONN_TIMEOUT
+        ))
+    } else {
+        Ok(secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_keys_round_trip() {
+        let json = r#"{"name":"Bob","port":7777,"some_future_option":{"enabled":true}}"#;
+
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(config.name, "Bob");
+        assert_eq!(config.port, 7777);
+        assert_eq!(
+            config.extra.get("some_future_option"),
+            Some(&serde_json::json!({"enabled": true}))
+        );
+
+        let saved: serde_json::Value = serde_json::from_str(&config.get_json_string()).unwrap();
+        assert_eq!(saved["some_future_option"]["enabled"], true);
+        assert_eq!(saved["name"], "Bob");
+    }
+
+    #[test]
+    fn test_known_keys_not_duplicated_in_extra() {
+        let config: Config = serde_json::from_str(r#"{"name":"Bob"}"#).unwrap();
+        assert!(config.extra.is_empty());
+    }
+
+    #[test]
+    fn test_recent_aircraft_dedup_and_cap() {
+        let mut config = Config::default();
+        for name in &["a", "b", "c", "d", "e", "f"] {
+            config.push_recent_aircraft(name);
+        }
+        assert_eq!(config.recent_aircraft, vec!["f", "e", "d", "c", "b"]);
+
+        config.push_recent_aircraft("c");
+        assert_eq!(config.recent_aircraft, vec!["c", "f", "e", "d", "b"]);
+    }
+
+    #[test]
+    fn test_profiles_replace_by_name() {
+        let mut config = Config::default();
+        config.save_profile(ServerProfile {
+            name: "Bob".to_string(),
+            session_code: "ABC123".to_string(),
+            ..Default::default()
+        });
+        config.save_profile(ServerProfile {
+            name: "Carol".to_string(),
+            method: ConnectionMethod::Direct,
+            ip: "1.2.3.4".to_string(),
+            ..Default::default()
+        });
+        config.save_profile(ServerProfile {
+            name: "Bob".to_string(),
+            session_code: "XYZ789".to_string(),
+            ..Default::default()
+        });
+
+        assert_eq!(config.profiles.len(), 2);
+        assert_eq!(config.profiles[0].session_code, "XYZ789");
+
+        config.remove_profile("Bob");
+        let names: Vec<&str> = config.profiles.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Carol"]);
+
+        // Survives a save and load
+        let json = config.get_json_string();
+        let loaded: Config = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.profiles, config.profiles);
+    }
+
+    #[test]
+    fn test_validate_defaults_and_missing_fields() {
+        assert!(Config::default().validate().is_ok());
+
+        let config: Config = serde_json::from_str(r#"{"name":"Bob"}"#).unwrap();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_invalid_fields() {
+        let config: Config = serde_json::from_str(r#"{"port":0}"#).unwrap();
+        assert_eq!(config.validate().unwrap_err().len(), 1);
+
+        let config: Config =
+            serde_json::from_str(r#"{"port":0,"conn_timeout":0,"ip":"nope"}"#).unwrap();
+        assert_eq!(config.validate().unwrap_err().len(), 3);
+
+        let config: Config = serde_json::from_str(
+            r#"{"conn_timeout":1000,"name":"a name that is far too long to be shown"}"#,
+        )
+        .unwrap();
+        assert_eq!(config.validate().unwrap_err().len(), 2);
+    }
+
+    #[test]
+    fn test_validate_conn_timeout_range() {
+        assert!(validate_conn_timeout(0).is_err());
+        assert_eq!(validate_conn_timeout(1), Ok(1));
+        assert_eq!(
+            validate_conn_timeout(MAX_CONN_TIMEOUT),
+            Ok(MAX_CONN_TIMEOUT)
+        );
+        assert!(validate_conn_timeout(MAX_CONN_TIMEOUT + 1).is_err());
+    }
+
+    #[test]
+    fn test_negative_values_rejected() {
+        assert!(serde_json::from_str::<Config>(r#"{"conn_timeout":-5}"#).is_err());
+    }
+
+    #[test]
+    fn test_update_rate_clamped() {
+        assert_eq!(Config::default().update_rate(), MAX_UPDATE_RATE);
+
+        let config: Config = serde_json::from_str(r#"{"update_rate_hz":1}"#).unwrap();
+        assert_eq!(config.update_rate(), MIN_UPDATE_RATE);
+
+        let config: Config = serde_json::from_str(r#"{"update_rate_hz":500}"#).unwrap();
+        assert_eq!(config.update_rate(), MAX_UPDATE_RATE);
+
+        let config: Config = serde_json::from_str(r#"{"update_rate_hz":30}"#).unwrap();
+        assert_eq!(config.update_rate(), 30);
+    }
+
+    #[test]
+    fn test_smoothing_clamped() {
+        assert_eq!(Config::default().smoothing(), 0);
+
+        let config: Config = serde_json::from_str(r#"{"smoothing_ms":2000}"#).unwrap();
+        assert_eq!(config.smoothing(), MAX_SMOOTHING_MS);
+
+        let config: Config = serde_json::from_str(r#"{"smoothing_ms":150}"#).unwrap();
+        assert_eq!(config.smoothing(), 150);
+    }
+}
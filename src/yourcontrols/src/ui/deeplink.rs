@@ -0,0 +1,164 @@
+// Join Links
+//
+// Communities share sessions as links such as "yourcontrols://join?code=ABC123" or
+// "yourcontrols://join?ip=1.2.3.4&port=7777". This module turns such a link into the
+// details needed to fill in the Join form.
+
+use super::ConnectionMethod;
+use std::net::IpAddr;
+
+pub const DEEPLINK_SCHEME: &str = "yourcontrols://";
+
+/// Connection details parsed from a join link
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectInfo {
+    pub method: ConnectionMethod,
+    pub session_id: Option<String>,
+    pub ip: Option<IpAddr>,
+    pub port: Option<u16>,
+    pub isipv6: bool,
+}
+
+fn parse_method(method: &str) -> Option<ConnectionMethod> {
+    match method.to_ascii_lowercase().as_str() {
+        "direct" => Some(ConnectionMethod::Direct),
+        "cloud" | "cloudserver" => Some(ConnectionMethod::CloudServer),
+        "relay" => Some(ConnectionMethod::Relay),
+        _ => None,
+    }
+}
+
+fn parse_flag(value: &str) -> Option<bool> {
+    match value {
+        "1" | "true" => Some(true),
+        "0" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse a join link, returning `None` if it is malformed or missing what the method needs
+pub fn parse_join_url(url: &str) -> Option<ConnectInfo> {
+    let rest = url.trim().strip_prefix(DEEPLINK_SCHEME)?;
+    let (path, query) = match rest.find('?') {
+        Some(index) => (&rest[..index], &rest[index + 1..]),
+        None => (rest, ""),
+    };
+
+    if path.trim_end_matches('/') != "join" {
+        return None;
+    }
+
+    let mut method = None;
+    let mut session_id = None;
+    let mut ip: Option<IpAddr> = None;
+    let mut port = None;
+    let mut isipv6 = None;
+
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = match pair.find('=') {
+            Some(index) => (&pair[..index], &pair[index + 1..]),
+            None => (pair, ""),
+        };
+
+        match key {
+            "method" => method = Some(parse_method(value)?),
+            "code" => session_id = Some(value.to_string()).filter(|code| !code.is_empty()),
+            "ip" => ip = Some(value.parse().ok()?),
+            "port" => port = Some(value.parse::<u16>().ok().filter(|port| *port != 0)?),
+            "ipv6" => isipv6 = Some(parse_flag(value)?),
+            // Ignore unknown keys so newer links still work
+            _ => {}
+        }
+    }
+
+    // Infer the method from what was given
+    let method = match method {
+        Some(method) => method,
+        None if ip.is_some() => ConnectionMethod::Direct,
+        None => ConnectionMethod::CloudServer,
+    };
+
+    match method {
+        ConnectionMethod::Direct if ip.is_none() => return None,
+        ConnectionMethod::CloudServer if session_id.is_none() => return None,
+        _ => {}
+    }
+
+    Some(ConnectInfo {
+        method,
+        session_id,
+        isipv6: isipv6.unwrap_or_else(|| ip.is_some_and(|ip| ip.is_ipv6())),
+        ip,
+        port,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cloud_code() {
+        assert_eq!(
+            parse_join_url("yourcontrols://join?code=ABC123"),
+            Some(ConnectInfo {
+                method: ConnectionMethod::CloudServer,
+                session_id: Some("ABC123".to_string()),
+                ip: None,
+                port: None,
+                isipv6: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_direct() {
+        let info = parse_join_url("yourcontrols://join/?ip=1.2.3.4&port=7777").unwrap();
+        assert_eq!(info.method, ConnectionMethod::Direct);
+        assert_eq!(info.ip, Some("1.2.3.4".parse().unwrap()));
+        assert_eq!(info.port, Some(7777));
+        assert!(!info.isipv6);
+    }
+
+    #[test]
+    fn test_parse_ipv6() {
+        let info = parse_join_url("yourcontrols://join?ip=::1&port=7777").unwrap();
+        assert!(info.isipv6);
+
+        let info = parse_join_url("yourcontrols://join?code=ABC&ipv6=1").unwrap();
+        assert!(info.isipv6);
+    }
+
+    #[test]
+    fn test_parse_explicit_method() {
+        let info = parse_join_url("yourcontrols://join?method=relay").unwrap();
+        assert_eq!(info.method, ConnectionMethod::Relay);
+        assert_eq!(info.session_id, None);
+
+        let info = parse_join_url("yourcontrols://join?method=cloud&code=XYZ&extra=1").unwrap();
+        assert_eq!(info.method, ConnectionMethod::CloudServer);
+        assert_eq!(info.session_id, Some("XYZ".to_string()));
+    }
+
+    #[test]
+    fn test_parse_malformed() {
+        let invalid = [
+            "",
+            "join?code=ABC",
+            "https://join?code=ABC",
+            "yourcontrols://host?code=ABC",
+            "yourcontrols://join",
+            "yourcontrols://join?code=",
+            "yourcontrols://join?ip=not-an-ip",
+            "yourcontrols://join?ip=1.2.3.4&port=99999",
+            "yourcontrols://join?ip=1.2.3.4&port=0",
+            "yourcontrols://join?method=direct&code=ABC",
+            "yourcontrols://join?method=carrier-pigeon&code=ABC",
+            "yourcontrols://join?code=ABC&ipv6=maybe",
+        ];
+
+        for url in invalid.iter() {
+            assert_eq!(parse_join_url(url), None, "{}", url);
+        }
+    }
+}
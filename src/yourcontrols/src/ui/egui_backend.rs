@@ -1,13 +1,25 @@
 // egui UI Backend
 
-use super::{AppMessage, ConnectionMethod, UIBackend};
+use super::deeplink::parse_join_url;
+use super::ratelimit::LimitedSender;
+use super::tray::{Tray, TrayAction};
+use super::{AppMessage, ConnectionMethod, StatusState, UIBackend};
+use crate::simconfig::{
+    validate_conn_timeout, BandwidthUnit, Config, ServerProfile, SoundEvent, MAX_SMOOTHING_MS,
+    MAX_UPDATE_RATE, MIN_UPDATE_RATE,
+};
+use crate::util::{aircraft_compatible, aircraft_matches_config};
 use crossbeam_channel::{unbounded, Receiver, Sender, TryRecvError};
 use eframe::egui;
-use std::collections::VecDeque;
+use log::{info, warn};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::net::IpAddr;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
 };
+use std::time::{Duration, Instant};
+use yourcontrols_net::{CAPABILITY_CHAT, CAPABILITY_SEATS, CAPABILITY_SESSION_LOCK};
 
 // egui-based UI backend
 pub struct EguiBackend {
@@ -17,29 +29,139 @@ pub struct EguiBackend {
 }
 
 // Events sent from the application to the UI
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum UiEvent {
     Error(String),
     Attempt,
     Connected,
+    RelayFallback,
     ServerFail(String),
+    ServerBindFailed {
+        addr: String,
+        reason: String,
+        suggested_port: Option<u16>,
+    },
     ClientFail(String),
+    SetStatus(StatusState),
+    ClearStatus,
+    SimDisconnected,
+    SimConnected(bool),
+    DetectedAircraft(String),
+    RequiredAircraft(String),
+    UnsupportedAircraft(String),
+    AircraftLoaded(String),
+    Capacity {
+        current: usize,
+        max: usize,
+    },
+    SessionLocked(bool),
+    ServerCapabilities(Vec<String>),
+    SyncPaused(bool),
+    Syncing(bool),
+    Deeplink(String),
+    Prediction(bool),
+    ScanningAircraft(bool),
+    SyncCaveats(Vec<String>),
+    ConfigErrors(Vec<String>),
+    QualityReduced {
+        active: bool,
+        reason: String,
+    },
+    SetSelfName(String),
+    PublicIpCheck {
+        ip: Option<String>,
+        reachable: Option<bool>,
+    },
+    UpnpResult {
+        success: bool,
+        external_port: Option<u16>,
+    },
+    TestConnectionResult {
+        success: bool,
+        detail: String,
+    },
+    RelayRegion {
+        region: String,
+        ping: Option<f32>,
+    },
+    RequestAttention,
     GainControl,
     LoseControl,
+    InputActivity,
     ServerStarted,
     SessionCode(String),
     SetHost,
+    PublicEndpoint(String),
+    NatResult {
+        punched: bool,
+        relay_fallback: bool,
+    },
     NewConnection(String),
+    NewConnectionDetailed {
+        name: String,
+        aircraft: Option<String>,
+        version: Option<String>,
+    },
     LostConnection(String),
+    ClientPosition {
+        name: String,
+        lat: f64,
+        lon: f64,
+        heading: f32,
+    },
+    ClientChangingAircraft {
+        name: String,
+        to: String,
+    },
     Observing(bool),
     SetObserving {
         name: String,
         observing: bool,
     },
+    SetSpectator(String),
+    ClientInputActive {
+        name: String,
+        active: bool,
+    },
+    SetClientReady {
+        name: String,
+        ready: bool,
+    },
+    ClientSeat {
+        name: String,
+        seat: Option<String>,
+    },
+    ClockOffset {
+        name: String,
+        offset: f32,
+    },
     SetInControl(String),
+    ControlChanged {
+        new: String,
+        previous: Option<String>,
+    },
+    ControlTransferFailed {
+        target: String,
+        reason: String,
+    },
+    ControlCountdown {
+        seconds_remaining: u32,
+        next: String,
+    },
     AddAircraft(String),
+    NoAircraftFound(String),
     Version(String),
+    UpdateReady(String),
+    SyncValues(Vec<(String, String)>),
     UpdateFailed,
+    UpdateFailedDetail {
+        reason: String,
+        retryable: bool,
+    },
+    UpdateConfirm {
+        files: Vec<String>,
+        size: u64,
+    },
     SendConfig(String),
     SendMetrics {
         sent_packets: u64,
@@ -49,6 +171,29 @@ pub enum UiEvent {
         packet_loss: f32,
         ping: f32,
     },
+    QueueDepth {
+        outbound: usize,
+        inbound: usize,
+    },
+    UpdateRate(f32),
+    SmoothingChanged(u32),
+    FullMetrics {
+        sent_packets: u64,
+        received_packets: u64,
+        acked_packets: u64,
+        sent_kbps: f32,
+        receive_kbps: f32,
+        packet_loss: f32,
+        rtt: f32,
+    },
+    ChatMessage {
+        from: String,
+        text: String,
+    },
+    DesyncVars {
+        client: String,
+        vars: Vec<String>,
+    },
 }
 
 impl UIBackend for EguiBackend {
@@ -57,10 +202,8 @@ impl UIBackend for EguiBackend {
         let (event_tx, event_rx) = unbounded();
 
         let exited = Arc::new(AtomicBool::new(false));
-        let exited_clone = exited.clone();
 
-        // Spawn egui window in separate thread
-        std::thread::spawn(move || {
+        let run = move || {
             use eframe::egui::ViewportBuilder;
 
             #[cfg(target_os = "windows")]
@@ -82,8 +225,10 @@ impl UIBackend for EguiBackend {
             let options = eframe::NativeOptions {
                 viewport: ViewportBuilder::default()
                     .with_title(&title)
-                    .with_inner_size([1000.0, 800.0])
-                    .with_min_inner_size([800.0, 600.0]),
+                    .with_inner_size(FULL_SIZE)
+                    .with_min_inner_size(FULL_MIN_SIZE)
+                    // Lets the overlay see through to the sim
+                    .with_transparent(true),
                 event_loop_builder,
                 ..Default::default()
             };
@@ -93,9 +238,25 @@ impl UIBackend for EguiBackend {
             if let Err(e) = eframe::run_native(&title, options, Box::new(|_cc| Ok(Box::new(app)))) {
                 eprintln!("egui error: {}", e);
             }
+        };
 
-            exited_clone.store(true, Ordering::SeqCst);
-        });
+        #[cfg(target_os = "macos")]
+        {
+            if MAIN_THREAD_RUNNER.load(Ordering::SeqCst) {
+                let exited = exited.clone();
+                *MAIN_THREAD_WINDOW.lock().unwrap() = Some(Box::new(move || {
+                    let _guard = ExitGuard(exited);
+                    run();
+                }));
+            } else {
+                warn!("[UI] Not started through run_with_main_thread, the window may fail to open");
+                spawn_ui_thread(exited.clone(), run);
+            }
+        }
+
+        // Spawn egui window in separate thread
+        #[cfg(not(target_os = "macos"))]
+        spawn_ui_thread(exited.clone(), run);
 
         Self {
             exited,
@@ -109,68 +270,583 @@ impl UIBackend for EguiBackend {
     }
 
     fn get_next_message(&self) -> Result<AppMessage, TryRecvError> {
-        self.rx.try_recv()
+        let message = self.rx.try_recv();
+        if let Err(TryRecvError::Disconnected) = message {
+            self.exited.store(true, Ordering::SeqCst);
+        }
+        message
     }
 
     fn invoke(&self, type_string: &str, data: Option<&str>) {
-        let event = match type_string {
-            "error" => UiEvent::Error(data.unwrap_or("Unknown error").to_string()),
-            "attempt" => UiEvent::Attempt,
-            "connected" => UiEvent::Connected,
-            "server_fail" => UiEvent::ServerFail(data.unwrap_or("Unknown reason").to_string()),
-            "client_fail" => UiEvent::ClientFail(data.unwrap_or("Unknown reason").to_string()),
-            "control" => UiEvent::GainControl,
-            "lostcontrol" => UiEvent::LoseControl,
-            "server" => UiEvent::ServerStarted,
-            "session" => UiEvent::SessionCode(data.unwrap_or("").to_string()),
-            "host" => UiEvent::SetHost,
-            "newconnection" => UiEvent::NewConnection(data.unwrap_or("").to_string()),
-            "lostconnection" => UiEvent::LostConnection(data.unwrap_or("").to_string()),
-            "observing" => UiEvent::Observing(true),
-            "stop_observing" => UiEvent::Observing(false),
-            "set_observing" => UiEvent::SetObserving {
-                name: data.unwrap_or("").to_string(),
-                observing: true,
-            },
-            "set_not_observing" => UiEvent::SetObserving {
-                name: data.unwrap_or("").to_string(),
-                observing: false,
-            },
-            "set_incontrol" => UiEvent::SetInControl(data.unwrap_or("").to_string()),
-            "add_aircraft" => UiEvent::AddAircraft(data.unwrap_or("").to_string()),
-            "version" => UiEvent::Version(data.unwrap_or("").to_string()),
-            "update_failed" => UiEvent::UpdateFailed,
-            "config_msg" => UiEvent::SendConfig(data.unwrap_or("{}").to_string()),
-            "metrics" => {
-                if let Some(data) = data {
-                    if let Ok(json) = serde_json::from_str::<serde_json::Value>(data) {
-                        self.event_tx
-                            .send(UiEvent::SendMetrics {
-                                sent_packets: json["sentPackets"].as_u64().unwrap_or(0),
-                                received_packets: json["receivePackets"].as_u64().unwrap_or(0),
-                                sent_kbps: json["sentBandwidth"].as_f64().unwrap_or(0.0) as f32,
-                                receive_kbps: json["receiveBandwidth"].as_f64().unwrap_or(0.0)
-                                    as f32,
-                                packet_loss: json["packetLoss"].as_f64().unwrap_or(0.0) as f32,
-                                ping: json["ping"].as_f64().unwrap_or(0.0) as f32,
-                            })
-                            .ok();
-                        return;
-                    }
-                }
-                return;
+        if let Some(event) = map_invoke(type_string, data) {
+            self.event_tx.send(event).ok();
+        }
+    }
+}
+
+// Flags the UI as exited when dropped, including while unwinding from a panic
+struct ExitGuard(Arc<AtomicBool>);
+
+impl Drop for ExitGuard {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+// macOS only allows an event loop on the main thread, where run_with_main_thread picks the window up
+#[cfg(target_os = "macos")]
+static MAIN_THREAD_RUNNER: AtomicBool = AtomicBool::new(false);
+#[cfg(target_os = "macos")]
+static MAIN_THREAD_WINDOW: std::sync::Mutex<Option<Box<dyn FnOnce() + Send>>> =
+    std::sync::Mutex::new(None);
+
+/// Runs `app` on a new thread and opens the window it sets up on this one, which must be
+/// the main thread. Returns once `app` does, after the window has closed.
+#[cfg(target_os = "macos")]
+pub fn run_with_main_thread<F>(app: F)
+where
+    F: FnOnce() + Send + 'static,
+{
+    MAIN_THREAD_RUNNER.store(true, Ordering::SeqCst);
+    let app_thread = std::thread::spawn(app);
+
+    while !app_thread.is_finished() {
+        let window = MAIN_THREAD_WINDOW.lock().unwrap().take();
+        match window {
+            Some(window) => window(),
+            None => std::thread::sleep(Duration::from_millis(10)),
+        }
+    }
+
+    if let Err(panic) = app_thread.join() {
+        std::panic::resume_unwind(panic);
+    }
+}
+
+fn spawn_ui_thread<F>(exited: Arc<AtomicBool>, run: F) -> std::thread::JoinHandle<()>
+where
+    F: FnOnce() + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let _guard = ExitGuard(exited);
+        run();
+    })
+}
+
+// Maps an invoke type string and its payload to the event the egui app handles
+fn map_invoke(type_string: &str, data: Option<&str>) -> Option<UiEvent> {
+    let event = match type_string {
+        "error" => UiEvent::Error(data.unwrap_or("Unknown error").to_string()),
+        "attempt" => UiEvent::Attempt,
+        "connected" => UiEvent::Connected,
+        "relay_fallback" => UiEvent::RelayFallback,
+        "server_fail" => UiEvent::ServerFail(data.unwrap_or("Unknown reason").to_string()),
+        "server_bind_failed" => {
+            let json = serde_json::from_str::<serde_json::Value>(data?).ok()?;
+            UiEvent::ServerBindFailed {
+                addr: json["addr"].as_str()?.to_string(),
+                reason: json["reason"].as_str()?.to_string(),
+                suggested_port: json["suggestedPort"].as_u64().map(|port| port as u16),
             }
-            _ => return, // Unknown event type
-        };
+        }
+        "client_fail" => UiEvent::ClientFail(data.unwrap_or("Unknown reason").to_string()),
+        "request_attention" => UiEvent::RequestAttention,
+        "control" => UiEvent::GainControl,
+        "lostcontrol" => UiEvent::LoseControl,
+        "input_activity" => UiEvent::InputActivity,
+        "sim_disconnected" => UiEvent::SimDisconnected,
+        "sim_connected" => UiEvent::SimConnected(true),
+        "sim_unavailable" => UiEvent::SimConnected(false),
+        "detected_aircraft" => UiEvent::DetectedAircraft(data?.to_string()),
+        "required_aircraft" => UiEvent::RequiredAircraft(data?.to_string()),
+        "unsupported_aircraft" => UiEvent::UnsupportedAircraft(data?.to_string()),
+        "aircraft_loaded" => UiEvent::AircraftLoaded(data?.to_string()),
+        "capacity" => {
+            let json = serde_json::from_str::<serde_json::Value>(data?).ok()?;
+            UiEvent::Capacity {
+                current: json["current"].as_u64().unwrap_or(0) as usize,
+                max: json["max"].as_u64().unwrap_or(0) as usize,
+            }
+        }
+        "session_locked" => UiEvent::SessionLocked(true),
+        "session_unlocked" => UiEvent::SessionLocked(false),
+        "syncing" => UiEvent::Syncing(true),
+        "synced" => UiEvent::Syncing(false),
+        "server_capabilities" => UiEvent::ServerCapabilities(serde_json::from_str(data?).ok()?),
+        "sync_paused" => UiEvent::SyncPaused(true),
+        "sync_resumed" => UiEvent::SyncPaused(false),
+        "deeplink" => UiEvent::Deeplink(data?.to_string()),
+        "predicting" => UiEvent::Prediction(true),
+        "stop_predicting" => UiEvent::Prediction(false),
+        "scanning_aircraft" => UiEvent::ScanningAircraft(true),
+        "scanning_aircraft_done" => UiEvent::ScanningAircraft(false),
+        "sync_caveats" => UiEvent::SyncCaveats(serde_json::from_str(data?).ok()?),
+        "config_errors" => UiEvent::ConfigErrors(serde_json::from_str(data?).ok()?),
+        "quality_reduced" => {
+            let json = serde_json::from_str::<serde_json::Value>(data?).ok()?;
+            UiEvent::QualityReduced {
+                active: json["active"].as_bool().unwrap_or(false),
+                reason: json["reason"].as_str().unwrap_or("").to_string(),
+            }
+        }
+        "self_name" => UiEvent::SetSelfName(data.unwrap_or("").to_string()),
+        "clear_status" => UiEvent::ClearStatus,
+        "status" => UiEvent::SetStatus(serde_json::from_str(data?).ok()?),
+        "public_ip_check" => {
+            let json = serde_json::from_str::<serde_json::Value>(data?).ok()?;
+            UiEvent::PublicIpCheck {
+                ip: json["ip"].as_str().map(String::from),
+                reachable: json["reachable"].as_bool(),
+            }
+        }
+        "test_connection_result" => {
+            let json = serde_json::from_str::<serde_json::Value>(data?).ok()?;
+            UiEvent::TestConnectionResult {
+                success: json["success"].as_bool().unwrap_or(false),
+                detail: json["detail"].as_str().unwrap_or_default().to_string(),
+            }
+        }
+        "upnp_result" => {
+            let json = serde_json::from_str::<serde_json::Value>(data?).ok()?;
+            UiEvent::UpnpResult {
+                success: json["success"].as_bool().unwrap_or(false),
+                external_port: json["externalPort"].as_u64().map(|port| port as u16),
+            }
+        }
+        "relay_region" => {
+            let json = serde_json::from_str::<serde_json::Value>(data?).ok()?;
+            UiEvent::RelayRegion {
+                region: json["region"].as_str()?.to_string(),
+                ping: json["ping"].as_f64().map(|ping| ping as f32),
+            }
+        }
+        "server" => UiEvent::ServerStarted,
+        "session" => UiEvent::SessionCode(data.unwrap_or("").to_string()),
+        "host" => UiEvent::SetHost,
+        "public_endpoint" => UiEvent::PublicEndpoint(data.unwrap_or("").to_string()),
+        "nat_result" => {
+            let json = serde_json::from_str::<serde_json::Value>(data?).ok()?;
+            UiEvent::NatResult {
+                punched: json["punched"].as_bool().unwrap_or(false),
+                relay_fallback: json["relayFallback"].as_bool().unwrap_or(false),
+            }
+        }
+        "newconnection" => UiEvent::NewConnection(data.unwrap_or("").to_string()),
+        "newconnection_detailed" => {
+            let json: serde_json::Value =
+                serde_json::from_str(data.unwrap_or("{}")).unwrap_or_default();
+            UiEvent::NewConnectionDetailed {
+                name: json["name"].as_str().unwrap_or("").to_string(),
+                aircraft: json["aircraft"].as_str().map(String::from),
+                version: json["version"].as_str().map(String::from),
+            }
+        }
+        "lostconnection" => UiEvent::LostConnection(data.unwrap_or("").to_string()),
+        "client_changing_aircraft" => {
+            let json = serde_json::from_str::<serde_json::Value>(data?).ok()?;
+            UiEvent::ClientChangingAircraft {
+                name: json["name"].as_str()?.to_string(),
+                to: json["to"].as_str()?.to_string(),
+            }
+        }
+        "client_position" => {
+            let json = serde_json::from_str::<serde_json::Value>(data?).ok()?;
+            UiEvent::ClientPosition {
+                name: json["name"].as_str()?.to_string(),
+                lat: json["lat"].as_f64()?,
+                lon: json["lon"].as_f64()?,
+                heading: json["heading"].as_f64().unwrap_or(0.0) as f32,
+            }
+        }
+        "observing" => UiEvent::Observing(true),
+        "stop_observing" => UiEvent::Observing(false),
+        "set_observing" => UiEvent::SetObserving {
+            name: data.unwrap_or("").to_string(),
+            observing: true,
+        },
+        "set_not_observing" => UiEvent::SetObserving {
+            name: data.unwrap_or("").to_string(),
+            observing: false,
+        },
+        "set_spectator" => UiEvent::SetSpectator(data.unwrap_or("").to_string()),
+        "client_input_active" => UiEvent::ClientInputActive {
+            name: data.unwrap_or("").to_string(),
+            active: true,
+        },
+        "client_input_stalled" => UiEvent::ClientInputActive {
+            name: data.unwrap_or("").to_string(),
+            active: false,
+        },
+        "set_ready" => UiEvent::SetClientReady {
+            name: data.unwrap_or("").to_string(),
+            ready: true,
+        },
+        "set_not_ready" => UiEvent::SetClientReady {
+            name: data.unwrap_or("").to_string(),
+            ready: false,
+        },
+        "set_incontrol" => UiEvent::SetInControl(data.unwrap_or("").to_string()),
+        "client_seat" => {
+            let json = serde_json::from_str::<serde_json::Value>(data?).ok()?;
+            UiEvent::ClientSeat {
+                name: json["name"].as_str()?.to_string(),
+                seat: json["seat"].as_str().map(String::from),
+            }
+        }
+        "clock_offset" => {
+            let json = serde_json::from_str::<serde_json::Value>(data?).ok()?;
+            UiEvent::ClockOffset {
+                name: json["name"].as_str()?.to_string(),
+                offset: json["offset"].as_f64()? as f32,
+            }
+        }
+        "control_changed" => {
+            let json = serde_json::from_str::<serde_json::Value>(data?).ok()?;
+            UiEvent::ControlChanged {
+                new: json["new"].as_str()?.to_string(),
+                previous: json["previous"].as_str().map(String::from),
+            }
+        }
+        "control_transfer_failed" => {
+            let json: serde_json::Value =
+                serde_json::from_str(data.unwrap_or("{}")).unwrap_or_default();
+            UiEvent::ControlTransferFailed {
+                target: json["target"].as_str().unwrap_or("").to_string(),
+                reason: json["reason"]
+                    .as_str()
+                    .unwrap_or("Unknown reason")
+                    .to_string(),
+            }
+        }
+        "control_countdown" => {
+            let json: serde_json::Value =
+                serde_json::from_str(data.unwrap_or("{}")).unwrap_or_default();
+            UiEvent::ControlCountdown {
+                seconds_remaining: json["secondsRemaining"].as_u64().unwrap_or(0) as u32,
+                next: json["next"].as_str().unwrap_or("").to_string(),
+            }
+        }
+        "add_aircraft" => UiEvent::AddAircraft(data.unwrap_or("").to_string()),
+        "no_aircraft_found" => UiEvent::NoAircraftFound(data.unwrap_or("").to_string()),
+        "version" => UiEvent::Version(data.unwrap_or("").to_string()),
+        "update_ready" => UiEvent::UpdateReady(data.unwrap_or("").to_string()),
+        "sync_values" => UiEvent::SyncValues(serde_json::from_str(data?).ok()?),
+        "update_failed" => UiEvent::UpdateFailed,
+        "update_failed_detail" => {
+            let json = serde_json::from_str::<serde_json::Value>(data?).ok()?;
+            UiEvent::UpdateFailedDetail {
+                reason: json["reason"].as_str()?.to_string(),
+                retryable: json["retryable"].as_bool().unwrap_or(false),
+            }
+        }
+        "update_confirm" => {
+            let json = serde_json::from_str::<serde_json::Value>(data?).ok()?;
+            UiEvent::UpdateConfirm {
+                files: json["files"]
+                    .as_array()?
+                    .iter()
+                    .filter_map(|file| file.as_str().map(String::from))
+                    .collect(),
+                size: json["size"].as_u64()?,
+            }
+        }
+        "config_msg" => UiEvent::SendConfig(data.unwrap_or("{}").to_string()),
+        "metrics" => {
+            let json = serde_json::from_str::<serde_json::Value>(data?).ok()?;
+            UiEvent::SendMetrics {
+                sent_packets: json["sentPackets"].as_u64().unwrap_or(0),
+                received_packets: json["receivePackets"].as_u64().unwrap_or(0),
+                sent_kbps: json["sentBandwidth"].as_f64().unwrap_or(0.0) as f32,
+                receive_kbps: json["receiveBandwidth"].as_f64().unwrap_or(0.0) as f32,
+                packet_loss: json["packetLoss"].as_f64().unwrap_or(0.0) as f32,
+                ping: json["ping"].as_f64().unwrap_or(0.0) as f32,
+            }
+        }
+        "metrics_full" => {
+            let json = serde_json::from_str::<serde_json::Value>(data?).ok()?;
+            UiEvent::FullMetrics {
+                sent_packets: json["sentPackets"].as_u64().unwrap_or(0),
+                received_packets: json["receivePackets"].as_u64().unwrap_or(0),
+                acked_packets: json["ackedPackets"].as_u64().unwrap_or(0),
+                sent_kbps: json["sentBandwidth"].as_f64().unwrap_or(0.0) as f32,
+                receive_kbps: json["receiveBandwidth"].as_f64().unwrap_or(0.0) as f32,
+                packet_loss: json["packetLoss"].as_f64().unwrap_or(0.0) as f32,
+                rtt: json["rtt"].as_f64().unwrap_or(0.0) as f32,
+            }
+        }
+        "queue_depth" => {
+            let json = serde_json::from_str::<serde_json::Value>(data?).ok()?;
+            UiEvent::QueueDepth {
+                outbound: json["outbound"].as_u64().unwrap_or(0) as usize,
+                inbound: json["inbound"].as_u64().unwrap_or(0) as usize,
+            }
+        }
+        "update_rate" => UiEvent::UpdateRate(data?.parse().ok()?),
+        "smoothing_changed" => UiEvent::SmoothingChanged(data?.parse().ok()?),
+        "desync_vars" => {
+            let json = serde_json::from_str::<serde_json::Value>(data?).ok()?;
+            UiEvent::DesyncVars {
+                client: json["client"].as_str()?.to_string(),
+                vars: json["vars"]
+                    .as_array()?
+                    .iter()
+                    .filter_map(|var| var.as_str().map(str::to_string))
+                    .collect(),
+            }
+        }
+        "chat_message" => {
+            let json = serde_json::from_str::<serde_json::Value>(data?).ok()?;
+            UiEvent::ChatMessage {
+                from: json["from"].as_str()?.to_string(),
+                text: json["text"].as_str()?.to_string(),
+            }
+        }
+        _ => {
+            warn!("[UI] Unknown invoke type {}", type_string);
+            return None;
+        }
+    };
+
+    Some(event)
+}
+
+// Suffixes a name already in the roster ("Bob (2)") so two clients never merge
+fn unique_client_name(clients: &[ClientInfo], name: &str) -> String {
+    let taken = |candidate: &str| clients.iter().any(|c| c.name == candidate);
+    if !taken(name) {
+        return name.to_string();
+    }
+
+    (2..)
+        .map(|n| format!("{} ({})", name, n))
+        .find(|candidate| !taken(candidate))
+        .expect("unbounded range")
+}
+
+// Password to send, None when the field is left empty
+fn password_value(input: &str) -> Option<String> {
+    Some(input.trim().to_string()).filter(|password| !password.is_empty())
+}
+
+// (pilots, observers) in the roster, spectators count as observers
+fn roster_counts(clients: &[ClientInfo]) -> (usize, usize) {
+    let observers = clients.iter().filter(|client| client.is_observer).count();
+    (clients.len() - observers, observers)
+}
 
-        self.event_tx.send(event).ok();
+// Moves the keyboard selection one row, staying within a list of `len` rows
+fn move_selection(selected: Option<usize>, len: usize, down: bool) -> Option<usize> {
+    if len == 0 {
+        return None;
     }
+
+    Some(match selected {
+        None if down => 0,
+        None => len - 1,
+        Some(index) if down => (index + 1).min(len - 1),
+        Some(index) => index.saturating_sub(1).min(len - 1),
+    })
+}
+
+// Places positions around the centre of a minimap with the given radius, north up
+fn minimap_offsets(positions: &[(f64, f64)], radius: f32) -> Vec<egui::Vec2> {
+    let bounds = positions.iter().fold(None, |bounds, &(lat, lon)| {
+        Some(match bounds {
+            None => (lat, lat, lon, lon),
+            Some((min_lat, max_lat, min_lon, max_lon)) => (
+                f64::min(min_lat, lat),
+                f64::max(max_lat, lat),
+                f64::min(min_lon, lon),
+                f64::max(max_lon, lon),
+            ),
+        })
+    });
+    let (min_lat, max_lat, min_lon, max_lon) = match bounds {
+        Some(bounds) => bounds,
+        None => return Vec::new(),
+    };
+
+    let centre = ((min_lat + max_lat) / 2.0, (min_lon + max_lon) / 2.0);
+    let half_span = f64::max(max_lat - min_lat, max_lon - min_lon) / 2.0;
+    // Everyone in the same spot sits in the middle
+    let scale = if half_span > 0.0 {
+        f64::from(radius) / half_span
+    } else {
+        0.0
+    };
+
+    positions
+        .iter()
+        .map(|&(lat, lon)| {
+            egui::vec2(
+                ((lon - centre.1) * scale) as f32,
+                ((centre.0 - lat) * scale) as f32,
+            )
+        })
+        .collect()
+}
+
+// Parses shortcuts like "Ctrl+Shift+O", None if a part isn't recognised
+fn parse_shortcut(text: &str) -> Option<egui::KeyboardShortcut> {
+    let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+    let key = egui::Key::from_name(parts.pop()?)?;
+
+    let mut modifiers = egui::Modifiers::NONE;
+    for part in parts {
+        modifiers = modifiers
+            | match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => egui::Modifiers::CTRL,
+                "shift" => egui::Modifiers::SHIFT,
+                "alt" => egui::Modifiers::ALT,
+                _ => return None,
+            };
+    }
+
+    Some(egui::KeyboardShortcut::new(modifiers, key))
+}
+
+// Colors for good and bad states, (good, bad)
+// Color-blind mode swaps green/red for blue/orange, which stay distinct with red-green color blindness
+fn status_colors(colorblind: bool) -> (egui::Color32, egui::Color32) {
+    if colorblind {
+        (
+            egui::Color32::from_rgb(0, 114, 178),
+            egui::Color32::from_rgb(230, 159, 0),
+        )
+    } else {
+        (egui::Color32::GREEN, egui::Color32::RED)
+    }
+}
+
+// Whether version `a` is newer than `b`, false if either can't be parsed
+fn is_newer(a: &str, b: &str) -> bool {
+    let parse = |version: &str| semver::Version::parse(version.trim().trim_start_matches('v'));
+    match (parse(a), parse(b)) {
+        (Ok(a), Ok(b)) => a > b,
+        _ => false,
+    }
+}
+
+// Synced values whose name contains the filter, ignoring case
+fn filter_sync_values<'a>(
+    values: &'a [(String, String)],
+    filter: &str,
+) -> impl Iterator<Item = &'a (String, String)> + 'a {
+    let filter = filter.trim().to_lowercase();
+    values
+        .iter()
+        .filter(move |(name, _)| name.to_lowercase().contains(&filter))
+}
+
+// Status text shown when nothing more specific applies
+fn neutral_status(is_connected: bool) -> &'static str {
+    if is_connected {
+        "Connected"
+    } else {
+        "Not connected"
+    }
+}
+
+// First line of the status, and whether anything after it was cut
+fn status_summary(message: &str) -> (&str, bool) {
+    let first = message.lines().next().unwrap_or_default().trim_end();
+    (first, first.len() < message.trim_end().len())
+}
+
+// Whether dirty settings have been left alone long enough to save
+fn autosave_due(dirty: bool, last_change: Instant, now: Instant) -> bool {
+    dirty && now.saturating_duration_since(last_change) >= AUTOSAVE_DELAY
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Quality {
+    Good,
+    Fair,
+    Poor,
+}
+
+impl Quality {
+    fn label(self) -> &'static str {
+        match self {
+            Quality::Good => "Good",
+            Quality::Fair => "Fair",
+            Quality::Poor => "Poor",
+        }
+    }
+
+    fn color(self, colorblind: bool) -> egui::Color32 {
+        let (good, bad) = status_colors(colorblind);
+        match self {
+            Quality::Good => good,
+            Quality::Fair => egui::Color32::YELLOW,
+            Quality::Poor => bad,
+        }
+    }
+}
+
+// Rates the connection from ping (ms), packet loss (fraction) and bandwidth (KB/s)
+fn quality_score(ping: f32, loss: f32, bandwidth: f32) -> Quality {
+    if ping > FAIR_PING || loss > DEGRADED_PACKET_LOSS {
+        Quality::Poor
+    } else if ping > GOOD_PING || loss > GOOD_PACKET_LOSS || bandwidth > HEAVY_BANDWIDTH {
+        Quality::Fair
+    } else {
+        Quality::Good
+    }
+}
+
+// Exponential moving average, so single spikes don't trip the cap warning
+fn rolling_average(average: Option<f32>, sample: f32) -> f32 {
+    match average {
+        Some(average) => average + (sample - average) * BANDWIDTH_AVERAGE_WEIGHT,
+        None => sample,
+    }
+}
+
+// Whether the cap warning should show, with some slack before it clears again
+fn over_bandwidth_cap(average: f32, cap: f32, warning: bool) -> bool {
+    if warning {
+        average >= cap * BANDWIDTH_WARN_RESET
+    } else {
+        average > cap
+    }
+}
+
+// Formats a bandwidth reported in KB/s in the chosen unit
+fn format_bandwidth(kbps: f32, unit: BandwidthUnit) -> String {
+    match unit {
+        BandwidthUnit::KilobytesPerSec => format!("{:.2} KB/s", kbps),
+        BandwidthUnit::Megabits => format!("{:.2} Mbps", kbps * 0.008),
+    }
+}
+
+// Top level pages of the window
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Tab {
+    Connect,
+    Session,
+    Settings,
+    Advanced,
+}
+
+const TABS: [(Tab, &str); 4] = [
+    (Tab::Connect, "🔌 Connect"),
+    (Tab::Session, "👥 Session"),
+    (Tab::Settings, "⚙ Settings"),
+    (Tab::Advanced, "🔧 Advanced"),
+];
+
+// Halves of the Connect tab
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ConnectColumn {
+    Host,
+    Join,
 }
 
 // The egui application state
 struct YourControlsApp {
     // Communication
-    action_tx: Sender<AppMessage>,
+    action_tx: LimitedSender<AppMessage>,
     event_rx: Receiver<UiEvent>,
     event_queue: VecDeque<UiEvent>,
 
@@ -180,30 +856,255 @@ struct YourControlsApp {
     port: String,
     ip_input: String,
     is_connected: bool,
+    status_state: StatusState,
+    predicting: bool,
+    is_host: bool,
+    // Show the Connect tab column that doesn't apply to the current role anyway
+    show_inactive_column: bool,
+    in_control: bool,
     status_message: String,
     server_connection_method: ConnectionMethod,
     client_connection_method: ConnectionMethod,
     is_ipv6: bool,
+    join_as_spectator: bool,
+    // Public address when hosting direct, empty if it couldn't be determined
+    public_endpoint: Option<String>,
+    // Code others join our hosted cloud session with
+    hosted_session_code: Option<String>,
+    // Why updates are being sent less often, while they are
+    quality_reduced: Option<String>,
+    session_locked: bool,
+    host_password: String,
+    join_password: String,
+    // Reveal typed passwords, never while streaming
+    show_passwords: bool,
+    // None while a Test Connection is running
+    connection_test: Option<Option<(bool, String)>>,
+    // What the connected server advertised, None until it says
+    server_capabilities: Option<HashSet<String>>,
+    sync_paused: bool,
+    // Percent of the initial state received after joining, None once complete
+    syncing: bool,
+    // Sessions can't start until the simulator is running
+    sim_connected: bool,
+    // Title of the aircraft loaded in the sim, and the definition file loaded for it
+    detected_aircraft: Option<String>,
+    loaded_aircraft: Option<String>,
+    // Aircraft the host is flying, everyone in the session should load it
+    required_aircraft: Option<String>,
+    // Title of the detected aircraft when no definition exists for it
+    unsupported_aircraft: Option<String>,
+    // Connected clients and the most allowed, 0 meaning no limit
+    capacity: Option<(usize, usize)>,
+    max_clients_input: String,
+    // Outcome of the UPnP mapping, (success, external_port)
+    upnp_result: Option<(bool, Option<u16>)>,
+    // Public IP and whether our port looks reachable, for direct hosts
+    public_ip_check: Option<(Option<String>, Option<bool>)>,
+    // Outcome of the last cloud connection, (punched, relay_fallback)
+    nat_result: Option<(bool, bool)>,
+    // Relay the session goes through and its ping once measured
+    relay_region: Option<(String, Option<f32>)>,
 
     // Client list
     clients: Vec<ClientInfo>,
+    // Our own ready status as last sent to the app
+    self_ready: bool,
+    // Client we asked to give control to, until the server confirms or rejects
+    pending_transfer: Option<String>,
+    // Row picked with the arrow keys, and who Enter asked to give control to
+    selected_client: Option<usize>,
+    confirm_transfer: Option<String>,
+
+    toast: Option<Toast>,
+    // Last Connect/StartServer sent, reused by the reconnect button
+    last_connect: Option<AppMessage>,
+    connection_failed: bool,
+    // Asking whether to disconnect while others rely on us flying
+    confirm_disconnect: bool,
+    // Aircraft picked mid-session, loaded once the switch is confirmed
+    pending_aircraft: Option<String>,
+    // Sim closed while the session was kept alive, ask whether to leave it
+    confirm_sim_lost: bool,
+    // Minutes in control without input before offering to go observer
+    afk_observer_mins: Option<u32>,
+    // Minutes in control before handing it to the next pilot
+    control_rotation_mins: Option<u32>,
+    last_activity: Instant,
+    // When the idle prompt appeared
+    afk_prompt_since: Option<Instant>,
+    // Seconds left and who receives control next
+    control_countdown: Option<(u32, String)>,
+
+    // Saved join details, indexes into config.profiles
+    selected_profile: Option<usize>,
+    profile_name_input: String,
 
     // Aircraft selection
     selected_aircraft: usize,
     aircraft_list: Vec<String>,
+    // Folder that was searched when the scan found no aircraft configs
+    no_aircraft_path: Option<String>,
+    scanning_aircraft: bool,
+    // Known sync limitations of the loaded aircraft
+    sync_caveats: Vec<String>,
 
     // Settings
-    connection_timeout: String,
+    connection_timeout: u64,
     instructor_mode: bool,
     streamer_mode: bool,
     sound_muted: bool,
+    sound_events: HashSet<SoundEvent>,
     dark_theme: bool,
+    current_tab: Tab,
+    // Live (name, value) of every synced variable, and the inspector's search
+    sync_values: Vec<(String, String)>,
+    sync_values_filter: String,
+    // Inspector was drawn open this frame, and whether the app was told to send values
+    sync_inspector_open: bool,
+    sync_values_watched: bool,
+    // Getting started overlay, shown until dismissed
+    first_run: bool,
+    // Newer version that can be installed
+    update_available: Option<String>,
+    // Files and size of a downloaded update waiting on approval
+    update_confirm: Option<(Vec<String>, u64)>,
+    // Why the last update attempt failed and whether it can be retried
+    update_error: Option<(String, bool)>,
+    update_retries: u32,
+    // Version the installer just updated to, shown after it reopens the program
+    update_ready: Option<String>,
+    notifications_enabled: bool,
+    minimize_to_tray: bool,
+    tray: Option<Tray>,
+    // Tray icon couldn't be created, so closing quits as usual
+    tray_failed: bool,
+    hidden_to_tray: bool,
+    // Quit chosen from the tray, let the close through
+    quitting: bool,
+    auto_disconnect_on_sim_exit: bool,
+    auto_fallback_relay: bool,
+    auto_copy_code: bool,
+    // Text to put on the clipboard next frame
+    pending_copy: Option<String>,
+    record_session: bool,
+    // Name the local user joined the session with
+    self_name: String,
+    // Settings edited since the last save, saved once they stop changing
+    settings_dirty: bool,
+    last_settings_change: Instant,
+    bandwidth_unit: BandwidthUnit,
+    // Set by the app, flashes the window on the next frame
+    attention_requested: bool,
+    compact_mode: bool,
+    colorblind_mode: bool,
+    // Compact mode the window was last resized for
+    applied_compact_mode: bool,
+    overlay_mode: bool,
+    applied_overlay_mode: bool,
+    overlay_opacity: f32,
+    overlay_position: Option<[f32; 2]>,
+    // The overlay is being moved, its position is saved once let go
+    overlay_dragging: bool,
+    // Last config received from the app, used as the base when saving
+    config: Config,
+
+    // Advanced
+    custom_aircraft_path: String,
+    // File used by settings export/import
+    config_file_path: String,
 
     // Network stats
     download_bandwidth: f32,
     upload_bandwidth: f32,
     packet_loss: f32,
     ping: f32,
+    // Overall rating of the latest metrics
+    quality: Option<Quality>,
+    // Rolling average of upload plus download in KB/s
+    bandwidth_average: Option<f32>,
+    bandwidth_warn_kbps: Option<f32>,
+    bandwidth_warning: bool,
+    queue_outbound: usize,
+    queue_inbound: usize,
+    // Measured send rate in Hz
+    update_rate: Option<f32>,
+    // Most updates per second the user asked for
+    target_update_rate: u32,
+    // Interpolation smoothing in ms
+    smoothing_ms: u32,
+    full_metrics: Option<MetricsDetail>,
+    session_stats: SessionStats,
+    // Recap of the session that just ended, until dismissed
+    last_session: Option<SessionSummary>,
+    chat: VecDeque<(String, String)>,
+    // Variables reported out of sync, keyed by client name
+    desync_vars: HashMap<String, Vec<String>>,
+    chat_input: String,
+    // Chat messages sent by the QUICK_MESSAGE_KEYS
+    quick_messages: Vec<String>,
+    // We're observing, as last reported by the app
+    is_self_observing: bool,
+    // Shortcut text as typed in settings, parsed when used
+    observer_shortcut: String,
+}
+
+// Every metric reported for the connection, shown in the Advanced panel
+#[derive(Clone, Debug, Default)]
+struct MetricsDetail {
+    sent_packets: u64,
+    received_packets: u64,
+    acked_packets: u64,
+    sent_kbps: f32,
+    receive_kbps: f32,
+    packet_loss: f32,
+    rtt: f32,
+}
+
+// Running totals for the current session, turned into a SessionSummary on disconnect
+#[derive(Clone, Debug, Default)]
+struct SessionStats {
+    started: Option<Instant>,
+    peak_clients: usize,
+    sent_kb: f64,
+    received_kb: f64,
+    ping_total: f64,
+    ping_samples: u32,
+    last_sample: Option<Instant>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct SessionSummary {
+    duration: Duration,
+    peak_clients: usize,
+    sent_kb: f64,
+    received_kb: f64,
+    average_ping: Option<f32>,
+}
+
+// Text for whether a direct host's port looks open, and whether that's good, bad or unknown
+fn reachability_verdict(port: &str, reachable: Option<bool>) -> (String, Option<bool>) {
+    match reachable {
+        Some(true) => (format!("Port {} appears open", port), Some(true)),
+        Some(false) => (
+            "Port may be blocked — check forwarding".to_string(),
+            Some(false),
+        ),
+        None => (format!("Couldn't check if port {} is open", port), None),
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    }
+}
+
+fn format_session_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
 }
 
 #[derive(Clone, Debug)]
@@ -211,6 +1112,133 @@ struct ClientInfo {
     name: String,
     has_control: bool,
     is_observer: bool,
+    is_spectator: bool,
+    ready: bool,
+    aircraft: Option<String>,
+    version: Option<String>,
+    // Last reported (lat, lon, heading)
+    position: Option<(f64, f64, f32)>,
+    // Aircraft being switched to and since when
+    loading_aircraft: Option<(String, Instant)>,
+    // False while in control but no updates are arriving from them
+    input_active: bool,
+    // Multi-crew position they hold
+    seat: Option<String>,
+    // How far their clock is ahead of ours in ms
+    clock_offset: Option<f32>,
+}
+
+impl Default for ClientInfo {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            has_control: false,
+            is_observer: false,
+            is_spectator: false,
+            ready: false,
+            aircraft: None,
+            version: None,
+            position: None,
+            loading_aircraft: None,
+            input_active: true,
+            seat: None,
+            clock_offset: None,
+        }
+    }
+}
+
+impl ClientInfo {
+    // Hover text for the roster row, None if nothing is known
+    fn details(&self) -> Option<String> {
+        let mut lines = Vec::new();
+        if let Some(aircraft) = &self.aircraft {
+            lines.push(format!("Aircraft: {}", aircraft));
+        }
+        if let Some(version) = &self.version {
+            lines.push(format!("Version: {}", version));
+        }
+
+        if lines.is_empty() {
+            None
+        } else {
+            Some(lines.join("\n"))
+        }
+    }
+}
+
+const LOCAL_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const FULL_SIZE: [f32; 2] = [1000.0, 800.0];
+const FULL_MIN_SIZE: [f32; 2] = [800.0, 600.0];
+const COMPACT_SIZE: [f32; 2] = [320.0, 110.0];
+const OVERLAY_SIZE: [f32; 2] = [280.0, 80.0];
+const MIN_OVERLAY_OPACITY: f32 = 0.2;
+
+// Packet loss above this shows the connection as degraded
+const DEGRADED_PACKET_LOSS: f32 = 0.05;
+
+// Positions offered when giving control in a multi-crew aircraft
+const SEATS: [&str; 2] = ["Pilot", "Copilot"];
+
+// Keys that send the matching quick message slot
+const QUICK_MESSAGE_KEYS: [egui::Key; 4] =
+    [egui::Key::F1, egui::Key::F2, egui::Key::F3, egui::Key::F4];
+
+// Weight of each new sample in the rolling bandwidth average
+const BANDWIDTH_AVERAGE_WEIGHT: f32 = 0.2;
+// The cap warning clears once the average drops below this share of the cap
+const BANDWIDTH_WARN_RESET: f32 = 0.9;
+
+// Connection quality thresholds, ping in ms and loss as a fraction
+const GOOD_PING: f32 = 100.0;
+const FAIR_PING: f32 = 250.0;
+const GOOD_PACKET_LOSS: f32 = 0.01;
+// Traffic in KB/s above which the link is likely congested
+const HEAVY_BANDWIDTH: f32 = 500.0;
+
+// Clock offsets in ms above this throw off prediction and are shown in red
+const CLOCK_OFFSET_WARN_MS: f32 = 100.0;
+
+// Times a failed update can be retried before giving up
+const MAX_UPDATE_RETRIES: u32 = 3;
+
+// Queued messages above this are shown in red
+const QUEUE_DEPTH_WARN: usize = 100;
+
+// Longer status text is cut short in the status bar, the rest shown on hover
+const STATUS_MAX_WIDTH: f32 = 360.0;
+
+// Oldest chat messages are dropped past this
+const CHAT_HISTORY: usize = 50;
+
+const CONFIG_EXPORT_FILENAME: &str = "config_export.json";
+
+// Settings are saved once left unchanged for this long
+const AUTOSAVE_DELAY: Duration = Duration::from_secs(2);
+
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+
+// A client switching aircraft is assumed done after this without a roster update
+const AIRCRAFT_SWITCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+// How long the idle prompt waits for an answer before going observer
+const AFK_PROMPT_TIMEOUT: Duration = Duration::from_secs(30);
+
+const MINIMAP_SIZE: f32 = 160.0;
+// Keeps markers and names inside the minimap
+const MINIMAP_MARGIN: f32 = 20.0;
+
+#[derive(Clone, Copy)]
+enum PasswordField {
+    Host,
+    Join,
+}
+
+// Short lived notice shown over the bottom right corner
+struct Toast {
+    text: String,
+    is_error: bool,
+    shown_at: Instant,
 }
 
 impl YourControlsApp {
@@ -219,7 +1247,7 @@ impl YourControlsApp {
         action_tx.send(AppMessage::Startup).ok();
 
         Self {
-            action_tx,
+            action_tx: LimitedSender::new(action_tx),
             event_rx,
             event_queue: VecDeque::new(),
             username: String::new(),
@@ -227,403 +1255,4930 @@ impl YourControlsApp {
             port: "7777".to_string(),
             ip_input: String::new(),
             is_connected: false,
-            status_message: "Not connected".to_string(),
+            status_state: StatusState::Disconnected,
+            predicting: false,
+            is_host: false,
+            show_inactive_column: false,
+            in_control: false,
+            status_message: neutral_status(false).to_string(),
             server_connection_method: ConnectionMethod::CloudServer,
             client_connection_method: ConnectionMethod::CloudServer,
+            selected_profile: None,
+            profile_name_input: String::new(),
             is_ipv6: false,
+            join_as_spectator: false,
+            public_endpoint: None,
+            hosted_session_code: None,
+            upnp_result: None,
+            public_ip_check: None,
+            session_locked: false,
+            host_password: String::new(),
+            join_password: String::new(),
+            show_passwords: false,
+            connection_test: None,
+            server_capabilities: None,
+            sync_paused: false,
+            syncing: false,
+            sim_connected: false,
+            detected_aircraft: None,
+            loaded_aircraft: None,
+            required_aircraft: None,
+            unsupported_aircraft: None,
+            capacity: None,
+            max_clients_input: String::new(),
+            quality_reduced: None,
+            nat_result: None,
+            relay_region: None,
             clients: Vec::new(),
+            selected_client: None,
+            confirm_transfer: None,
+            self_ready: false,
+            pending_transfer: None,
+            toast: None,
+            control_countdown: None,
+            confirm_disconnect: false,
+            pending_aircraft: None,
+            last_connect: None,
+            connection_failed: false,
+            confirm_sim_lost: false,
+            afk_observer_mins: None,
+            control_rotation_mins: None,
+            last_activity: Instant::now(),
+            afk_prompt_since: None,
             selected_aircraft: 0,
             aircraft_list: vec!["Select an aircraft...".to_string()],
-            connection_timeout: "30".to_string(),
+            no_aircraft_path: None,
+            scanning_aircraft: false,
+            sync_caveats: Vec::new(),
+            connection_timeout: 30,
             instructor_mode: false,
             streamer_mode: false,
             sound_muted: false,
+            sound_events: HashSet::new(),
             dark_theme: false,
+            current_tab: Tab::Connect,
+            update_available: None,
+            update_confirm: None,
+            update_error: None,
+            update_retries: 0,
+            update_ready: None,
+            // Waits for the saved config so it doesn't flash for returning users
+            first_run: false,
+            sync_values: Vec::new(),
+            sync_values_filter: String::new(),
+            sync_inspector_open: false,
+            sync_values_watched: false,
+            notifications_enabled: true,
+            minimize_to_tray: false,
+            tray: None,
+            tray_failed: false,
+            hidden_to_tray: false,
+            quitting: false,
+            auto_disconnect_on_sim_exit: true,
+            auto_fallback_relay: true,
+            auto_copy_code: false,
+            pending_copy: None,
+            record_session: false,
+            self_name: String::new(),
+            settings_dirty: false,
+            last_settings_change: Instant::now(),
+            bandwidth_unit: BandwidthUnit::KilobytesPerSec,
+            attention_requested: false,
+            compact_mode: false,
+            colorblind_mode: false,
+            applied_compact_mode: false,
+            overlay_mode: false,
+            applied_overlay_mode: false,
+            overlay_opacity: 0.8,
+            overlay_position: None,
+            overlay_dragging: false,
+            config: Config::default(),
+            custom_aircraft_path: String::new(),
+            config_file_path: CONFIG_EXPORT_FILENAME.to_string(),
             download_bandwidth: 0.0,
             upload_bandwidth: 0.0,
             packet_loss: 0.0,
             ping: 0.0,
+            quality: None,
+            bandwidth_average: None,
+            bandwidth_warn_kbps: None,
+            bandwidth_warning: false,
+            queue_outbound: 0,
+            queue_inbound: 0,
+            update_rate: None,
+            target_update_rate: MAX_UPDATE_RATE,
+            smoothing_ms: 0,
+            full_metrics: None,
+            session_stats: SessionStats::default(),
+            last_session: None,
+            chat: VecDeque::new(),
+            desync_vars: HashMap::new(),
+            chat_input: String::new(),
+            quick_messages: Vec::new(),
+            is_self_observing: false,
+            observer_shortcut: String::new(),
         }
     }
 
-    fn process_events(&mut self) {
-        // Process all pending events
-        while let Ok(event) = self.event_rx.try_recv() {
-            self.event_queue.push_back(event);
-        }
+    // Builds a config from the current settings on top of the last one received
+    fn build_config(&self) -> Config {
+        let mut config = self.config.clone();
+        config.name.clone_from(&self.username);
+        config.port = self.port.parse().unwrap_or(config.port);
+        config.conn_timeout =
+            validate_conn_timeout(self.connection_timeout).unwrap_or(config.conn_timeout);
+        config.ui_dark_theme = self.dark_theme;
+        config.streamer_mode = self.streamer_mode;
+        config.instructor_mode = self.instructor_mode;
+        config.sound_muted = self.sound_muted;
+        config.sound_events.clone_from(&self.sound_events);
+        config.ui_compact_mode = self.compact_mode;
+        config.ui_overlay_mode = self.overlay_mode;
+        config.overlay_opacity = self.overlay_opacity;
+        config.overlay_position = self.overlay_position;
+        config.ui_colorblind_mode = self.colorblind_mode;
+        config.notifications_enabled = self.notifications_enabled;
+        config.minimize_to_tray = self.minimize_to_tray;
+        config.auto_disconnect_on_sim_exit = self.auto_disconnect_on_sim_exit;
+        config.auto_fallback_relay = self.auto_fallback_relay;
+        config.auto_copy_code = self.auto_copy_code;
+        config.afk_observer_mins = self.afk_observer_mins;
+        config.control_rotation_mins = self.control_rotation_mins;
+        config.update_rate_hz = self.target_update_rate;
+        config.smoothing_ms = self.smoothing_ms;
+        config.first_run = self.first_run;
+        config.record_session = self.record_session;
+        config.bandwidth_unit = self.bandwidth_unit;
+        config.bandwidth_warn_kbps = self.bandwidth_warn_kbps;
+        config.quick_messages.clone_from(&self.quick_messages);
+        config.observer_shortcut.clone_from(&self.observer_shortcut);
+        config
+    }
 
-        // Handle events
-        while let Some(event) = self.event_queue.pop_front() {
-            self.handle_event(event);
-        }
+    fn select_aircraft(&mut self, index: usize) {
+        let config_file_name = self.aircraft_list[index].clone();
+        self.selected_aircraft = index;
+        self.loaded_aircraft = Some(config_file_name.clone());
+        self.unsupported_aircraft = None;
+        // The app sends the new aircraft's caveats once it reads them
+        self.sync_caveats.clear();
+        // Mirror the app side so the recent group updates without a config round trip
+        self.config.push_recent_aircraft(&config_file_name);
+        self.action_tx
+            .send(AppMessage::LoadAircraft { config_file_name })
+            .ok();
     }
 
-    fn handle_event(&mut self, event: UiEvent) {
-        match event {
-            UiEvent::Error(msg) => {
-                self.status_message = format!("Error: {}", msg);
-                self.is_connected = false;
+    fn load_config(&mut self, config: Config) {
+        self.username.clone_from(&config.name);
+        self.port = config.port.to_string();
+        self.connection_timeout = config.conn_timeout;
+        self.dark_theme = config.ui_dark_theme;
+        self.streamer_mode = config.streamer_mode;
+        self.instructor_mode = config.instructor_mode;
+        self.sound_muted = config.sound_muted;
+        self.sound_events.clone_from(&config.sound_events);
+        self.compact_mode = config.ui_compact_mode;
+        self.overlay_mode = config.ui_overlay_mode;
+        self.overlay_opacity = config.overlay_opacity.clamp(MIN_OVERLAY_OPACITY, 1.0);
+        self.overlay_position = config.overlay_position;
+        self.colorblind_mode = config.ui_colorblind_mode;
+        self.notifications_enabled = config.notifications_enabled;
+        self.minimize_to_tray = config.minimize_to_tray;
+        self.auto_disconnect_on_sim_exit = config.auto_disconnect_on_sim_exit;
+        self.auto_fallback_relay = config.auto_fallback_relay;
+        self.auto_copy_code = config.auto_copy_code;
+        self.afk_observer_mins = config.afk_observer_mins;
+        self.control_rotation_mins = config.control_rotation_mins;
+        self.target_update_rate = config.update_rate();
+        self.smoothing_ms = config.smoothing();
+        self.first_run = config.first_run;
+        self.record_session = config.record_session;
+        self.bandwidth_unit = config.bandwidth_unit;
+        self.bandwidth_warn_kbps = config.bandwidth_warn_kbps;
+        self.quick_messages.clone_from(&config.quick_messages);
+        // One slot per key, however many the file had
+        self.quick_messages
+            .resize(QUICK_MESSAGE_KEYS.len(), String::new());
+        self.observer_shortcut.clone_from(&config.observer_shortcut);
+        self.config = config;
+    }
+
+    fn export_config(&mut self) {
+        let path = self.config_file_path.trim().to_string();
+        match self.build_config().write_to_file(&path) {
+            Ok(_) => {
+                let full_path = std::fs::canonicalize(&path)
+                    .map(|p| p.to_string_lossy().to_string())
+                    .unwrap_or(path);
+                self.show_toast(format!("Settings exported to {}", full_path), false);
             }
-            UiEvent::Attempt => {
-                self.status_message = "Attempting connection...".to_string();
+            Err(e) => self.show_toast(format!("Could not export settings: {}", e), true),
+        }
+    }
+
+    fn import_config(&mut self) {
+        match Config::read_from_file(self.config_file_path.trim()) {
+            Ok(config) => {
+                self.load_config(config);
+                self.save_settings();
+                self.show_toast("Settings imported".to_string(), false);
             }
-            UiEvent::Connected => {
-                self.status_message = "Connected to server".to_string();
-                self.is_connected = true;
+            Err(e) => self.show_toast(format!("Could not import settings: {}", e), true),
+        }
+    }
+
+    // Fills in the join fields from a saved profile
+    fn apply_profile(&mut self, index: usize) {
+        if let Some(profile) = self.config.profiles.get(index) {
+            self.client_connection_method = profile.method;
+            self.ip_input.clone_from(&profile.ip);
+            self.port = profile.port.to_string();
+            self.session_code.clone_from(&profile.session_code);
+            self.is_ipv6 = profile.isipv6;
+            self.profile_name_input.clone_from(&profile.name);
+            self.selected_profile = Some(index);
+        }
+    }
+
+    fn save_current_profile(&mut self) {
+        let name = self.profile_name_input.trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+
+        self.config.save_profile(ServerProfile {
+            name: name.clone(),
+            method: self.client_connection_method,
+            ip: self.ip_input.trim().to_string(),
+            port: self.port.parse().unwrap_or(self.config.port),
+            session_code: self.session_code.trim().to_string(),
+            isipv6: self.is_ipv6,
+        });
+        self.selected_profile = self.config.profiles.iter().position(|p| p.name == name);
+        self.save_settings();
+    }
+
+    fn delete_selected_profile(&mut self) {
+        if let Some(profile) = self
+            .selected_profile
+            .and_then(|i| self.config.profiles.get(i))
+        {
+            let name = profile.name.clone();
+            self.config.remove_profile(&name);
+            self.selected_profile = None;
+            self.save_settings();
+        }
+    }
+
+    fn mark_settings_dirty(&mut self) {
+        self.settings_dirty = true;
+        self.last_settings_change = Instant::now();
+    }
+
+    fn save_settings(&mut self) {
+        self.settings_dirty = false;
+        self.config = self.build_config();
+        self.action_tx
+            .send(AppMessage::UpdateConfig {
+                new_config: self.config.clone(),
+            })
+            .ok();
+    }
+
+    fn dismiss_onboarding(&mut self) {
+        self.first_run = false;
+        self.save_settings();
+    }
+
+    fn set_compact_mode(&mut self, compact: bool) {
+        self.compact_mode = compact;
+        self.save_settings();
+    }
+
+    fn set_overlay_mode(&mut self, overlay: bool) {
+        self.overlay_mode = overlay;
+        self.save_settings();
+    }
+
+    // Switches the window between its normal frame and the borderless overlay
+    fn apply_overlay_mode(&mut self, ctx: &egui::Context) {
+        let overlay = self.overlay_mode;
+        ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(!overlay));
+        ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(if overlay {
+            egui::WindowLevel::AlwaysOnTop
+        } else {
+            egui::WindowLevel::Normal
+        }));
+
+        if overlay {
+            ctx.send_viewport_cmd(egui::ViewportCommand::MinInnerSize(OVERLAY_SIZE.into()));
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(OVERLAY_SIZE.into()));
+            if let Some(position) = self.overlay_position {
+                ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(position.into()));
             }
-            UiEvent::ServerFail(reason) => {
-                self.status_message = format!("Server failed: {}", reason);
-                self.is_connected = false;
+        } else {
+            // Restore whichever size compact mode wants
+            self.applied_compact_mode = !self.compact_mode;
+        }
+        self.applied_overlay_mode = overlay;
+    }
+
+    fn overlay_panel(&mut self, ui: &mut egui::Ui) {
+        // There's no title bar, so dragging the background moves the window
+        let background = ui.interact(
+            ui.max_rect(),
+            ui.id().with("overlay_drag"),
+            egui::Sense::drag(),
+        );
+        if background.drag_started() {
+            ui.ctx().send_viewport_cmd(egui::ViewportCommand::StartDrag);
+            self.overlay_dragging = true;
+        }
+
+        let (status, _) = status_summary(&self.status_message);
+        ui.label(status);
+        ui.horizontal(|ui| {
+            ui.label(format!(
+                "In control: {}",
+                self.controller_name().unwrap_or("Nobody")
+            ));
+            if self.is_connected && !self.in_control && ui.small_button("Take Control").clicked() {
+                self.action_tx.send(AppMessage::ForceTakeControl).ok();
             }
-            UiEvent::ClientFail(reason) => {
-                self.status_message = format!("Client failed: {}", reason);
-                self.is_connected = false;
-                self.clients.clear();
+            if ui
+                .small_button("✖")
+                .on_hover_text("Leave overlay mode")
+                .clicked()
+            {
+                self.set_overlay_mode(false);
             }
-            UiEvent::GainControl => {
-                self.status_message = "You have control".to_string();
+        });
+    }
+
+    // Saves where the overlay ended up once a drag is let go
+    fn track_overlay_position(&mut self, ctx: &egui::Context) {
+        if !self.overlay_dragging || ctx.input(|i| i.pointer.any_down()) {
+            return;
+        }
+        self.overlay_dragging = false;
+
+        if let Some(rect) = ctx.input(|i| i.viewport().outer_rect) {
+            self.overlay_position = Some([rect.min.x, rect.min.y]);
+            self.mark_settings_dirty();
+        }
+    }
+
+    fn controller_name(&self) -> Option<&str> {
+        if self.in_control {
+            return Some("You");
+        }
+        self.clients
+            .iter()
+            .find(|c| c.has_control)
+            .map(|c| c.name.as_str())
+    }
+
+    fn nat_text(&self) -> Option<&'static str> {
+        Some(match self.nat_result? {
+            (true, _) => "Direct P2P established",
+            (false, true) => "Using relay (NAT blocked)",
+            (false, false) => "NAT traversal failed",
+        })
+    }
+
+    fn relay_text(&self) -> Option<String> {
+        let (region, ping) = self.relay_region.as_ref()?;
+        Some(match ping {
+            Some(ping) => format!("Relay: {} ({:.0}ms)", region, ping),
+            None => format!("Relay: {}", region),
+        })
+    }
+
+    // Plain text summary users can paste into a bug report
+    fn diagnostics_text(&self) -> String {
+        let mut lines = vec![
+            format!("Status: {}", self.status_message),
+            format!("Connected: {}, host: {}", self.is_connected, self.is_host),
+            format!("Clients: {}", self.clients.len()),
+            format!(
+                "Ping: {:.0}ms, loss: {:.1}%",
+                self.ping,
+                self.packet_loss * 100.0
+            ),
+            format!(
+                "Bandwidth: down {:.2} KB/s, up {:.2} KB/s",
+                self.download_bandwidth, self.upload_bandwidth
+            ),
+            format!(
+                "Queued updates: out {}, in {}",
+                self.queue_outbound, self.queue_inbound
+            ),
+        ];
+        if let Some(nat) = self.nat_text() {
+            lines.push(format!("NAT: {}", nat));
+        }
+        if let Some(relay) = self.relay_text() {
+            lines.push(relay);
+        }
+        lines.join("\n")
+    }
+
+    fn status_bar(&self, ui: &mut egui::Ui) {
+        let (good, bad) = status_colors(self.colorblind_mode);
+        ui.horizontal(|ui| {
+            let (color, icon) = match self.status_state {
+                StatusState::Disconnected => (egui::Color32::GRAY, "○"),
+                StatusState::Connecting => (egui::Color32::YELLOW, "◌"),
+                StatusState::Connected => (good, "●"),
+                StatusState::Degraded => (egui::Color32::ORANGE, "◐"),
+                StatusState::Error => (bad, "✖"),
+            };
+            ui.colored_label(color, icon);
+            let (summary, cut) = status_summary(&self.status_message);
+            let label = ui
+                .allocate_ui(
+                    egui::vec2(STATUS_MAX_WIDTH, ui.spacing().interact_size.y),
+                    |ui| ui.add(egui::Label::new(summary).truncate()),
+                )
+                .inner;
+            if cut || label.rect.width() >= STATUS_MAX_WIDTH {
+                label.on_hover_text(&self.status_message);
             }
-            UiEvent::LoseControl => {
-                self.status_message = "You lost control".to_string();
+            if self.predicting {
+                ui.weak("~")
+                    .on_hover_text("Showing predicted positions, brief jitter is expected");
             }
-            UiEvent::ServerStarted => {
-                self.status_message = "Server started".to_string();
-                self.is_connected = true;
+
+            ui.separator();
+            if self.sim_connected {
+                ui.colored_label(good, "✈ Sim connected");
+            } else {
+                ui.colored_label(egui::Color32::GRAY, "○ Waiting for simulator");
             }
-            UiEvent::SessionCode(code) => {
-                self.status_message = format!("Session Code: {}", code);
+
+            if let Some(title) = &self.detected_aircraft {
+                ui.separator();
+                ui.label(format!("Flying: {}", title));
+
+                let mismatched = self
+                    .loaded_aircraft
+                    .as_ref()
+                    .filter(|config| !aircraft_matches_config(title, config));
+                if let Some(config) = mismatched {
+                    ui.colored_label(egui::Color32::YELLOW, "⚠ Config mismatch")
+                        .on_hover_text(format!(
+                            "The loaded config is {}, which may not be for this aircraft",
+                            config
+                        ));
+                }
             }
-            UiEvent::SetHost => {
-                self.status_message = "You are now hosting".to_string();
+        });
+    }
+
+    fn compact_panel(&mut self, ui: &mut egui::Ui) {
+        self.status_bar(ui);
+
+        ui.horizontal(|ui| {
+            ui.label(format!(
+                "In control: {}",
+                self.controller_name().unwrap_or("Nobody")
+            ));
+        });
+
+        ui.horizontal(|ui| {
+            if self.is_connected && !self.in_control && ui.button("Take Control").clicked() {
+                self.action_tx.send(AppMessage::ForceTakeControl).ok();
             }
-            UiEvent::NewConnection(name) => {
-                self.clients.push(ClientInfo {
-                    name,
-                    has_control: false,
-                    is_observer: false,
-                });
+            if ui.button("⬜ Expand").clicked() {
+                self.set_compact_mode(false);
             }
-            UiEvent::LostConnection(name) => {
-                self.clients.retain(|c| c.name != name);
+        });
+    }
+
+    // Newest version a connected client runs, if it's newer than ours
+    fn newer_peer_version(&self) -> Option<&str> {
+        self.clients
+            .iter()
+            .filter_map(|client| client.version.as_deref())
+            .filter(|version| is_newer(version, LOCAL_VERSION))
+            .fold(None, |newest, version| match newest {
+                Some(newest) if !is_newer(version, newest) => Some(newest),
+                _ => Some(version),
+            })
+    }
+
+    fn can_retry_update(&self) -> bool {
+        matches!(self.update_error, Some((_, true))) && self.update_retries < MAX_UPDATE_RETRIES
+    }
+
+    fn retry_update(&mut self) {
+        if !self.can_retry_update() {
+            return;
+        }
+
+        self.update_retries += 1;
+        self.update_error = None;
+        self.status_message = "Retrying update...".to_string();
+        self.action_tx.send(AppMessage::RunUpdater).ok();
+    }
+
+    // Tells the app to start or stop sending sync values when the inspector opens or closes
+    fn watch_sync_values(&mut self, watching: bool) {
+        if watching == self.sync_values_watched {
+            return;
+        }
+
+        self.sync_values_watched = watching;
+        self.action_tx
+            .send(AppMessage::WatchSyncValues { watching })
+            .ok();
+        if !watching {
+            self.sync_values.clear();
+        }
+    }
+
+    fn confirm_update(&mut self, accepted: bool) {
+        if self.update_confirm.take().is_none() {
+            return;
+        }
+
+        self.action_tx
+            .send(AppMessage::ConfirmUpdate { accepted })
+            .ok();
+        if accepted {
+            self.status_message = "Installing update...".to_string();
+            self.update_available = None;
+        }
+    }
+
+    // Sends the quick message in `slot`, false if there's nothing to send
+    fn send_quick_message(&mut self, slot: usize) -> bool {
+        let text = match self.quick_messages.get(slot).map(|text| text.trim()) {
+            Some(text) if !text.is_empty() => text.to_string(),
+            _ => return false,
+        };
+
+        if !self.is_connected || !self.server_supports(CAPABILITY_CHAT) {
+            return false;
+        }
+
+        self.action_tx
+            .send(AppMessage::BroadcastMessage { text })
+            .ok();
+        true
+    }
+
+    // Servers that never advertised anything are assumed to support everything
+    fn server_supports(&self, capability: &str) -> bool {
+        self.server_capabilities
+            .as_ref()
+            .map_or(true, |caps| caps.contains(capability))
+    }
+
+    // Masked password entry, with a reveal toggle that's hidden in streamer mode
+    fn password_field(&mut self, ui: &mut egui::Ui, field: PasswordField) {
+        let reveal = self.show_passwords && !self.streamer_mode;
+        let password = match field {
+            PasswordField::Host => &mut self.host_password,
+            PasswordField::Join => &mut self.join_password,
+        };
+        ui.add(
+            egui::TextEdit::singleline(password)
+                .password(!reveal)
+                .hint_text("None")
+                .desired_width(120.0),
+        );
+        if !self.streamer_mode {
+            ui.toggle_value(&mut self.show_passwords, "👁")
+                .on_hover_text("Show passwords");
+        }
+    }
+
+    // Session code, IP and port from the join fields for the selected method
+    fn connect_fields(&self) -> (Option<String>, Option<IpAddr>, Option<u16>) {
+        if self.client_connection_method == ConnectionMethod::Direct {
+            // Direct: use IP and port
+            (None, self.ip_input.parse().ok(), self.port.parse().ok())
+        } else {
+            // Cloud: use session code
+            (Some(self.session_code.clone()), None, None)
+        }
+    }
+
+    fn request_connection_test(&mut self) {
+        let (session_id, ip, port) = self.connect_fields();
+        self.connection_test = Some(None);
+        self.action_tx
+            .send(AppMessage::TestConnection {
+                session_id,
+                isipv6: self.is_ipv6,
+                ip,
+                hostname: None,
+                port,
+                method: self.client_connection_method,
+            })
+            .ok();
+    }
+
+    // Why Start/Connect can't be used right now
+    fn start_blocked_reason(&self) -> Option<String> {
+        if self.is_connected {
+            None
+        } else if !self.sim_connected {
+            Some("Waiting for simulator...".to_string())
+        } else if let Err(e) = validate_conn_timeout(self.connection_timeout) {
+            Some(e)
+        } else {
+            self.unsupported_aircraft.as_ref().map(|title| {
+                format!(
+                    "No definition for {}, load a definition for this aircraft first",
+                    title
+                )
+            })
+        }
+    }
+
+    // How our aircraft differs from the one the session requires, None if it matches or isn't known
+    fn required_aircraft_mismatch(&self) -> Option<String> {
+        let required = self.required_aircraft.as_deref()?;
+        if let Some(loaded) = self.loaded_aircraft.as_deref() {
+            if !aircraft_compatible(loaded, required, &self.config.aircraft_families) {
+                return Some(format!(
+                    "You have {} loaded",
+                    loaded.trim_end_matches(".yaml")
+                ));
             }
-            UiEvent::Observing(_observing) => {
-                // Update own observer state if needed
+        }
+
+        let title = self.detected_aircraft.as_deref()?;
+        if aircraft_matches_config(title, required) {
+            None
+        } else {
+            Some(format!("You are flying {}", title))
+        }
+    }
+
+    // The client's aircraft if it isn't one we can hand control to, None while either side is unknown
+    fn different_aircraft<'a>(&self, client: &'a ClientInfo) -> Option<&'a str> {
+        let ours = self.loaded_aircraft.as_deref()?;
+        let theirs = client.aircraft.as_deref()?;
+        if aircraft_compatible(ours, theirs, &self.config.aircraft_families) {
+            None
+        } else {
+            Some(theirs)
+        }
+    }
+
+    // Clients that never confirmed their new aircraft are assumed to have loaded it
+    fn finish_aircraft_switches(&mut self, now: Instant) {
+        for client in &mut self.clients {
+            let timed_out = client
+                .loading_aircraft
+                .as_ref()
+                .is_some_and(|(_, since)| now.duration_since(*since) >= AIRCRAFT_SWITCH_TIMEOUT);
+            if timed_out {
+                client.aircraft = client.loading_aircraft.take().map(|(to, _)| to);
             }
-            UiEvent::SetObserving { name, observing } => {
-                if let Some(client) = self.clients.iter_mut().find(|c| c.name == name) {
-                    client.is_observer = observing;
-                }
+        }
+    }
+
+    // Offers to go observer once idle in control, going anyway if the offer is ignored
+    fn check_idle(&mut self, now: Instant) {
+        let idle_limit = match self.afk_observer_mins {
+            Some(mins) if self.in_control => Duration::from_secs(u64::from(mins) * 60),
+            _ => {
+                self.afk_prompt_since = None;
+                return;
             }
-            UiEvent::SetInControl(name) => {
-                // Clear all control flags
-                for client in &mut self.clients {
-                    client.has_control = false;
-                }
-                // Set the new controller
-                if let Some(client) = self.clients.iter_mut().find(|c| c.name == name) {
-                    client.has_control = true;
-                }
+        };
+
+        match self.afk_prompt_since {
+            Some(since) if now.saturating_duration_since(since) >= AFK_PROMPT_TIMEOUT => {
+                self.go_observer();
             }
-            UiEvent::AddAircraft(name) => {
-                if self.aircraft_list.len() == 1 && self.aircraft_list[0] == "Select an aircraft..."
-                {
-                    self.aircraft_list.clear();
-                }
-                self.aircraft_list.push(name);
+            Some(_) => {}
+            None if now.saturating_duration_since(self.last_activity) >= idle_limit => {
+                self.afk_prompt_since = Some(now);
             }
-            UiEvent::Version(version) => {
-                self.status_message = format!("Update available: {}", version);
+            None => {}
+        }
+    }
+
+    fn go_observer(&mut self) {
+        self.afk_prompt_since = None;
+        // Don't ask again while control is being handed over
+        self.last_activity = Instant::now();
+        self.action_tx.send(AppMessage::GoObserver).ok();
+    }
+
+    fn toggle_self_observer(&mut self) {
+        if !self.is_connected {
+            return;
+        }
+
+        if self.is_self_observing {
+            self.action_tx.send(AppMessage::LeaveObserver).ok();
+        } else {
+            self.go_observer();
+        }
+    }
+
+    // Clears connection stats so the next connection starts blank
+    fn reset_metrics(&mut self) {
+        self.download_bandwidth = 0.0;
+        self.upload_bandwidth = 0.0;
+        self.packet_loss = 0.0;
+        self.ping = 0.0;
+        self.quality = None;
+        self.bandwidth_average = None;
+        self.bandwidth_warning = false;
+        self.queue_outbound = 0;
+        self.queue_inbound = 0;
+        self.update_rate = None;
+        self.full_metrics = None;
+        self.sync_values.clear();
+    }
+
+    // Session code a direct join can fall back to the cloud server with
+    fn relay_fallback_session_id(&self) -> Option<String> {
+        let code = self.session_code.trim();
+        if self.client_connection_method != ConnectionMethod::Direct
+            || !self.auto_fallback_relay
+            || code.is_empty()
+        {
+            return None;
+        }
+        Some(code.to_string())
+    }
+
+    // Ready to paste message telling others how to join the session we're hosting
+    fn invite_text(&self) -> Option<String> {
+        if !self.is_host {
+            return None;
+        }
+
+        let (kind, label, code) = match self.server_connection_method {
+            ConnectionMethod::Direct => (
+                "Address",
+                "Direct",
+                self.public_endpoint.as_deref().filter(|e| !e.is_empty())?,
+            ),
+            ConnectionMethod::CloudServer | ConnectionMethod::Relay => {
+                ("Code", "Cloud", self.hosted_session_code.as_deref()?)
             }
-            UiEvent::UpdateFailed => {
-                self.status_message = "Update download failed".to_string();
+        };
+
+        if self.streamer_mode {
+            return Some(format!(
+                "Join my YourControls session! DM me for the {} ({})",
+                kind.to_lowercase(),
+                label
+            ));
+        }
+        Some(format!(
+            "Join my YourControls session! {}: {} ({})",
+            kind, code, label
+        ))
+    }
+
+    fn set_target_update_rate(&mut self, hz: u32) {
+        let hz = hz.clamp(MIN_UPDATE_RATE, MAX_UPDATE_RATE);
+        if hz == self.target_update_rate {
+            return;
+        }
+        self.target_update_rate = hz;
+        self.action_tx.send(AppMessage::SetUpdateRate { hz }).ok();
+    }
+
+    fn set_smoothing(&mut self, ms: u32) {
+        let ms = ms.min(MAX_SMOOTHING_MS);
+        if ms == self.smoothing_ms {
+            return;
+        }
+        self.smoothing_ms = ms;
+        self.action_tx.send(AppMessage::SetSmoothing { ms }).ok();
+    }
+
+    // The Connect tab column that doesn't apply while hosting or joined
+    fn inactive_column(&self) -> Option<ConnectColumn> {
+        match (self.is_connected, self.is_host) {
+            (false, _) => None,
+            (true, true) => Some(ConnectColumn::Join),
+            (true, false) => Some(ConnectColumn::Host),
+        }
+    }
+
+    // Shows a collapsed placeholder for a column that doesn't apply, returns true if collapsed
+    fn collapse_inactive_column(&mut self, ui: &mut egui::Ui, column: ConnectColumn) -> bool {
+        if self.inactive_column() != Some(column) {
+            return false;
+        }
+
+        if self.show_inactive_column {
+            if ui.small_button("⏶ Collapse").clicked() {
+                self.show_inactive_column = false;
             }
-            UiEvent::SendConfig(config_json) => {
-                // Parse and load config
-                if let Ok(config) = serde_json::from_str::<serde_json::Value>(&config_json) {
-                    if let Some(name) = config["name"].as_str() {
-                        self.username = name.to_string();
-                    }
-                    if let Some(port) = config["port"].as_u64() {
-                        self.port = port.to_string();
-                    }
-                    if let Some(timeout) = config["conn_timeout"].as_u64() {
-                        self.connection_timeout = timeout.to_string();
-                    }
-                    if let Some(dark) = config["ui_dark_theme"].as_bool() {
-                        self.dark_theme = dark;
-                    }
+            return false;
+        }
+
+        ui.weak(match column {
+            ConnectColumn::Host => "Connected as a client",
+            ConnectColumn::Join => "You are hosting",
+        });
+        if ui.small_button("⏷ Expand").clicked() {
+            self.show_inactive_column = true;
+        }
+        true
+    }
+
+    fn start_session(&mut self) {
+        self.show_inactive_column = false;
+        self.session_stats = SessionStats {
+            started: Some(Instant::now()),
+            ..Default::default()
+        };
+        self.last_session = None;
+    }
+
+    fn session_summary(&self) -> SessionSummary {
+        let stats = &self.session_stats;
+        SessionSummary {
+            duration: stats.started.map(|s| s.elapsed()).unwrap_or_default(),
+            peak_clients: stats.peak_clients,
+            sent_kb: stats.sent_kb,
+            received_kb: stats.received_kb,
+            average_ping: if stats.ping_samples > 0 {
+                Some((stats.ping_total / f64::from(stats.ping_samples)) as f32)
+            } else {
+                None
+            },
+        }
+    }
+
+    // Keeps the recap if a session was actually running
+    fn end_session(&mut self) {
+        if self.session_stats.started.is_some() {
+            self.last_session = Some(self.session_summary());
+        }
+        self.session_stats = SessionStats::default();
+    }
+
+    fn show_toast(&mut self, text: String, is_error: bool) {
+        self.toast = Some(Toast {
+            text,
+            is_error,
+            shown_at: Instant::now(),
+        });
+    }
+
+    // Swapping planes mid-session disrupts everyone, so ask first
+    fn request_select_aircraft(&mut self, index: usize) {
+        if self.is_connected && index != self.selected_aircraft {
+            self.pending_aircraft = Some(self.aircraft_list[index].clone());
+        } else {
+            self.select_aircraft(index);
+        }
+    }
+
+    fn confirm_pending_aircraft(&mut self) {
+        let pending = self.pending_aircraft.take();
+        // The list may have been rescanned while the prompt was open
+        if let Some(index) =
+            pending.and_then(|name| self.aircraft_list.iter().position(|a| *a == name))
+        {
+            self.select_aircraft(index);
+        }
+    }
+
+    // Disconnecting while in control leaves the others without a pilot, so ask first
+    fn request_disconnect(&mut self) {
+        if self.in_control && !self.clients.is_empty() {
+            self.confirm_disconnect = true;
+        } else {
+            self.disconnect();
+        }
+    }
+
+    // Whether closing the window should hide it in the tray rather than quit
+    fn should_hide_on_close(&self) -> bool {
+        self.minimize_to_tray && !self.quitting
+    }
+
+    fn handle_tray_action(&mut self, action: TrayAction) -> Vec<egui::ViewportCommand> {
+        match action {
+            TrayAction::Show => {
+                self.hidden_to_tray = false;
+                vec![
+                    egui::ViewportCommand::Visible(true),
+                    egui::ViewportCommand::Focus,
+                ]
+            }
+            TrayAction::Disconnect => {
+                self.disconnect();
+                Vec::new()
+            }
+            TrayAction::Quit => {
+                self.quitting = true;
+                vec![egui::ViewportCommand::Close]
+            }
+        }
+    }
+
+    // Creates or removes the tray icon to match the setting, and hides the window into it on close
+    fn update_tray(&mut self, ctx: &egui::Context) {
+        if self.minimize_to_tray && self.tray.is_none() && !self.tray_failed {
+            let repaint_ctx = ctx.clone();
+            match Tray::new("YourControls", move || repaint_ctx.request_repaint()) {
+                Ok(tray) => self.tray = Some(tray),
+                Err(e) => {
+                    warn!("[UI] Could not create tray icon: {}", e);
+                    self.tray_failed = true;
+                    self.minimize_to_tray = false;
                 }
             }
-            UiEvent::SendMetrics {
-                sent_packets: _,
-                received_packets: _,
-                sent_kbps,
-                receive_kbps,
-                packet_loss,
-                ping,
-            } => {
-                self.download_bandwidth = receive_kbps;
-                self.upload_bandwidth = sent_kbps;
-                self.packet_loss = packet_loss;
-                self.ping = ping;
+        } else if !self.minimize_to_tray && !self.hidden_to_tray {
+            self.tray = None;
+        }
+
+        while let Some(action) = self.tray.as_ref().and_then(Tray::try_action) {
+            for command in self.handle_tray_action(action) {
+                ctx.send_viewport_cmd(command);
+            }
+        }
+
+        // The UI thread keeps running while hidden, so exited() stays false until Quit
+        if ctx.input(|i| i.viewport().close_requested())
+            && self.tray.is_some()
+            && self.should_hide_on_close()
+        {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
+            self.hidden_to_tray = true;
+        }
+    }
+
+    // Resyncing only makes sense when receiving state and not already syncing
+    fn can_request_resync(&self) -> bool {
+        self.is_connected && !self.in_control && !self.syncing
+    }
+
+    fn request_resync(&mut self) {
+        self.action_tx.send(AppMessage::RequestResync).ok();
+    }
+
+    fn disconnect(&mut self) {
+        // Leaving on purpose, nothing to reconnect to
+        self.last_connect = None;
+        self.action_tx.send(AppMessage::Disconnect).ok();
+    }
+
+    // Sends a Connect/StartServer and remembers it for reconnecting
+    fn send_connect(&mut self, message: AppMessage) {
+        self.last_connect = Some(message.clone());
+        self.action_tx.send(message).ok();
+    }
+
+    fn reconnect(&mut self) {
+        if let Some(message) = self.last_connect.clone() {
+            self.connection_failed = false;
+            self.action_tx.send(message).ok();
+        }
+    }
+
+    fn transfer_control(&mut self, target: &str) {
+        self.pending_transfer = Some(target.to_string());
+        self.action_tx
+            .send(AppMessage::TransferControl {
+                target: target.to_string(),
+            })
+            .ok();
+    }
+
+    // Top down view of where everyone is relative to each other, not to scale with a map
+    fn minimap(&self, ui: &mut egui::Ui) {
+        let placed: Vec<(&ClientInfo, (f64, f64, f32))> = self
+            .clients
+            .iter()
+            .filter_map(|c| c.position.map(|position| (c, position)))
+            .collect();
+        let positions: Vec<(f64, f64)> = placed
+            .iter()
+            .map(|(_, (lat, lon, _))| (*lat, *lon))
+            .collect();
+
+        let (response, painter) =
+            ui.allocate_painter(egui::vec2(MINIMAP_SIZE, MINIMAP_SIZE), egui::Sense::hover());
+        let rect = response.rect;
+        painter.rect_filled(rect, 4.0, ui.visuals().extreme_bg_color);
+
+        let offsets = minimap_offsets(&positions, MINIMAP_SIZE / 2.0 - MINIMAP_MARGIN);
+        let (good, _) = status_colors(self.colorblind_mode);
+        for ((client, (_, _, heading)), offset) in placed.iter().zip(offsets) {
+            let color = if client.has_control {
+                good
+            } else {
+                ui.visuals().text_color()
+            };
+
+            // Arrow pointing along the heading
+            let centre = rect.center() + offset;
+            let heading = heading.to_radians();
+            let forward = egui::vec2(heading.sin(), -heading.cos());
+            let side = egui::vec2(-forward.y, forward.x);
+            painter.add(egui::Shape::convex_polygon(
+                vec![
+                    centre + forward * 6.0,
+                    centre - forward * 4.0 + side * 4.0,
+                    centre - forward * 4.0 - side * 4.0,
+                ],
+                color,
+                egui::Stroke::NONE,
+            ));
+            // Ring the one in control so it doesn't rely on color alone
+            if client.has_control {
+                painter.circle_stroke(centre, 8.0, egui::Stroke::new(1.0, color));
+            }
+            painter.text(
+                centre + egui::vec2(0.0, 8.0),
+                egui::Align2::CENTER_TOP,
+                &client.name,
+                egui::FontId::proportional(10.0),
+                color,
+            );
+        }
+    }
+
+    // Asks to confirm giving control to the keyboard selected client, host only
+    fn request_transfer_selected(&mut self) {
+        if !self.is_host || self.pending_transfer.is_some() {
+            return;
+        }
+
+        let self_name = if self.self_name.is_empty() {
+            &self.username
+        } else {
+            &self.self_name
+        };
+        if let Some(client) = self
+            .selected_client
+            .and_then(|index| self.clients.get(index))
+        {
+            if !client.has_control && &client.name != self_name {
+                self.confirm_transfer = Some(client.name.clone());
+            }
+        }
+    }
+
+    // Host only, flips the badge locally before the server echoes it back
+    fn toggle_observer(&mut self, name: &str) {
+        if let Some(client) = self.clients.iter_mut().find(|c| c.name == name) {
+            client.is_observer = !client.is_observer;
+            self.action_tx
+                .send(AppMessage::SetObserver {
+                    target: client.name.clone(),
+                    is_observer: client.is_observer,
+                })
+                .ok();
+        }
+    }
+
+    fn connect_tab(&mut self, ui: &mut egui::Ui) {
+        // Main content - two columns
+        ui.columns(2, |columns| {
+            // LEFT COLUMN: Server
+            columns[0].group(|ui| {
+                ui.heading("🖥 Host");
+                ui.add_space(5.0);
+                if self.collapse_inactive_column(ui, ConnectColumn::Host) {
+                    return;
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Port:");
+                    if ui.text_edit_singleline(&mut self.port).changed() {
+                        self.mark_settings_dirty();
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.radio_value(
+                        &mut self.server_connection_method,
+                        ConnectionMethod::CloudServer,
+                        "Cloud P2P",
+                    );
+                    ui.radio_value(
+                        &mut self.server_connection_method,
+                        ConnectionMethod::Relay,
+                        "Cloud Host",
+                    );
+                    ui.radio_value(
+                        &mut self.server_connection_method,
+                        ConnectionMethod::Direct,
+                        "Direct",
+                    );
+                });
+
+                ui.checkbox(&mut self.is_ipv6, "Use IPv6");
+
+                ui.horizontal(|ui| {
+                    ui.label("Max clients:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.max_clients_input)
+                            .hint_text("No limit")
+                            .desired_width(60.0),
+                    );
+                });
+
+                let relay = self.server_connection_method == ConnectionMethod::Relay;
+                let password_row = ui
+                    .add_enabled_ui(!relay, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label("Password:");
+                            self.password_field(ui, PasswordField::Host);
+                        });
+                    })
+                    .response;
+                if relay {
+                    password_row.on_hover_text("Cloud Host sessions can't be password protected");
+                }
+
+                let blocked_reason = self.start_blocked_reason();
+                if ui
+                    .add_enabled(
+                        blocked_reason.is_none(),
+                        egui::Button::new(if self.is_connected {
+                            "Stop Server"
+                        } else {
+                            "Start Server"
+                        }),
+                    )
+                    .on_disabled_hover_text(blocked_reason.unwrap_or_default())
+                    .clicked()
+                {
+                    if self.is_connected {
+                        self.request_disconnect();
+                    } else {
+                        self.send_connect(AppMessage::StartServer {
+                            username: self.username.clone(),
+                            port: self.port.parse().unwrap_or(7777),
+                            is_ipv6: self.is_ipv6,
+                            use_upnp: true,
+                            method: self.server_connection_method,
+                            max_clients: self.max_clients_input.trim().parse().ok(),
+                            password: if self.server_connection_method == ConnectionMethod::Relay {
+                                None
+                            } else {
+                                password_value(&self.host_password)
+                            },
+                        });
+                    }
+                }
+
+                if let Some(endpoint) = &self.public_endpoint {
+                    ui.horizontal(|ui| {
+                        ui.label("Public address:");
+                        if endpoint.is_empty() {
+                            ui.label("unknown — check your IP manually");
+                        } else {
+                            ui.label(if self.streamer_mode {
+                                "(hidden in streamer mode)"
+                            } else {
+                                endpoint.as_str()
+                            });
+                            if ui.small_button("📋 Copy").clicked() {
+                                ui.ctx().copy_text(endpoint.clone());
+                            }
+                        }
+                    });
+                }
+
+                if let Some((ip, reachable)) = &self.public_ip_check {
+                    let (good, bad) = status_colors(self.colorblind_mode);
+                    let (verdict, ok) = reachability_verdict(&self.port, *reachable);
+                    ui.horizontal(|ui| {
+                        if let (Some(ip), false) = (ip, self.streamer_mode) {
+                            ui.label(format!("🌐 {}", ip));
+                        }
+                        match ok {
+                            Some(true) => ui.colored_label(good, verdict),
+                            Some(false) => ui.colored_label(bad, verdict),
+                            None => ui.weak(verdict),
+                        };
+                    });
+                }
+
+                if let Some(invite) = self.invite_text() {
+                    if ui
+                        .button("📨 Copy Invite")
+                        .on_hover_text(invite.as_str())
+                        .clicked()
+                    {
+                        ui.ctx().copy_text(invite);
+                        self.show_toast("Invite copied".to_string(), false);
+                    }
+                }
+
+                if let (true, Some((current, max))) = (self.is_host, self.capacity) {
+                    if max == 0 {
+                        ui.label(format!("Clients: {}", current));
+                    } else {
+                        ui.label(format!("Clients: {}/{}", current, max));
+                    }
+                }
+
+                if self.is_host && self.server_supports(CAPABILITY_SESSION_LOCK) {
+                    ui.horizontal(|ui| {
+                        let mut locked = self.session_locked;
+                        if ui.checkbox(&mut locked, "Lock Session").changed() {
+                            // Reflected once the app confirms
+                            self.action_tx
+                                .send(AppMessage::SetSessionLocked { locked })
+                                .ok();
+                        }
+                        if self.session_locked {
+                            ui.label("🔒").on_hover_text("New clients can't join");
+                        }
+                    });
+                }
+
+                match self.upnp_result {
+                    Some((true, Some(port))) => {
+                        ui.label(format!("UPnP: mapped :{}", port));
+                    }
+                    Some((true, None)) => {
+                        ui.label("UPnP: mapped");
+                    }
+                    Some((false, _)) => {
+                        ui.colored_label(egui::Color32::YELLOW, "UPnP failed — forward manually");
+                    }
+                    None => {}
+                }
+            });
+
+            // RIGHT COLUMN: Client
+            columns[1].group(|ui| {
+                ui.heading("🔌 Join");
+                ui.add_space(5.0);
+                if self.collapse_inactive_column(ui, ConnectColumn::Join) {
+                    return;
+                }
+
+                let mut chosen_profile = None;
+                let mut delete_profile = false;
+                ui.horizontal(|ui| {
+                    ui.label("Profile:");
+                    let selected_text = self
+                        .selected_profile
+                        .and_then(|i| self.config.profiles.get(i))
+                        .map_or("None", |profile| profile.name.as_str());
+                    egui::ComboBox::new("join_profile", "")
+                        .selected_text(selected_text)
+                        .show_ui(ui, |ui| {
+                            for (i, profile) in self.config.profiles.iter().enumerate() {
+                                if ui
+                                    .selectable_label(
+                                        self.selected_profile == Some(i),
+                                        &profile.name,
+                                    )
+                                    .clicked()
+                                {
+                                    chosen_profile = Some(i);
+                                }
+                            }
+                        });
+                    delete_profile = ui
+                        .add_enabled(self.selected_profile.is_some(), egui::Button::new("🗑"))
+                        .on_hover_text("Delete profile")
+                        .clicked();
+                });
+                if let Some(index) = chosen_profile {
+                    self.apply_profile(index);
+                }
+                if delete_profile {
+                    self.delete_selected_profile();
+                }
+
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.profile_name_input)
+                            .hint_text("Profile name")
+                            .desired_width(120.0),
+                    );
+                    if ui
+                        .add_enabled(
+                            !self.profile_name_input.trim().is_empty(),
+                            egui::Button::new("💾 Save current as profile"),
+                        )
+                        .clicked()
+                    {
+                        self.save_current_profile();
+                    }
+                });
+
+                // Connection method radio buttons
+                ui.horizontal(|ui| {
+                    ui.radio_value(
+                        &mut self.client_connection_method,
+                        ConnectionMethod::CloudServer,
+                        "Cloud Server",
+                    );
+                    ui.radio_value(
+                        &mut self.client_connection_method,
+                        ConnectionMethod::Direct,
+                        "Direct",
+                    );
+                });
+
+                ui.add_space(5.0);
+
+                // Show different fields based on connection method
+                if self.client_connection_method == ConnectionMethod::Direct {
+                    // Direct connection: IP + Port
+                    ui.horizontal(|ui| {
+                        ui.label("IP Address:");
+                        ui.text_edit_singleline(&mut self.ip_input);
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Port:");
+                        if ui.text_edit_singleline(&mut self.port).changed() {
+                            self.mark_settings_dirty();
+                        }
+                    });
+                    if self.auto_fallback_relay {
+                        ui.horizontal(|ui| {
+                            ui.label("Session Code (optional):");
+                            ui.text_edit_singleline(&mut self.session_code);
+                        })
+                        .response
+                        .on_hover_text(
+                            "Used to retry through the cloud server if the direct connection fails",
+                        );
+                    }
+                } else {
+                    // Cloud connection: Session Code
+                    ui.horizontal(|ui| {
+                        ui.label("Session Code:");
+                        ui.text_edit_singleline(&mut self.session_code);
+                    });
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Password:");
+                    self.password_field(ui, PasswordField::Join);
+                });
+
+                ui.checkbox(&mut self.is_ipv6, "Use IPv6");
+                ui.checkbox(&mut self.join_as_spectator, "Join as Spectator")
+                    .on_hover_text("Watch the flight without ever taking control");
+
+                let blocked_reason = self.start_blocked_reason();
+                if ui
+                    .add_enabled(
+                        blocked_reason.is_none(),
+                        egui::Button::new(if self.is_connected {
+                            "Disconnect"
+                        } else {
+                            "Connect"
+                        }),
+                    )
+                    .on_disabled_hover_text(blocked_reason.unwrap_or_default())
+                    .clicked()
+                {
+                    if self.is_connected {
+                        self.request_disconnect();
+                    } else {
+                        let (session_id, ip, port) = self.connect_fields();
+                        self.send_connect(AppMessage::Connect {
+                            username: self.username.clone(),
+                            session_id,
+                            isipv6: self.is_ipv6,
+                            ip,
+                            hostname: None,
+                            port,
+                            method: self.client_connection_method,
+                            spectator: self.join_as_spectator,
+                            relay_session_id: self.relay_fallback_session_id(),
+                            password: password_value(&self.join_password),
+                        });
+                    }
+                }
+
+                if !self.is_connected {
+                    let testing = matches!(self.connection_test, Some(None));
+                    if ui
+                        .add_enabled(!testing, egui::Button::new("Test Connection"))
+                        .on_hover_text("Check the address can be reached without joining")
+                        .clicked()
+                    {
+                        self.request_connection_test();
+                    }
+
+                    match &self.connection_test {
+                        Some(None) => {
+                            ui.horizontal(|ui| {
+                                ui.spinner();
+                                ui.label("Testing...");
+                            });
+                        }
+                        Some(Some((success, detail))) => {
+                            let (good, bad) = status_colors(self.colorblind_mode);
+                            let (color, icon) = if *success {
+                                (good, "✔")
+                            } else {
+                                (bad, "✖")
+                            };
+                            ui.colored_label(color, format!("{} {}", icon, detail));
+                        }
+                        None => {}
+                    }
+                }
+            });
+        });
+    }
+
+    fn session_tab(&mut self, ui: &mut egui::Ui) {
+        let client_panel = ui.group(|ui| {
+            ui.heading("👥 Connected Clients");
+            if !self.clients.is_empty() {
+                let (pilots, observers) = roster_counts(&self.clients);
+                ui.weak(format!(
+                    "{} pilot{}, {} observer{}",
+                    pilots,
+                    if pilots == 1 { "" } else { "s" },
+                    observers,
+                    if observers == 1 { "" } else { "s" }
+                ));
+            }
+            if self.is_connected && ui.toggle_value(&mut self.self_ready, "✔ Ready").changed() {
+                self.action_tx
+                    .send(AppMessage::SetReady {
+                        ready: self.self_ready,
+                    })
+                    .ok();
+            }
+            if self.is_connected {
+                let mut observing = self.is_self_observing;
+                let mut toggle = ui.toggle_value(&mut observing, "👁 Observer");
+                if let Some(shortcut) = parse_shortcut(&self.observer_shortcut) {
+                    toggle = toggle.on_hover_text(format!(
+                        "Toggle with {}",
+                        ui.ctx().format_shortcut(&shortcut)
+                    ));
+                }
+                if toggle.clicked() {
+                    self.toggle_self_observer();
+                }
+            }
+            let mut observer_toggle = None;
+            let mut transfer_to = None;
+            let mut seat_transfer = None;
+            let mut instructor_take = None;
+            let seats_supported = self.server_supports(CAPABILITY_SEATS);
+            egui::ScrollArea::vertical()
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    let self_name = if self.self_name.is_empty() {
+                        &self.username
+                    } else {
+                        &self.self_name
+                    };
+                    let self_listed = self.clients.iter().any(|c| &c.name == self_name);
+
+                    // We aren't part of our own roster, so add a row for us
+                    if self.is_connected && !self_listed {
+                        let icon = if self.in_control { "✓" } else { "○" };
+                        ui.label(
+                            egui::RichText::new(format!("{} {} (you)", icon, self_name)).strong(),
+                        );
+                    }
+
+                    for (index, client) in self.clients.iter().enumerate() {
+                        let is_self = &client.name == self_name;
+                        let is_selected = self.selected_client == Some(index);
+                        ui.horizontal(|ui| {
+                            let is_pending =
+                                self.pending_transfer.as_deref() == Some(client.name.as_str());
+                            let icon = if client.has_control {
+                                "✓"
+                            } else if is_pending {
+                                "⏳"
+                            } else if client.is_spectator {
+                                "🎥"
+                            } else if client.is_observer {
+                                "👁"
+                            } else {
+                                "○"
+                            };
+                            let seat = client
+                                .seat
+                                .as_ref()
+                                .map(|seat| format!(" — {}", seat))
+                                .unwrap_or_default();
+                            let mut text = if is_self {
+                                egui::RichText::new(format!(
+                                    "{} {}{} (you)",
+                                    icon, client.name, seat
+                                ))
+                                .strong()
+                            } else {
+                                egui::RichText::new(format!("{} {}{}", icon, client.name, seat))
+                            };
+                            if is_selected {
+                                text = text.background_color(ui.visuals().selection.bg_fill);
+                            }
+                            let label = ui.label(text);
+                            if let Some(details) = client.details() {
+                                label.on_hover_text(details);
+                            }
+                            if client.ready {
+                                ui.colored_label(status_colors(self.colorblind_mode).0, "✔");
+                            }
+                            if let Some((to, _)) = &client.loading_aircraft {
+                                ui.weak("⟳ loading")
+                                    .on_hover_text(format!("Switching to {}", to));
+                            }
+                            let different_aircraft = self.different_aircraft(client);
+                            if let Some(aircraft) = &different_aircraft {
+                                ui.colored_label(egui::Color32::YELLOW, "⚠ different aircraft")
+                                    .on_hover_text(format!("Flying {}", aircraft));
+                            }
+                            if client.has_control && !client.input_active {
+                                ui.colored_label(egui::Color32::YELLOW, "⚠ no input")
+                                    .on_hover_text(
+                                        "No updates from them for a while, their sim or app may be frozen",
+                                    );
+                            }
+
+                            if is_self {
+                                return;
+                            }
+
+                            if client.has_control
+                                && self.instructor_mode
+                                && self.is_host
+                                && ui.small_button("Take (Instructor)").clicked()
+                            {
+                                instructor_take = Some(client.name.clone());
+                            }
+
+                            // Spectators can't take control or stop observing
+                            if client.is_spectator {
+                                ui.weak("spectating");
+                                return;
+                            }
+
+                            if !client.has_control
+                                && !is_pending
+                                && ui
+                                    .add_enabled(
+                                        different_aircraft.is_none(),
+                                        egui::Button::new("Give Control").small(),
+                                    )
+                                    .on_disabled_hover_text("They are flying a different aircraft")
+                                    .clicked()
+                            {
+                                transfer_to = Some(client.name.clone());
+                            }
+
+                            if seats_supported && !is_pending && different_aircraft.is_none() {
+                                ui.menu_button("🪑", |ui| {
+                                    for seat in SEATS {
+                                        if ui.button(format!("Give Control as {}", seat)).clicked() {
+                                            seat_transfer =
+                                                Some((client.name.clone(), seat.to_string()));
+                                            ui.close_menu();
+                                        }
+                                    }
+                                })
+                                .response
+                                .on_hover_text("Give control at a multi-crew seat");
+                            }
+
+                            if self.is_host {
+                                let label = if client.is_observer {
+                                    "Unset Observer"
+                                } else {
+                                    "Set Observer"
+                                };
+                                if ui.small_button(label).clicked() {
+                                    observer_toggle = Some(client.name.clone());
+                                }
+                            }
+                        });
+                    }
+                });
+
+            if self.clients.iter().any(|c| c.position.is_some()) {
+                egui::CollapsingHeader::new("🗺 Minimap")
+                    .default_open(true)
+                    .show(ui, |ui| self.minimap(ui));
+            }
+
+            if let Some(name) = observer_toggle {
+                self.toggle_observer(&name);
+            }
+            if let Some(name) = transfer_to {
+                self.transfer_control(&name);
+            }
+            if let Some((target, seat)) = seat_transfer {
+                self.pending_transfer = Some(target.clone());
+                self.action_tx
+                    .send(AppMessage::TransferControlSeat { target, seat })
+                    .ok();
+            }
+            if let Some(target) = instructor_take {
+                self.action_tx
+                    .send(AppMessage::InstructorTakeControl { target })
+                    .ok();
+            }
+        });
+
+        // Arrow keys move through the list while hovering it, unless typing elsewhere
+        let ctx = client_panel.response.ctx.clone();
+        if client_panel.response.contains_pointer() && !ctx.wants_keyboard_input() {
+            let (up, down, enter) = ctx.input(|i| {
+                (
+                    i.key_pressed(egui::Key::ArrowUp),
+                    i.key_pressed(egui::Key::ArrowDown),
+                    i.key_pressed(egui::Key::Enter),
+                )
+            });
+            if up || down {
+                self.selected_client =
+                    move_selection(self.selected_client, self.clients.len(), down);
+            }
+            if enter {
+                self.request_transfer_selected();
+            }
+        }
+
+        if self.is_connected && self.server_supports(CAPABILITY_CHAT) {
+            ui.separator();
+            self.chat_panel(ui);
+        }
+    }
+
+    fn settings_tab(&mut self, ui: &mut egui::Ui) {
+        ui.group(|ui| {
+            ui.heading("⚙ Settings");
+
+            let mut settings_changed = ui
+                .horizontal(|ui| {
+                    ui.label("Username:");
+                    ui.text_edit_singleline(&mut self.username).changed()
+                })
+                .inner;
+
+            ui.horizontal(|ui| {
+                ui.label("Connection timeout:");
+                let response =
+                    ui.add(egui::DragValue::new(&mut self.connection_timeout).suffix(" s"));
+                settings_changed |= response.changed();
+                if let Err(e) = validate_conn_timeout(self.connection_timeout) {
+                    let (_, bad) = status_colors(self.colorblind_mode);
+                    ui.painter().rect_stroke(
+                        response.rect.expand(1.0),
+                        2.0,
+                        egui::Stroke::new(1.0, bad),
+                    );
+                    response.on_hover_text(e);
+                }
+            });
+
+            if let Some(path) = &self.no_aircraft_path {
+                ui.colored_label(
+                    egui::Color32::YELLOW,
+                    format!("No aircraft definitions found in {}", path),
+                );
+                ui.horizontal(|ui| {
+                    ui.hyperlink_to(
+                        "📂 Open folder",
+                        format!("file:///{}", path.replace('\\', "/")),
+                    );
+                    ui.hyperlink_to(
+                        "⬇ Download definitions",
+                        "https://github.com/Sequal32/yourcontrols/releases/latest",
+                    )
+                    .on_hover_text("Reinstalling with the installer restores the definitions");
+                });
+            }
+
+            if let Some(required) = &self.required_aircraft {
+                ui.horizontal(|ui| {
+                    ui.label(format!("This session requires: {}", required));
+                    if let Some(mismatch) = self.required_aircraft_mismatch() {
+                        ui.colored_label(egui::Color32::YELLOW, format!("⚠ {}", mismatch));
+                    }
+                });
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("Aircraft:");
+                let mut picked = None;
+                ui.add_enabled_ui(!self.scanning_aircraft, |ui| {
+                    egui::ComboBox::new("aircraft", "")
+                        .selected_text(&self.aircraft_list[self.selected_aircraft])
+                        .show_ui(ui, |ui| {
+                            // Recently used configs that are still installed
+                            let recents: Vec<usize> = self
+                                .config
+                                .recent_aircraft
+                                .iter()
+                                .filter_map(|recent| {
+                                    self.aircraft_list.iter().position(|a| a == recent)
+                                })
+                                .collect();
+
+                            if !recents.is_empty() {
+                                ui.weak("Recent");
+                                for i in recents {
+                                    if ui
+                                        .selectable_label(
+                                            self.selected_aircraft == i,
+                                            &self.aircraft_list[i],
+                                        )
+                                        .clicked()
+                                    {
+                                        picked = Some(i);
+                                    }
+                                }
+                                ui.separator();
+                            }
+
+                            for (i, aircraft) in self.aircraft_list.iter().enumerate() {
+                                let is_required =
+                                    self.required_aircraft.as_deref().is_some_and(|required| {
+                                        aircraft_compatible(aircraft, required, &[])
+                                    });
+                                let text = if is_required {
+                                    egui::RichText::new(format!("★ {}", aircraft)).strong()
+                                } else {
+                                    egui::RichText::new(aircraft)
+                                };
+                                if ui
+                                    .selectable_label(self.selected_aircraft == i, text)
+                                    .clicked()
+                                {
+                                    picked = Some(i);
+                                }
+                            }
+                        });
+                });
+                if self.scanning_aircraft {
+                    ui.weak("(scanning...)");
+                }
+                if let Some(i) = picked {
+                    self.request_select_aircraft(i);
+                }
+            });
+
+            if !self.sync_caveats.is_empty() {
+                egui::CollapsingHeader::new(format!(
+                    "ℹ Known sync limitations ({})",
+                    self.sync_caveats.len()
+                ))
+                .default_open(true)
+                .show(ui, |ui| {
+                    for caveat in &self.sync_caveats {
+                        ui.label(format!("• {}", caveat));
+                    }
+                });
+            }
+
+            settings_changed |= ui
+                .checkbox(&mut self.instructor_mode, "Instructor Mode")
+                .changed();
+            settings_changed |= ui
+                .checkbox(&mut self.streamer_mode, "Streamer Mode")
+                .changed();
+            settings_changed |= ui.checkbox(&mut self.sound_muted, "Mute Sound").changed();
+            ui.add_enabled_ui(!self.sound_muted, |ui| {
+                ui.indent("sound_events", |ui| {
+                    for event in SoundEvent::ALL {
+                        let mut enabled = self.sound_events.contains(&event);
+                        if ui.checkbox(&mut enabled, event.label()).changed() {
+                            settings_changed = true;
+                            if enabled {
+                                self.sound_events.insert(event);
+                            } else {
+                                self.sound_events.remove(&event);
+                            }
+                        }
+                    }
+                });
+            });
+            settings_changed |= ui.checkbox(&mut self.dark_theme, "Dark Theme").changed();
+            settings_changed |= ui
+                .checkbox(&mut self.colorblind_mode, "Color-blind Friendly Colors")
+                .on_hover_text("Uses blue and orange instead of green and red")
+                .changed();
+            settings_changed |= ui
+                .checkbox(&mut self.notifications_enabled, "Flash Window On Events")
+                .changed();
+            settings_changed |= ui
+                .add_enabled(
+                    !self.tray_failed,
+                    egui::Checkbox::new(&mut self.minimize_to_tray, "Minimize To Tray On Close"),
+                )
+                .on_hover_text("Keeps the session running in the tray, choose Quit there to exit")
+                .on_disabled_hover_text("The tray icon isn't available on this system")
+                .changed();
+            settings_changed |= ui
+                .checkbox(
+                    &mut self.auto_disconnect_on_sim_exit,
+                    "Disconnect When Sim Closes",
+                )
+                .changed();
+            settings_changed |= ui
+                .checkbox(
+                    &mut self.auto_fallback_relay,
+                    "Fall Back To Relay If Direct Fails",
+                )
+                .on_hover_text("Needs the host's session code entered when joining directly")
+                .changed();
+            settings_changed |= ui
+                .checkbox(&mut self.auto_copy_code, "Copy Session Code When Hosting")
+                .on_hover_text("Skipped in streamer mode")
+                .changed();
+            settings_changed |= ui
+                .horizontal(|ui| {
+                    let mut enabled = self.afk_observer_mins.is_some();
+                    let toggled = ui.checkbox(&mut enabled, "Go Observer When Idle").changed();
+                    if toggled {
+                        self.afk_observer_mins = enabled.then(|| 10);
+                    }
+
+                    let mut changed = toggled;
+                    if let Some(mins) = &mut self.afk_observer_mins {
+                        changed |= ui
+                            .add(egui::DragValue::new(mins).range(1..=120).suffix(" min"))
+                            .changed();
+                    }
+                    changed
+                })
+                .inner;
+            settings_changed |= ui
+                .horizontal(|ui| {
+                    let mut enabled = self.control_rotation_mins.is_some();
+                    let toggled = ui
+                        .checkbox(&mut enabled, "Rotate Control")
+                        .on_hover_text("Hands control to the next pilot after this long in control")
+                        .changed();
+                    if toggled {
+                        self.control_rotation_mins = enabled.then(|| 15);
+                    }
+
+                    let mut changed = toggled;
+                    if let Some(mins) = &mut self.control_rotation_mins {
+                        changed |= ui
+                            .add(egui::DragValue::new(mins).range(1..=120).suffix(" min"))
+                            .changed();
+                    }
+                    changed
+                })
+                .inner;
+            settings_changed |= ui
+                .checkbox(&mut self.record_session, "Record Session")
+                .on_hover_text("Saves every UI message to a file to attach to bug reports")
+                .changed();
+            settings_changed |= ui
+                .horizontal(|ui| {
+                    ui.label("Bandwidth:");
+                    let kilobytes = ui.radio_value(
+                        &mut self.bandwidth_unit,
+                        BandwidthUnit::KilobytesPerSec,
+                        "KB/s",
+                    );
+                    let megabits =
+                        ui.radio_value(&mut self.bandwidth_unit, BandwidthUnit::Megabits, "Mbps");
+                    kilobytes.changed() || megabits.changed()
+                })
+                .inner;
+            settings_changed |= ui
+                .horizontal(|ui| {
+                    let mut enabled = self.bandwidth_warn_kbps.is_some();
+                    let toggled = ui
+                        .checkbox(&mut enabled, "Warn Above Bandwidth")
+                        .on_hover_text(
+                            "For metered connections, uses the average over a few seconds",
+                        )
+                        .changed();
+                    if toggled {
+                        self.bandwidth_warn_kbps = enabled.then(|| 50.0);
+                    }
+
+                    let mut changed = toggled;
+                    if let Some(kbps) = &mut self.bandwidth_warn_kbps {
+                        changed |= ui
+                            .add(
+                                egui::DragValue::new(kbps)
+                                    .range(1.0..=10000.0)
+                                    .suffix(" KB/s"),
+                            )
+                            .changed();
+                    }
+                    changed
+                })
+                .inner;
+            ui.horizontal(|ui| {
+                ui.label("Observer shortcut:");
+                settings_changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut self.observer_shortcut)
+                            .desired_width(100.0),
+                    )
+                    .on_hover_text("e.g. Ctrl+O, leave empty to turn off")
+                    .changed();
+                if !self.observer_shortcut.trim().is_empty()
+                    && parse_shortcut(&self.observer_shortcut).is_none()
+                {
+                    ui.colored_label(
+                        status_colors(self.colorblind_mode).1,
+                        "Not a valid shortcut",
+                    );
+                }
+            });
+            egui::CollapsingHeader::new("💬 Quick Messages").show(ui, |ui| {
+                for (key, text) in QUICK_MESSAGE_KEYS.iter().zip(&mut self.quick_messages) {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{}:", key.name()));
+                        settings_changed |= ui.text_edit_singleline(text).changed();
+                    });
+                }
+            });
+            settings_changed |= ui
+                .add(
+                    egui::Slider::new(&mut self.overlay_opacity, MIN_OVERLAY_OPACITY..=1.0)
+                        .text("Overlay Opacity"),
+                )
+                .changed();
+            if settings_changed {
+                self.mark_settings_dirty();
+            }
+            if ui.button("▭ Compact Mode").clicked() {
+                self.set_compact_mode(true);
+            }
+            if ui
+                .button("🪟 Overlay Mode")
+                .on_hover_text("A small see-through window that stays on top of the sim")
+                .clicked()
+            {
+                self.set_overlay_mode(true);
+            }
+            if ui.button("❓ Show help again").clicked() {
+                self.first_run = true;
+            }
+
+            // Flushes immediately instead of waiting for the auto-save
+            if ui.button("💾 Save Settings").clicked() {
+                self.save_settings();
+            }
+        });
+    }
+
+    // Shown above the tabs while connected
+    fn network_stats(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(format!(
+                "↓ {}",
+                format_bandwidth(self.download_bandwidth, self.bandwidth_unit)
+            ));
+            ui.separator();
+            ui.label(format!(
+                "↑ {}",
+                format_bandwidth(self.upload_bandwidth, self.bandwidth_unit)
+            ));
+            ui.separator();
+            ui.label(format!("Loss: {:.1}%", self.packet_loss * 100.0));
+            ui.separator();
+            ui.label(format!("Ping: {:.0}ms", self.ping));
+            if let Some(quality) = self.quality {
+                ui.separator();
+                ui.colored_label(
+                    quality.color(self.colorblind_mode),
+                    format!("Quality: {}", quality.label()),
+                );
+            }
+            if let Some(nat) = self.nat_text() {
+                ui.separator();
+                ui.label(nat);
+            }
+            if let Some(relay) = self.relay_text() {
+                ui.separator();
+                ui.label(relay);
+            }
+        });
+
+        let mut paused = self.sync_paused;
+        if ui.toggle_value(&mut paused, "Pause Sync").changed() {
+            // Reflected once the app confirms
+            self.action_tx
+                .send(AppMessage::SetSyncPaused { paused })
+                .ok();
+        }
+
+        if self.sync_paused {
+            ui.colored_label(egui::Color32::YELLOW, "⏸ Sync paused");
+        }
+
+        if let Some(reason) = &self.quality_reduced {
+            let text = match self.update_rate {
+                Some(hz) => format!("⚠ Reduced update rate ({}, {:.0} Hz)", reason, hz),
+                None => format!("⚠ Reduced update rate ({})", reason),
+            };
+            ui.colored_label(egui::Color32::ORANGE, text);
+        }
+
+        if let (true, Some(average), Some(cap)) = (
+            self.bandwidth_warning,
+            self.bandwidth_average,
+            self.bandwidth_warn_kbps,
+        ) {
+            ui.horizontal(|ui| {
+                ui.colored_label(
+                    egui::Color32::from_rgb(255, 191, 0),
+                    format!(
+                        "⚠ Using {} on average, above your {} limit",
+                        format_bandwidth(average, self.bandwidth_unit),
+                        format_bandwidth(cap, self.bandwidth_unit)
+                    ),
+                );
+                if self.target_update_rate > MIN_UPDATE_RATE
+                    && ui
+                        .small_button("Lower update rate")
+                        .on_hover_text("Halves how often aircraft state is sent")
+                        .clicked()
+                {
+                    self.set_target_update_rate(self.target_update_rate / 2);
+                }
+            });
+        }
+    }
+
+    fn chat_panel(&mut self, ui: &mut egui::Ui) {
+        ui.group(|ui| {
+            ui.heading("💬 Chat");
+
+            egui::ScrollArea::vertical()
+                .max_height(120.0)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for (from, text) in &self.chat {
+                        ui.label(format!("{}: {}", from, text));
+                    }
+                });
+
+            let input = ui.text_edit_singleline(&mut self.chat_input);
+            if input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                let text = self.chat_input.trim().to_string();
+                if !text.is_empty() {
+                    self.action_tx
+                        .send(AppMessage::BroadcastMessage { text })
+                        .ok();
+                }
+                self.chat_input.clear();
+                input.request_focus();
+            }
+        });
+    }
+
+    fn advanced_panel(&mut self, ui: &mut egui::Ui) {
+        ui.group(|ui| {
+            ui.heading("🔧 Advanced");
+
+            let (_, bad) = status_colors(self.colorblind_mode);
+            ui.horizontal(|ui| {
+                ui.label("Queued updates:");
+                for (label, depth) in [("out", self.queue_outbound), ("in", self.queue_inbound)] {
+                    if depth > QUEUE_DEPTH_WARN {
+                        ui.colored_label(bad, format!("⚠ {} {}", label, depth));
+                    } else {
+                        ui.label(format!("{} {}", label, depth));
+                    }
+                }
+            });
+
+            if let Some(hz) = self.update_rate {
+                ui.label(format!("Rate: {:.0} Hz", hz));
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("Max update rate:");
+                let mut hz = self.target_update_rate;
+                let slider = ui
+                    .add(
+                        egui::Slider::new(&mut hz, MIN_UPDATE_RATE..=MAX_UPDATE_RATE).suffix(" Hz"),
+                    )
+                    .on_hover_text("Lower rates use less bandwidth but look less smooth");
+                if slider.changed() {
+                    self.set_target_update_rate(hz);
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Smoothing:");
+                let mut ms = self.smoothing_ms;
+                let slider = ui
+                    .add(egui::Slider::new(&mut ms, 0..=MAX_SMOOTHING_MS).suffix(" ms"))
+                    .on_hover_text("Higher values reduce jitter but respond slower");
+                if slider.changed() {
+                    self.set_smoothing(ms);
+                }
+            });
+
+            if ui
+                .add_enabled(
+                    self.can_request_resync(),
+                    egui::Button::new("🔄 Force Resync"),
+                )
+                .on_hover_text("Fetch the full aircraft state again from whoever is in control")
+                .clicked()
+            {
+                self.request_resync();
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("Definition path:");
+                ui.text_edit_singleline(&mut self.custom_aircraft_path);
+
+                let path = self.custom_aircraft_path.trim();
+                if ui
+                    .add_enabled(!path.is_empty(), egui::Button::new("Load"))
+                    .clicked()
+                {
+                    self.action_tx
+                        .send(AppMessage::LoadAircraftPath {
+                            path: path.to_string(),
+                        })
+                        .ok();
+                }
+            });
+
+            if let Some(metrics) = &self.full_metrics {
+                egui::CollapsingHeader::new("Network Metrics").show(ui, |ui| {
+                    egui::Grid::new("full_metrics")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            let rows = [
+                                ("Sent packets", metrics.sent_packets.to_string()),
+                                ("Received packets", metrics.received_packets.to_string()),
+                                ("Acked packets", metrics.acked_packets.to_string()),
+                                ("Upload", format!("{:.2} KB/s", metrics.sent_kbps)),
+                                ("Download", format!("{:.2} KB/s", metrics.receive_kbps)),
+                                (
+                                    "Packet loss",
+                                    format!("{:.2}%", metrics.packet_loss * 100.0),
+                                ),
+                                ("Round trip time", format!("{:.1} ms", metrics.rtt)),
+                            ];
+                            for (name, value) in rows.iter() {
+                                ui.label(*name);
+                                ui.monospace(value);
+                                ui.end_row();
+                            }
+                        });
+                });
+            }
+
+            if self
+                .clients
+                .iter()
+                .any(|client| client.clock_offset.is_some())
+            {
+                egui::CollapsingHeader::new("Clock Offsets").show(ui, |ui| {
+                    egui::Grid::new("clock_offsets")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for client in &self.clients {
+                                let offset = match client.clock_offset {
+                                    Some(offset) => offset,
+                                    None => continue,
+                                };
+                                ui.label(&client.name);
+                                let text = format!("{:+.0} ms", offset);
+                                if offset.abs() > CLOCK_OFFSET_WARN_MS {
+                                    ui.colored_label(bad, text).on_hover_text(
+                                        "Large clock differences can cause prediction errors",
+                                    );
+                                } else {
+                                    ui.monospace(text);
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+            }
+
+            if !self.desync_vars.is_empty() {
+                ui.label("Desynced variables:");
+                let mut names: Vec<&String> = self.desync_vars.keys().collect();
+                names.sort();
+                for name in names {
+                    let vars = &self.desync_vars[name];
+                    egui::CollapsingHeader::new(format!("⚠ {} ({})", name, vars.len()))
+                        .id_salt(("desync", name))
+                        .show(ui, |ui| {
+                            for var in vars {
+                                ui.monospace(var);
+                            }
+                        });
+                }
+            }
+
+            if self.is_connected {
+                let inspector = egui::CollapsingHeader::new("🔍 Sync Inspector").show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Filter:");
+                        ui.text_edit_singleline(&mut self.sync_values_filter);
+                    });
+                    egui::ScrollArea::vertical()
+                        .id_salt("sync_values")
+                        .max_height(250.0)
+                        .show(ui, |ui| {
+                            egui::Grid::new("sync_values").striped(true).show(ui, |ui| {
+                                let values =
+                                    filter_sync_values(&self.sync_values, &self.sync_values_filter);
+                                for (name, value) in values {
+                                    ui.label(name);
+                                    ui.monospace(value);
+                                    ui.end_row();
+                                }
+                            });
+                        });
+                });
+                self.sync_inspector_open = inspector.body_returned.is_some();
+            }
+
+            let mut export = false;
+            let mut import = false;
+            ui.horizontal(|ui| {
+                ui.label("Settings file:");
+                ui.text_edit_singleline(&mut self.config_file_path);
+
+                let has_path = !self.config_file_path.trim().is_empty();
+                export = ui
+                    .add_enabled(has_path, egui::Button::new("Export"))
+                    .clicked();
+                import = ui
+                    .add_enabled(has_path, egui::Button::new("Import"))
+                    .clicked();
+            });
+            if export {
+                self.export_config();
+            }
+            if import {
+                self.import_config();
+            }
+
+            if ui.button("📋 Copy Diagnostics").clicked() {
+                ui.ctx().copy_text(self.diagnostics_text());
+            }
+
+            egui::CollapsingHeader::new("Raw Settings").show(ui, |ui| {
+                let raw = serde_json::to_string_pretty(&self.config).unwrap_or_default();
+                ui.add(egui::TextEdit::multiline(&mut raw.as_str()).code_editor());
+            });
+        });
+    }
+
+    fn process_events(&mut self) {
+        // Process all pending events
+        while let Ok(event) = self.event_rx.try_recv() {
+            self.event_queue.push_back(event);
+        }
+
+        // Handle events
+        while let Some(event) = self.event_queue.pop_front() {
+            self.handle_event(event);
+        }
+    }
+
+    fn handle_event(&mut self, event: UiEvent) {
+        match event {
+            UiEvent::Error(msg) => {
+                self.status_message = format!("Error: {}", msg);
+                self.status_state = StatusState::Error;
+                self.is_connected = false;
+                self.is_host = false;
+            }
+            UiEvent::Attempt => {
+                self.status_message = "Attempting connection...".to_string();
+                self.connection_failed = false;
+                self.status_state = StatusState::Connecting;
+            }
+            UiEvent::RelayFallback => {
+                self.status_message = "Direct failed, trying relay...".to_string();
+                self.status_state = StatusState::Connecting;
+            }
+            UiEvent::Connected => {
+                self.status_message = "Connected to server".to_string();
+                self.status_state = StatusState::Connected;
+                self.is_connected = true;
+                self.current_tab = Tab::Session;
+                self.start_session();
+            }
+            UiEvent::SetStatus(state) => {
+                self.status_state = state;
+            }
+            UiEvent::ClearStatus => {
+                self.status_message = neutral_status(self.is_connected).to_string();
+            }
+            UiEvent::SetSelfName(name) => {
+                self.self_name = name;
+            }
+            UiEvent::QualityReduced { active, reason } => {
+                self.quality_reduced = if active { Some(reason) } else { None };
+            }
+            UiEvent::ConfigErrors(errors) => {
+                self.show_toast(format!("Settings not saved: {}", errors.join(", ")), true);
+            }
+            UiEvent::ScanningAircraft(active) => {
+                self.scanning_aircraft = active;
+            }
+            UiEvent::SyncCaveats(caveats) => {
+                self.sync_caveats = caveats;
+            }
+            UiEvent::Prediction(predicting) => {
+                self.predicting = predicting;
+            }
+            UiEvent::Deeplink(url) => match parse_join_url(&url) {
+                Some(info) => {
+                    self.client_connection_method = info.method;
+                    self.is_ipv6 = info.isipv6;
+                    if let Some(session_id) = info.session_id {
+                        self.session_code = session_id;
+                    }
+                    if let Some(ip) = info.ip {
+                        self.ip_input = ip.to_string();
+                    }
+                    if let Some(port) = info.port {
+                        self.port = port.to_string();
+                    }
+                    self.show_toast("Join details filled in from link".to_string(), false);
+                }
+                None => {
+                    warn!("[UI] Invalid join link {}", url);
+                    self.show_toast(format!("Invalid join link {}", url), true);
+                }
+            },
+            UiEvent::Capacity { current, max } => {
+                self.capacity = Some((current, max));
+            }
+            UiEvent::SessionLocked(locked) => {
+                self.session_locked = locked;
+            }
+            UiEvent::ServerCapabilities(caps) => {
+                self.server_capabilities = Some(caps.into_iter().collect());
+            }
+            UiEvent::SyncPaused(paused) => {
+                self.sync_paused = paused;
+            }
+            UiEvent::Syncing(syncing) => {
+                self.syncing = syncing;
+            }
+            UiEvent::SimConnected(connected) => {
+                self.sim_connected = connected;
+            }
+            UiEvent::DetectedAircraft(title) => {
+                self.detected_aircraft = Some(title).filter(|title| !title.is_empty());
+                // Followed by UnsupportedAircraft if there's no definition for it
+                self.unsupported_aircraft = None;
+            }
+            UiEvent::RequiredAircraft(name) => {
+                self.required_aircraft = Some(name).filter(|name| !name.is_empty());
+            }
+            UiEvent::UnsupportedAircraft(title) => {
+                self.unsupported_aircraft = Some(title);
+            }
+            // Only once the app has read the definition, so mismatch checks use what's loaded
+            UiEvent::AircraftLoaded(config_file_name) => {
+                self.loaded_aircraft = Some(config_file_name);
+                self.unsupported_aircraft = None;
+            }
+            UiEvent::SimDisconnected => {
+                self.sim_connected = false;
+                self.detected_aircraft = None;
+                self.unsupported_aircraft = None;
+                self.status_message = "Simulator connection lost".to_string();
+                self.status_state = StatusState::Error;
+                // The app already disconnected when the setting is on
+                self.confirm_sim_lost = self.is_connected && !self.auto_disconnect_on_sim_exit;
+            }
+            UiEvent::PublicIpCheck { ip, reachable } => {
+                self.public_ip_check = Some((ip, reachable));
+            }
+            UiEvent::TestConnectionResult { success, detail } => {
+                self.connection_test = Some(Some((success, detail)));
+            }
+            UiEvent::UpnpResult {
+                success,
+                external_port,
+            } => {
+                self.upnp_result = Some((success, external_port));
+            }
+            UiEvent::RelayRegion { region, ping } => {
+                self.relay_region = Some((region, ping));
+            }
+            UiEvent::ServerFail(reason) => {
+                self.status_message = format!("Server failed: {}", reason);
+                self.connection_failed = true;
+                self.status_state = StatusState::Disconnected;
+                self.is_connected = false;
+                self.is_host = false;
+                self.public_endpoint = None;
+                self.hosted_session_code = None;
+                self.upnp_result = None;
+                self.public_ip_check = None;
+                self.relay_region = None;
+                self.session_locked = false;
+                self.server_capabilities = None;
+                self.sync_paused = false;
+                self.syncing = false;
+                self.capacity = None;
+                self.confirm_sim_lost = false;
+                // The idle sim probe reports it again if it still applies
+                self.unsupported_aircraft = None;
+                self.end_session();
+                self.reset_metrics();
+            }
+            UiEvent::ServerBindFailed {
+                addr,
+                reason,
+                suggested_port,
+            } => {
+                self.handle_event(UiEvent::ServerFail(reason.clone()));
+                self.status_message = format!("Couldn't bind {} — {}.", addr, reason);
+                if let Some(port) = suggested_port {
+                    self.status_message += &format!(" Try port {} instead.", port);
+                    self.port = port.to_string();
+                } else {
+                    self.status_message += " Try another port.";
+                }
+            }
+            UiEvent::ClientFail(reason) => {
+                self.status_message = format!("Client failed: {}", reason);
+                self.connection_failed = true;
+                self.session_locked = false;
+                self.server_capabilities = None;
+                self.sync_paused = false;
+                self.syncing = false;
+                self.status_state = StatusState::Disconnected;
+                self.confirm_sim_lost = false;
+                self.is_connected = false;
+                self.is_host = false;
+                self.public_endpoint = None;
+                self.hosted_session_code = None;
+                self.public_ip_check = None;
+                self.nat_result = None;
+                self.relay_region = None;
+                self.self_ready = false;
+                self.is_self_observing = false;
+                self.required_aircraft = None;
+                self.unsupported_aircraft = None;
+                self.clients.clear();
+                self.selected_client = None;
+                self.confirm_transfer = None;
+                self.desync_vars.clear();
+                self.end_session();
+                self.reset_metrics();
+            }
+            UiEvent::RequestAttention => {
+                self.attention_requested = true;
+            }
+            UiEvent::InputActivity => {
+                self.last_activity = Instant::now();
+                self.afk_prompt_since = None;
+            }
+            UiEvent::GainControl => {
+                self.status_message = "You have control".to_string();
+                self.in_control = true;
+                self.last_activity = Instant::now();
+                self.control_countdown = None;
+            }
+            UiEvent::LoseControl => {
+                self.status_message = "You lost control".to_string();
+                self.in_control = false;
+                self.control_countdown = None;
+            }
+            UiEvent::ServerStarted => {
+                self.status_message = "Server started".to_string();
+                self.status_state = StatusState::Connected;
+                self.is_connected = true;
+                self.is_host = true;
+                self.current_tab = Tab::Session;
+                self.start_session();
+            }
+            UiEvent::SessionCode(code) => {
+                self.status_message = format!("Session Code: {}", code);
+                // Pasting it somewhere by accident would show it on stream
+                if self.auto_copy_code && !self.streamer_mode {
+                    self.pending_copy = Some(code.clone());
+                    self.show_toast("Code copied!".to_string(), false);
+                }
+                self.hosted_session_code = Some(code);
+            }
+            UiEvent::SetHost => {
+                self.status_message = "You are now hosting".to_string();
+                self.is_host = true;
+            }
+            UiEvent::PublicEndpoint(addr) => {
+                self.public_endpoint = Some(addr);
+            }
+            UiEvent::NatResult {
+                punched,
+                relay_fallback,
+            } => {
+                self.nat_result = Some((punched, relay_fallback));
+            }
+            UiEvent::NewConnection(name) => {
+                let unique_name = unique_client_name(&self.clients, &name);
+                if unique_name != name {
+                    warn!(
+                        "[UI] Duplicate client name {}, shown as {}",
+                        name, unique_name
+                    );
+                    self.show_toast(
+                        format!(
+                            "Two clients are named {}, actions may affect the wrong one",
+                            name
+                        ),
+                        true,
+                    );
+                }
+                self.clients.push(ClientInfo {
+                    name: unique_name,
+                    ..Default::default()
+                });
+                self.session_stats.peak_clients =
+                    self.session_stats.peak_clients.max(self.clients.len());
+            }
+            UiEvent::NewConnectionDetailed {
+                name,
+                aircraft,
+                version,
+            } => {
+                // A join goes through the usual path, later ones update the roster
+                if !self.clients.iter().any(|c| c.name == name) {
+                    self.handle_event(UiEvent::NewConnection(name.clone()));
+                }
+                if let Some(client) = self.clients.iter_mut().find(|c| c.name == name) {
+                    client.aircraft = aircraft;
+                    client.version = version;
+                    client.loading_aircraft = None;
+                }
+            }
+            UiEvent::ClientChangingAircraft { name, to } => {
+                if let Some(client) = self.clients.iter_mut().find(|c| c.name == name) {
+                    client.loading_aircraft = Some((to.clone(), Instant::now()));
+                    self.show_toast(format!("{} is switching to {}...", name, to), false);
+                }
+            }
+            UiEvent::ClientPosition {
+                name,
+                lat,
+                lon,
+                heading,
+            } => {
+                if let Some(client) = self.clients.iter_mut().find(|c| c.name == name) {
+                    client.position = Some((lat, lon, heading));
+                }
+            }
+            UiEvent::LostConnection(name) => {
+                self.clients.retain(|c| c.name != name);
+                self.desync_vars.remove(&name);
+                self.selected_client = self
+                    .selected_client
+                    .map(|index| index.min(self.clients.len().saturating_sub(1)))
+                    .filter(|_| !self.clients.is_empty());
+                if self.confirm_transfer.as_deref() == Some(name.as_str()) {
+                    self.confirm_transfer = None;
+                }
+
+                if self.pending_transfer.as_deref() == Some(name.as_str()) {
+                    self.pending_transfer = None;
+                    self.show_toast(
+                        format!("Could not give control to {}: they disconnected", name),
+                        true,
+                    );
+                }
+            }
+            UiEvent::Observing(observing) => {
+                self.is_self_observing = observing;
+            }
+            UiEvent::SetObserving { name, observing } => {
+                if let Some(client) = self.clients.iter_mut().find(|c| c.name == name) {
+                    client.is_observer = observing;
+                }
+            }
+            UiEvent::ClientInputActive { name, active } => {
+                if let Some(client) = self.clients.iter_mut().find(|c| c.name == name) {
+                    client.input_active = active;
+                }
+            }
+            UiEvent::SetSpectator(name) => {
+                if let Some(client) = self.clients.iter_mut().find(|c| c.name == name) {
+                    client.is_spectator = true;
+                    client.is_observer = true;
+                }
+            }
+            UiEvent::SetClientReady { name, ready } => {
+                if let Some(client) = self.clients.iter_mut().find(|c| c.name == name) {
+                    client.ready = ready;
+                }
+            }
+            UiEvent::ClientSeat { name, seat } => {
+                for client in &mut self.clients {
+                    if client.name == name {
+                        client.seat.clone_from(&seat);
+                    } else if seat.is_some() && client.seat == seat {
+                        // Whoever sat there before was moved out
+                        client.seat = None;
+                    }
+                }
+            }
+            UiEvent::ClockOffset { name, offset } => {
+                if let Some(client) = self.clients.iter_mut().find(|c| c.name == name) {
+                    client.clock_offset = Some(offset);
+                }
+            }
+            UiEvent::SetInControl(name) => {
+                self.pending_transfer = None;
+                self.control_countdown = None;
+                // Clear all control flags
+                for client in &mut self.clients {
+                    client.has_control = false;
+                }
+                // Set the new controller
+                if let Some(client) = self.clients.iter_mut().find(|c| c.name == name) {
+                    client.has_control = true;
+                }
+            }
+            UiEvent::ControlChanged { new, previous } => {
+                match &previous {
+                    Some(previous) => info!("[UI] Control changed from {} to {}", previous, new),
+                    None => info!("[UI] {} took control", new),
+                }
+                self.handle_event(UiEvent::SetInControl(new));
+            }
+            UiEvent::ControlTransferFailed { target, reason } => {
+                if self.pending_transfer.as_deref() == Some(target.as_str()) {
+                    self.pending_transfer = None;
+                }
+                self.show_toast(
+                    format!("Could not give control to {}: {}", target, reason),
+                    true,
+                );
+            }
+            UiEvent::ControlCountdown {
+                seconds_remaining,
+                next,
+            } => {
+                self.control_countdown = if seconds_remaining == 0 {
+                    None
+                } else {
+                    Some((seconds_remaining, next))
+                };
+            }
+            UiEvent::NoAircraftFound(path) => {
+                self.no_aircraft_path = Some(path);
+            }
+            UiEvent::AddAircraft(name) => {
+                self.no_aircraft_path = None;
+                if self.aircraft_list.len() == 1 && self.aircraft_list[0] == "Select an aircraft..."
+                {
+                    self.aircraft_list.clear();
+                }
+                self.aircraft_list.push(name);
+            }
+            UiEvent::Version(version) => {
+                self.status_message = format!("Update available: {}", version);
+                self.update_available = Some(version);
+            }
+            UiEvent::SyncValues(values) => {
+                self.sync_values = values;
+            }
+            UiEvent::UpdateReady(version) => {
+                self.update_ready = Some(version);
+            }
+            UiEvent::UpdateFailed => {
+                self.status_message = "Update download failed".to_string();
+            }
+            UiEvent::UpdateFailedDetail { reason, retryable } => {
+                self.status_message = format!("Update failed: {}", reason);
+                self.update_error = Some((reason, retryable));
+            }
+            UiEvent::UpdateConfirm { files, size } => {
+                // The download worked, so later failures get a fresh set of retries
+                self.update_retries = 0;
+                self.update_error = None;
+                self.update_confirm = Some((files, size));
+            }
+            UiEvent::SendConfig(config_json) => {
+                // Parse and load config, missing fields fall back to defaults
+                if let Ok(config) = serde_json::from_str::<Config>(&config_json) {
+                    self.load_config(config);
+                }
+            }
+            // Late metrics from a closed connection would show up on the next one
+            UiEvent::SendMetrics { .. } | UiEvent::FullMetrics { .. } if !self.is_connected => {}
+            UiEvent::SendMetrics {
+                sent_packets: _,
+                received_packets: _,
+                sent_kbps,
+                receive_kbps,
+                packet_loss,
+                ping,
+            } => {
+                self.download_bandwidth = receive_kbps;
+                self.upload_bandwidth = sent_kbps;
+                self.packet_loss = packet_loss;
+                self.ping = ping;
+                self.quality = Some(quality_score(ping, packet_loss, sent_kbps + receive_kbps));
+                let average = rolling_average(self.bandwidth_average, sent_kbps + receive_kbps);
+                self.bandwidth_average = Some(average);
+                self.bandwidth_warning = self
+                    .bandwidth_warn_kbps
+                    .is_some_and(|cap| over_bandwidth_cap(average, cap, self.bandwidth_warning));
+
+                let now = Instant::now();
+                let stats = &mut self.session_stats;
+                if let Some(since) = stats.last_sample.or(stats.started) {
+                    let secs = now.duration_since(since).as_secs_f64();
+                    stats.sent_kb += f64::from(sent_kbps) * secs;
+                    stats.received_kb += f64::from(receive_kbps) * secs;
+                }
+                stats.last_sample = Some(now);
+                stats.ping_total += f64::from(ping);
+                stats.ping_samples += 1;
+
+                // Flip between connected and degraded as the link changes
+                match self.status_state {
+                    StatusState::Connected if packet_loss > DEGRADED_PACKET_LOSS => {
+                        self.status_state = StatusState::Degraded;
+                    }
+                    StatusState::Degraded if packet_loss <= DEGRADED_PACKET_LOSS => {
+                        self.status_state = StatusState::Connected;
+                    }
+                    _ => {}
+                }
+            }
+            UiEvent::FullMetrics {
+                sent_packets,
+                received_packets,
+                acked_packets,
+                sent_kbps,
+                receive_kbps,
+                packet_loss,
+                rtt,
+            } => {
+                self.full_metrics = Some(MetricsDetail {
+                    sent_packets,
+                    received_packets,
+                    acked_packets,
+                    sent_kbps,
+                    receive_kbps,
+                    packet_loss,
+                    rtt,
+                });
+            }
+            UiEvent::QueueDepth { outbound, inbound } => {
+                self.queue_outbound = outbound;
+                self.queue_inbound = inbound;
+            }
+            UiEvent::UpdateRate(hz) => {
+                self.update_rate = Some(hz);
+            }
+            UiEvent::SmoothingChanged(ms) => {
+                self.smoothing_ms = ms;
+            }
+            UiEvent::DesyncVars { client, vars } => {
+                if vars.is_empty() {
+                    self.desync_vars.remove(&client);
+                } else {
+                    self.desync_vars.insert(client, vars);
+                }
+            }
+            UiEvent::ChatMessage { from, text } => {
+                if self.chat.len() == CHAT_HISTORY {
+                    self.chat.pop_front();
+                }
+                self.chat.push_back((from, text));
+            }
+        }
+    }
+}
+
+impl eframe::App for YourControlsApp {
+    // The window is created transparent, only the overlay lets that show
+    fn clear_color(&self, visuals: &egui::Visuals) -> [f32; 4] {
+        if self.overlay_mode {
+            egui::Rgba::TRANSPARENT.to_array()
+        } else {
+            visuals.panel_fill.to_normalized_gamma_f32()
+        }
+    }
+
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Process events from backend
+        self.process_events();
+        self.finish_aircraft_switches(Instant::now());
+        self.check_idle(Instant::now());
+
+        if autosave_due(
+            self.settings_dirty,
+            self.last_settings_change,
+            Instant::now(),
+        ) {
+            self.save_settings();
+        }
+
+        // Request repaint for real-time updates
+        ctx.request_repaint();
+
+        // Apply theme
+        if self.dark_theme {
+            ctx.set_visuals(egui::Visuals::dark());
+        } else {
+            ctx.set_visuals(egui::Visuals::light());
+        }
+
+        self.update_tray(ctx);
+
+        if let Some(text) = self.pending_copy.take() {
+            ctx.copy_text(text);
+        }
+
+        // Typing in a text field shouldn't fire them
+        if !ctx.wants_keyboard_input() {
+            let pressed = ctx.input(|i| {
+                QUICK_MESSAGE_KEYS
+                    .iter()
+                    .position(|key| i.focused && i.key_pressed(*key))
+            });
+            if let Some(slot) = pressed {
+                self.send_quick_message(slot);
+            }
+
+            let toggle_self_observer = parse_shortcut(&self.observer_shortcut)
+                .is_some_and(|shortcut| ctx.input_mut(|i| i.consume_shortcut(&shortcut)));
+            if toggle_self_observer {
+                self.toggle_self_observer();
+            }
+        }
+
+        if self.attention_requested {
+            ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(
+                egui::UserAttentionType::Informational,
+            ));
+            self.attention_requested = false;
+        }
+
+        if self.overlay_mode != self.applied_overlay_mode {
+            self.apply_overlay_mode(ctx);
+        }
+        if self.overlay_mode {
+            self.track_overlay_position(ctx);
+        }
+
+        if !self.overlay_mode && self.compact_mode != self.applied_compact_mode {
+            let (size, min_size) = if self.compact_mode {
+                (COMPACT_SIZE, COMPACT_SIZE)
+            } else {
+                (FULL_SIZE, FULL_MIN_SIZE)
+            };
+            ctx.send_viewport_cmd(egui::ViewportCommand::MinInnerSize(min_size.into()));
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size.into()));
+            self.applied_compact_mode = self.compact_mode;
+        }
+
+        let mut panel = egui::CentralPanel::default();
+        if self.overlay_mode {
+            let fill = ctx
+                .style()
+                .visuals
+                .panel_fill
+                .gamma_multiply(self.overlay_opacity);
+            panel = panel.frame(egui::Frame::central_panel(&ctx.style()).fill(fill));
+        }
+
+        panel.show(ctx, |ui| {
+            if self.overlay_mode {
+                self.overlay_panel(ui);
+                return;
+            }
+
+            if self.compact_mode {
+                self.compact_panel(ui);
+                return;
+            }
+
+            // Status bar
+            self.status_bar(ui);
+
+            if self.syncing {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Syncing aircraft state...");
+                });
+            }
+
+            if let Some(version) = &self.update_ready {
+                ui.label(format!("Updated to version {}", version));
+            } else if let Some(version) = &self.update_available {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Version {} is available", version));
+                    if ui
+                        .add_enabled(
+                            self.update_confirm.is_none(),
+                            egui::Button::new("⬇ Download Update"),
+                        )
+                        .clicked()
+                    {
+                        self.action_tx.send(AppMessage::RunUpdater).ok();
+                    }
+                });
+            }
+
+            if let Some((reason, _)) = &self.update_error {
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        status_colors(self.colorblind_mode).1,
+                        format!("Update failed: {}", reason),
+                    );
+                    if self.can_retry_update() && ui.button("🔄 Retry").clicked() {
+                        self.retry_update();
+                    } else if self.update_retries >= MAX_UPDATE_RETRIES {
+                        ui.weak("Try again later or download the installer manually");
+                    }
+                });
+            }
+
+            if let Some(version) = self.newer_peer_version() {
+                ui.colored_label(
+                    egui::Color32::from_rgb(255, 191, 0),
+                    format!(
+                        "A newer version ({}) is available — update for best compatibility",
+                        version
+                    ),
+                );
+            }
+
+            if let Some(title) = &self.unsupported_aircraft {
+                ui.add_space(4.0);
+                ui.heading(
+                    egui::RichText::new(format!("⚠ No definition for {} — sync disabled", title))
+                        .color(status_colors(self.colorblind_mode).1),
+                );
+            }
+
+            if self.connection_failed
+                && self.last_connect.is_some()
+                && ui.button("🔄 Reconnect").clicked()
+            {
+                self.reconnect();
+            }
+
+            if self.is_connected {
+                ui.separator();
+                self.network_stats(ui);
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                for (tab, label) in TABS {
+                    ui.selectable_value(&mut self.current_tab, tab, label);
+                }
+            });
+            ui.separator();
+
+            self.sync_inspector_open = false;
+            egui::ScrollArea::vertical().show(ui, |ui| match self.current_tab {
+                Tab::Connect => self.connect_tab(ui),
+                Tab::Session => self.session_tab(ui),
+                Tab::Settings => self.settings_tab(ui),
+                Tab::Advanced => self.advanced_panel(ui),
+            });
+            self.watch_sync_values(self.sync_inspector_open);
+        });
+
+        if self.first_run {
+            egui::Window::new("Welcome to YourControls")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.heading("🖥 Host");
+                    ui.label(
+                        "One person hosts the session. Start a server on the Connect tab and \
+                         share the session code it shows with everyone joining.",
+                    );
+                    ui.add_space(5.0);
+                    ui.heading("🔌 Join");
+                    ui.label(
+                        "Everyone else joins by entering the host's session code, or their \
+                         IP and port when connecting directly. Load the same aircraft \
+                         definition as the host first.",
+                    );
+                    ui.add_space(5.0);
+                    ui.heading("✓ Control");
+                    ui.label(
+                        "Only the person in control flies, the others follow along. Hand \
+                         control over from the Session tab with Give Control.",
+                    );
+                    ui.add_space(10.0);
+                    if ui.button("Got it").clicked() {
+                        self.dismiss_onboarding();
+                    }
+                });
+        }
+
+        if let Some(summary) = self.last_session.clone() {
+            egui::Window::new("Session Recap")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    egui::Grid::new("session_recap")
+                        .num_columns(2)
+                        .show(ui, |ui| {
+                            let ping = summary
+                                .average_ping
+                                .map(|ping| format!("{:.0} ms", ping))
+                                .unwrap_or_else(|| "—".to_string());
+                            for (label, value) in [
+                                ("Duration", format_session_duration(summary.duration)),
+                                ("Peak clients", summary.peak_clients.to_string()),
+                                ("Data sent", format!("{:.1} KB", summary.sent_kb)),
+                                ("Data received", format!("{:.1} KB", summary.received_kb)),
+                                ("Average ping", ping),
+                            ] {
+                                ui.label(label);
+                                ui.label(value);
+                                ui.end_row();
+                            }
+                        });
+                    if ui.button("Dismiss").clicked() {
+                        self.last_session = None;
+                    }
+                });
+        }
+
+        if let Some((files, size)) = self.update_confirm.clone() {
+            egui::Window::new("Install update?")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(format!(
+                        "The installer ({}) will replace the files in:",
+                        format_size(size)
+                    ));
+                    egui::ScrollArea::vertical()
+                        .max_height(160.0)
+                        .show(ui, |ui| {
+                            for file in &files {
+                                ui.monospace(file);
+                            }
+                        });
+                    ui.label("YourControls closes while it installs and reopens afterwards.");
+                    ui.horizontal(|ui| {
+                        if ui.button("Install").clicked() {
+                            self.confirm_update(true);
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.confirm_update(false);
+                        }
+                    });
+                });
+        }
+
+        if self.confirm_disconnect {
+            egui::Window::new("Disconnect?")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label("You are in control — disconnect anyway?");
+                    ui.horizontal(|ui| {
+                        if ui.button("Disconnect").clicked() {
+                            self.disconnect();
+                            self.confirm_disconnect = false;
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.confirm_disconnect = false;
+                        }
+                    });
+                });
+        }
+
+        if let Some(aircraft) = self.pending_aircraft.clone() {
+            egui::Window::new("Switch aircraft?")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(format!(
+                        "Switching to {} will reload your plane — continue?",
+                        aircraft
+                    ));
+                    ui.horizontal(|ui| {
+                        if ui.button("Switch").clicked() {
+                            self.confirm_pending_aircraft();
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.pending_aircraft = None;
+                        }
+                    });
+                });
+        }
+
+        if let Some(target) = self.confirm_transfer.clone() {
+            egui::Window::new("Give control?")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(format!("Give control to {}?", target));
+                    ui.horizontal(|ui| {
+                        if ui.button("Give Control").clicked() {
+                            self.transfer_control(&target);
+                            self.confirm_transfer = None;
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.confirm_transfer = None;
+                        }
+                    });
+                });
+        }
+
+        if self.confirm_sim_lost {
+            egui::Window::new("Simulator connection lost")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label("The simulator closed — leave the session?");
+                    ui.horizontal(|ui| {
+                        if ui.button("Disconnect").clicked() {
+                            self.disconnect();
+                            self.confirm_sim_lost = false;
+                        }
+                        if ui.button("Stay Connected").clicked() {
+                            self.confirm_sim_lost = false;
+                        }
+                    });
+                });
+        }
+
+        if let Some(since) = self.afk_prompt_since {
+            let remaining = AFK_PROMPT_TIMEOUT.saturating_sub(since.elapsed());
+            egui::Window::new("Idle — go observer?")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(format!(
+                        "No control inputs for a while. Going observer in {}s so someone else can fly.",
+                        remaining.as_secs()
+                    ));
+                    ui.horizontal(|ui| {
+                        if ui.button("Go Observer").clicked() {
+                            self.go_observer();
+                        }
+                        if ui.button("I'm Here").clicked() {
+                            self.last_activity = Instant::now();
+                            self.afk_prompt_since = None;
+                        }
+                    });
+                });
+        }
+
+        if let Some((seconds, next)) = &self.control_countdown {
+            egui::Area::new(egui::Id::new("control_countdown"))
+                .anchor(egui::Align2::CENTER_TOP, [0.0, 40.0])
+                .show(ctx, |ui| {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.heading(format!("Control → {} in {}s", next, seconds));
+                    });
+                });
+        }
+
+        if let Some(toast) = &self.toast {
+            if toast.shown_at.elapsed() > TOAST_DURATION {
+                self.toast = None;
+            } else {
+                egui::Area::new(egui::Id::new("toast"))
+                    .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -10.0])
+                    .show(ctx, |ui| {
+                        egui::Frame::popup(ui.style()).show(ui, |ui| {
+                            if toast.is_error {
+                                let (_, bad) = status_colors(self.colorblind_mode);
+                                ui.colored_label(bad, format!("✖ {}", toast.text));
+                            } else {
+                                ui.label(&toast.text);
+                            }
+                        });
+                    });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Startup message already taken off the returned receiver
+    fn test_app() -> (YourControlsApp, Receiver<AppMessage>) {
+        let (action_tx, action_rx) = unbounded();
+        let (_event_tx, event_rx) = unbounded();
+        let app = YourControlsApp::new(action_tx, event_rx);
+        action_rx.try_recv().unwrap();
+        (app, action_rx)
+    }
+
+    fn test_backend() -> (EguiBackend, Receiver<UiEvent>) {
+        let (_action_tx, action_rx) = unbounded();
+        let (event_tx, event_rx) = unbounded();
+        let backend = EguiBackend {
+            exited: Arc::new(AtomicBool::new(false)),
+            rx: action_rx,
+            event_tx,
+        };
+        (backend, event_rx)
+    }
+
+    #[test]
+    fn test_trait_methods_map_to_events() {
+        let (backend, event_rx) = test_backend();
+
+        let expected = vec![
+            UiEvent::Error("oops".to_string()),
+            UiEvent::Attempt,
+            UiEvent::Connected,
+            UiEvent::RelayFallback,
+            UiEvent::ServerFail("bind".to_string()),
+            UiEvent::ServerBindFailed {
+                addr: "0.0.0.0:7777".to_string(),
+                reason: "port in use".to_string(),
+                suggested_port: Some(7778),
+            },
+            UiEvent::ClientFail("timeout".to_string()),
+            UiEvent::SetStatus(StatusState::Degraded),
+            UiEvent::ClearStatus,
+            UiEvent::SimDisconnected,
+            UiEvent::SimConnected(true),
+            UiEvent::SimConnected(false),
+            UiEvent::DetectedAircraft("Cessna Skyhawk G1000 Asobo".to_string()),
+            UiEvent::RequiredAircraft("Asobo - C172".to_string()),
+            UiEvent::UnsupportedAircraft("Homebuilt Gyrocopter".to_string()),
+            UiEvent::AircraftLoaded("Custom - C172.yaml".to_string()),
+            UiEvent::Capacity { current: 3, max: 8 },
+            UiEvent::SessionLocked(true),
+            UiEvent::ServerCapabilities(vec!["chat".to_string()]),
+            UiEvent::SyncPaused(true),
+            UiEvent::SyncPaused(false),
+            UiEvent::Syncing(true),
+            UiEvent::Syncing(false),
+            UiEvent::Deeplink("yourcontrols://join?code=ABC123".to_string()),
+            UiEvent::Prediction(true),
+            UiEvent::ScanningAircraft(true),
+            UiEvent::ScanningAircraft(false),
+            UiEvent::SyncCaveats(vec!["Autopilot not synced on this aircraft".to_string()]),
+            UiEvent::ConfigErrors(vec!["Port must be between 1 and 65535".to_string()]),
+            UiEvent::QualityReduced {
+                active: true,
+                reason: "high packet loss".to_string(),
+            },
+            UiEvent::SetSelfName("Alice".to_string()),
+            UiEvent::PublicIpCheck {
+                ip: Some("1.2.3.4".to_string()),
+                reachable: Some(true),
+            },
+            UiEvent::UpnpResult {
+                success: true,
+                external_port: Some(7777),
+            },
+            UiEvent::TestConnectionResult {
+                success: true,
+                detail: "1.2.3.4:7777 answered".to_string(),
+            },
+            UiEvent::RelayRegion {
+                region: "eu-west".to_string(),
+                ping: Some(34.0),
+            },
+            UiEvent::RequestAttention,
+            UiEvent::GainControl,
+            UiEvent::LoseControl,
+            UiEvent::InputActivity,
+            UiEvent::ServerStarted,
+            UiEvent::SessionCode("ABC123".to_string()),
+            UiEvent::SetHost,
+            UiEvent::PublicEndpoint("1.2.3.4:7777".to_string()),
+            UiEvent::NatResult {
+                punched: false,
+                relay_fallback: true,
+            },
+            UiEvent::NewConnection("Bob".to_string()),
+            UiEvent::NewConnectionDetailed {
+                name: "Bob".to_string(),
+                aircraft: Some("C172".to_string()),
+                version: None,
+            },
+            UiEvent::ClientChangingAircraft {
+                name: "Bob".to_string(),
+                to: "C172".to_string(),
+            },
+            UiEvent::ClientPosition {
+                name: "Bob".to_string(),
+                lat: 47.5,
+                lon: -122.25,
+                heading: 90.0,
+            },
+            UiEvent::LostConnection("Bob".to_string()),
+            UiEvent::Observing(true),
+            UiEvent::Observing(false),
+            UiEvent::SetObserving {
+                name: "Bob".to_string(),
+                observing: true,
+            },
+            UiEvent::SetObserving {
+                name: "Bob".to_string(),
+                observing: false,
+            },
+            UiEvent::SetSpectator("Bob".to_string()),
+            UiEvent::ClientInputActive {
+                name: "Bob".to_string(),
+                active: false,
+            },
+            UiEvent::ClientInputActive {
+                name: "Bob".to_string(),
+                active: true,
+            },
+            UiEvent::SetClientReady {
+                name: "Bob".to_string(),
+                ready: true,
+            },
+            UiEvent::SetClientReady {
+                name: "Bob".to_string(),
+                ready: false,
+            },
+            UiEvent::ClientSeat {
+                name: "Bob".to_string(),
+                seat: Some("Copilot".to_string()),
+            },
+            UiEvent::ClientSeat {
+                name: "Bob".to_string(),
+                seat: None,
+            },
+            UiEvent::ClockOffset {
+                name: "Bob".to_string(),
+                offset: -42.5,
+            },
+            UiEvent::SetInControl("Bob".to_string()),
+            UiEvent::ControlChanged {
+                new: "Bob".to_string(),
+                previous: Some("Alice".to_string()),
+            },
+            UiEvent::ControlChanged {
+                new: "Bob".to_string(),
+                previous: None,
+            },
+            UiEvent::ControlTransferFailed {
+                target: "Bob".to_string(),
+                reason: "they are observing".to_string(),
+            },
+            UiEvent::ControlCountdown {
+                seconds_remaining: 3,
+                next: "Bob".to_string(),
+            },
+            UiEvent::AddAircraft("C172.yaml".to_string()),
+            UiEvent::NoAircraftFound("definitions/aircraft/".to_string()),
+            UiEvent::Version("2.9.0".to_string()),
+            UiEvent::UpdateReady("2.9.0".to_string()),
+            UiEvent::SyncValues(vec![(
+                "PLANE ALTITUDE".to_string(),
+                "1500.0000".to_string(),
+            )]),
+            UiEvent::UpdateFailed,
+            UiEvent::UpdateFailedDetail {
+                reason: "HTTP request failed".to_string(),
+                retryable: true,
+            },
+            UiEvent::UpdateConfirm {
+                files: vec!["YourControls.zip".to_string()],
+                size: 2048,
+            },
+            UiEvent::SendConfig("{}".to_string()),
+            UiEvent::QueueDepth {
+                outbound: 3,
+                inbound: 7,
+            },
+            UiEvent::UpdateRate(30.0),
+            UiEvent::SmoothingChanged(120),
+            UiEvent::ChatMessage {
+                from: "Bob".to_string(),
+                text: "look left".to_string(),
+            },
+            UiEvent::DesyncVars {
+                client: "Bob".to_string(),
+                vars: vec!["A:FLAPS HANDLE INDEX".to_string()],
+            },
+        ];
+
+        backend.error("oops");
+        backend.attempt();
+        backend.connected();
+        backend.relay_fallback();
+        backend.server_fail("bind");
+        backend.server_bind_failed("0.0.0.0:7777", "port in use", Some(7778));
+        backend.client_fail("timeout");
+        backend.set_status(StatusState::Degraded);
+        backend.clear_status();
+        backend.sim_disconnected();
+        backend.sim_connected(true);
+        backend.sim_connected(false);
+        backend.set_detected_aircraft("Cessna Skyhawk G1000 Asobo");
+        backend.set_required_aircraft("Asobo - C172");
+        backend.unsupported_aircraft("Homebuilt Gyrocopter");
+        backend.aircraft_loaded("Custom - C172.yaml");
+        backend.set_capacity(3, 8);
+        backend.session_locked(true);
+        backend.set_server_capabilities(&["chat".to_string()]);
+        backend.sync_paused(true);
+        backend.sync_paused(false);
+        backend.syncing(true);
+        backend.syncing(false);
+        backend.handle_deeplink("yourcontrols://join?code=ABC123");
+        backend.set_prediction(true);
+        backend.scanning_aircraft(true);
+        backend.scanning_aircraft(false);
+        backend.set_sync_caveats(&["Autopilot not synced on this aircraft".to_string()]);
+        backend.config_errors(&["Port must be between 1 and 65535".to_string()]);
+        backend.quality_reduced(true, "high packet loss");
+        backend.set_self_name("Alice");
+        backend.public_ip_check(Some("1.2.3.4".to_string()), Some(true));
+        backend.upnp_result(true, Some(7777));
+        backend.test_connection_result(true, "1.2.3.4:7777 answered");
+        backend.set_relay_region("eu-west", Some(34.0));
+        backend.request_attention();
+        backend.gain_control();
+        backend.lose_control();
+        backend.note_activity();
+        backend.server_started();
+        backend.set_session_code("ABC123");
+        backend.set_host();
+        backend.set_public_endpoint("1.2.3.4:7777");
+        backend.nat_result(false, true);
+        backend.new_connection("Bob");
+        backend.new_connection_detailed("Bob", Some("C172"), None);
+        backend.client_changing_aircraft("Bob", "C172");
+        backend.set_client_position("Bob", 47.5, -122.25, 90.0);
+        backend.lost_connection("Bob");
+        backend.observing(true);
+        backend.observing(false);
+        backend.set_observing("Bob", true);
+        backend.set_observing("Bob", false);
+        backend.set_spectator("Bob");
+        backend.set_client_input_active("Bob", false);
+        backend.set_client_input_active("Bob", true);
+        backend.set_client_ready("Bob", true);
+        backend.set_client_ready("Bob", false);
+        backend.set_client_seat("Bob", Some("Copilot"));
+        backend.set_client_seat("Bob", None);
+        backend.set_clock_offset("Bob", -42.5);
+        backend.set_incontrol("Bob");
+        backend.control_changed("Bob", Some("Alice"));
+        backend.control_changed("Bob", None);
+        backend.control_transfer_failed("Bob", "they are observing");
+        backend.control_countdown(3, "Bob");
+        backend.add_aircraft("C172.yaml");
+        backend.no_aircraft_found("definitions/aircraft/");
+        backend.version("2.9.0");
+        backend.update_ready("2.9.0");
+        backend.set_sync_values(&[("PLANE ALTITUDE".to_string(), "1500.0000".to_string())]);
+        backend.update_failed();
+        backend.update_failed_detail("HTTP request failed", true);
+        backend.update_confirm(&["YourControls.zip".to_string()], 2048);
+        backend.send_config("{}");
+        backend.set_queue_depth(3, 7);
+        backend.set_update_rate(30.0);
+        backend.smoothing_changed(120);
+        backend.chat_message("Bob", "look left");
+        backend.set_desync_vars("Bob", &["A:FLAPS HANDLE INDEX".to_string()]);
+
+        let received: Vec<UiEvent> = event_rx.try_iter().collect();
+        assert_eq!(received, expected);
+    }
+
+    #[test]
+    fn test_ui_thread_exit_sets_exited() {
+        let exited = Arc::new(AtomicBool::new(false));
+        spawn_ui_thread(exited.clone(), || {}).join().unwrap();
+        assert!(exited.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_ui_thread_panic_sets_exited() {
+        let exited = Arc::new(AtomicBool::new(false));
+        let result = spawn_ui_thread(exited.clone(), || panic!("update panicked")).join();
+
+        assert!(result.is_err());
+        assert!(exited.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_map_invoke_metrics() {
+        let data = r#"{"sentPackets":10,"receivePackets":20,"sentBandwidth":1.5,"receiveBandwidth":2.5,"packetLoss":0.25,"ping":40.0}"#;
+
+        assert_eq!(
+            map_invoke("metrics", Some(data)),
+            Some(UiEvent::SendMetrics {
+                sent_packets: 10,
+                received_packets: 20,
+                sent_kbps: 1.5,
+                receive_kbps: 2.5,
+                packet_loss: 0.25,
+                ping: 40.0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_map_invoke_metrics_full() {
+        let data = r#"{"sentPackets":10,"receivePackets":20,"ackedPackets":18,"sentBandwidth":1.5,"receiveBandwidth":2.5,"packetLoss":0.25,"rtt":80.0}"#;
+
+        assert_eq!(
+            map_invoke("metrics_full", Some(data)),
+            Some(UiEvent::FullMetrics {
+                sent_packets: 10,
+                received_packets: 20,
+                acked_packets: 18,
+                sent_kbps: 1.5,
+                receive_kbps: 2.5,
+                packet_loss: 0.25,
+                rtt: 80.0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_map_invoke_metrics_invalid() {
+        assert_eq!(map_invoke("metrics", None), None);
+        assert_eq!(map_invoke("queue_depth", None), None);
+        assert_eq!(map_invoke("metrics", Some("not json")), None);
+    }
+
+    #[test]
+    fn test_map_invoke_defaults() {
+        assert_eq!(
+            map_invoke("error", None),
+            Some(UiEvent::Error("Unknown error".to_string()))
+        );
+        assert_eq!(
+            map_invoke("config_msg", None),
+            Some(UiEvent::SendConfig("{}".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_map_invoke_unknown() {
+        assert_eq!(map_invoke("not_a_real_event", Some("data")), None);
+    }
+
+    #[test]
+    fn test_format_bandwidth_kilobytes() {
+        assert_eq!(
+            format_bandwidth(125.0, BandwidthUnit::KilobytesPerSec),
+            "125.00 KB/s"
+        );
+    }
+
+    #[test]
+    fn test_format_bandwidth_megabits() {
+        // 125 KB/s = 1000 kbit/s = 1 Mbps
+        assert_eq!(
+            format_bandwidth(125.0, BandwidthUnit::Megabits),
+            "1.00 Mbps"
+        );
+        assert_eq!(format_bandwidth(0.0, BandwidthUnit::Megabits), "0.00 Mbps");
+        assert_eq!(
+            format_bandwidth(1250.0, BandwidthUnit::Megabits),
+            "10.00 Mbps"
+        );
+    }
+
+    #[test]
+    fn test_unique_client_name() {
+        let mut clients = Vec::new();
+        for _ in 0..3 {
+            let name = unique_client_name(&clients, "Bob");
+            clients.push(ClientInfo {
+                name,
+                ..Default::default()
+            });
+        }
+
+        let names: Vec<&str> = clients.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Bob", "Bob (2)", "Bob (3)"]);
+        assert_eq!(unique_client_name(&clients, "Alice"), "Alice");
+    }
+
+    #[test]
+    fn test_disconnected_channel_marks_exited() {
+        // The action sender is dropped by test_backend, as if the UI thread died
+        let (backend, _event_rx) = test_backend();
+        assert!(!backend.exited());
+
+        assert_eq!(
+            backend.get_next_message().err(),
+            Some(TryRecvError::Disconnected)
+        );
+        assert!(backend.exited());
+        assert!(!backend.is_alive());
+    }
+
+    #[test]
+    fn test_status_state_follows_events() {
+        let (mut app, _action_rx) = test_app();
+        assert_eq!(app.status_state, StatusState::Disconnected);
+
+        app.handle_event(UiEvent::Attempt);
+        assert_eq!(app.status_state, StatusState::Connecting);
+
+        app.handle_event(UiEvent::Connected);
+        assert_eq!(app.status_state, StatusState::Connected);
+
+        let metrics = |packet_loss| UiEvent::SendMetrics {
+            sent_packets: 0,
+            received_packets: 0,
+            sent_kbps: 0.0,
+            receive_kbps: 0.0,
+            packet_loss,
+            ping: 0.0,
+        };
+        app.handle_event(metrics(0.2));
+        assert_eq!(app.status_state, StatusState::Degraded);
+        app.handle_event(metrics(0.0));
+        assert_eq!(app.status_state, StatusState::Connected);
+
+        app.handle_event(UiEvent::ClientFail("timeout".to_string()));
+        assert_eq!(app.status_state, StatusState::Disconnected);
+    }
+
+    #[test]
+    fn test_autosave_debounce() {
+        let changed = Instant::now();
+
+        assert!(!autosave_due(true, changed, changed));
+        assert!(!autosave_due(
+            true,
+            changed,
+            changed + AUTOSAVE_DELAY - Duration::from_millis(1)
+        ));
+        assert!(autosave_due(true, changed, changed + AUTOSAVE_DELAY));
+        // Nothing to save
+        assert!(!autosave_due(false, changed, changed + AUTOSAVE_DELAY * 2));
+    }
+
+    #[test]
+    fn test_reconnect_resends_last_connect() {
+        let (mut app, action_rx) = test_app();
+
+        app.send_connect(AppMessage::StartServer {
+            username: "Bob".to_string(),
+            is_ipv6: false,
+            use_upnp: true,
+            port: 7777,
+            method: ConnectionMethod::Direct,
+            max_clients: None,
+            password: None,
+        });
+        action_rx.try_recv().unwrap();
+        app.handle_event(UiEvent::ServerFail("bind".to_string()));
+        assert!(app.connection_failed);
+
+        app.reconnect();
+        match action_rx.try_recv() {
+            Ok(AppMessage::StartServer { username, port, .. }) => {
+                assert_eq!(username, "Bob");
+                assert_eq!(port, 7777);
+            }
+            other => panic!("Unexpected message {:?}", other),
+        }
+        assert!(!app.connection_failed);
+
+        app.disconnect();
+        assert!(app.last_connect.is_none());
+    }
+
+    #[test]
+    fn test_clear_status() {
+        let (mut app, _action_rx) = test_app();
+
+        app.handle_event(UiEvent::Connected);
+        app.handle_event(UiEvent::SessionCode("ABC123".to_string()));
+        app.handle_event(UiEvent::ClearStatus);
+        assert_eq!(app.status_message, neutral_status(true));
+
+        app.is_connected = false;
+        app.handle_event(UiEvent::ClearStatus);
+        assert_eq!(app.status_message, neutral_status(false));
+    }
+
+    #[test]
+    fn test_move_selection() {
+        // Nothing to select
+        assert_eq!(move_selection(None, 0, true), None);
+        assert_eq!(move_selection(Some(2), 0, false), None);
+
+        // Entering the list from either end
+        assert_eq!(move_selection(None, 3, true), Some(0));
+        assert_eq!(move_selection(None, 3, false), Some(2));
+
+        // Stops at the ends
+        assert_eq!(move_selection(Some(0), 3, false), Some(0));
+        assert_eq!(move_selection(Some(2), 3, true), Some(2));
+        assert_eq!(move_selection(Some(1), 3, true), Some(2));
+
+        // A single client stays selected
+        assert_eq!(move_selection(Some(0), 1, true), Some(0));
+        assert_eq!(move_selection(Some(0), 1, false), Some(0));
+    }
+
+    #[test]
+    fn test_transfer_selected_needs_confirm() {
+        let (mut app, action_rx) = test_app();
+        app.handle_event(UiEvent::NewConnection("Bob".to_string()));
+        app.handle_event(UiEvent::NewConnection("Carol".to_string()));
+        app.selected_client = Some(1);
+
+        // Clients can't hand out control
+        app.request_transfer_selected();
+        assert_eq!(app.confirm_transfer, None);
+
+        app.is_host = true;
+        app.request_transfer_selected();
+        assert_eq!(app.confirm_transfer, Some("Carol".to_string()));
+        assert!(action_rx.try_recv().is_err());
+
+        // Selection follows the list shrinking
+        app.handle_event(UiEvent::LostConnection("Carol".to_string()));
+        assert_eq!(app.selected_client, Some(0));
+        assert_eq!(app.confirm_transfer, None);
+
+        app.handle_event(UiEvent::LostConnection("Bob".to_string()));
+        assert_eq!(app.selected_client, None);
+    }
+
+    #[test]
+    fn test_minimap_offsets() {
+        assert!(minimap_offsets(&[], 50.0).is_empty());
+        assert_eq!(
+            minimap_offsets(&[(10.0, 20.0)], 50.0),
+            vec![egui::Vec2::ZERO]
+        );
+
+        // North up, the widest span fills the radius
+        let offsets = minimap_offsets(&[(10.0, 20.0), (12.0, 20.0), (11.0, 21.0)], 50.0);
+        assert_eq!(
+            offsets,
+            vec![
+                egui::vec2(-25.0, 50.0),
+                egui::vec2(-25.0, -50.0),
+                egui::vec2(25.0, 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_client_changing_aircraft() {
+        let (mut app, _action_rx) = test_app();
+        app.handle_event(UiEvent::NewConnection("Bob".to_string()));
+        app.handle_event(UiEvent::NewConnection("Carol".to_string()));
+
+        app.handle_event(UiEvent::ClientChangingAircraft {
+            name: "Bob".to_string(),
+            to: "C172".to_string(),
+        });
+        app.handle_event(UiEvent::ClientChangingAircraft {
+            name: "Carol".to_string(),
+            to: "A320".to_string(),
+        });
+        assert!(app.clients[0].loading_aircraft.is_some());
+        assert!(app.toast.is_some());
+
+        // A roster update confirms the switch
+        app.handle_event(UiEvent::NewConnectionDetailed {
+            name: "Bob".to_string(),
+            aircraft: Some("C172".to_string()),
+            version: None,
+        });
+        assert!(app.clients[0].loading_aircraft.is_none());
+        assert_eq!(app.clients[0].aircraft.as_deref(), Some("C172"));
+
+        // Otherwise it's assumed done after a while
+        app.finish_aircraft_switches(Instant::now());
+        assert!(app.clients[1].loading_aircraft.is_some());
+        app.finish_aircraft_switches(Instant::now() + AIRCRAFT_SWITCH_TIMEOUT);
+        assert!(app.clients[1].loading_aircraft.is_none());
+        assert_eq!(app.clients[1].aircraft.as_deref(), Some("A320"));
+    }
+
+    #[test]
+    fn test_syncing_hides_when_done() {
+        let (mut app, _action_rx) = test_app();
+
+        app.handle_event(UiEvent::Syncing(true));
+        assert!(app.syncing);
+        app.handle_event(UiEvent::Syncing(false));
+        assert!(!app.syncing);
+    }
+
+    #[test]
+    fn test_profiles_fill_join_fields() {
+        let (mut app, action_rx) = test_app();
+
+        app.client_connection_method = ConnectionMethod::Direct;
+        app.ip_input = "1.2.3.4".to_string();
+        app.port = "7777".to_string();
+        app.profile_name_input = "Bob's server".to_string();
+        app.save_current_profile();
+        assert_eq!(app.selected_profile, Some(0));
+        assert!(matches!(
+            action_rx.try_recv(),
+            Ok(AppMessage::UpdateConfig { new_config }) if new_config.profiles.len() == 1
+        ));
+
+        app.client_connection_method = ConnectionMethod::CloudServer;
+        app.ip_input.clear();
+        app.port = "1".to_string();
+        app.apply_profile(0);
+        assert_eq!(app.client_connection_method, ConnectionMethod::Direct);
+        assert_eq!(app.ip_input, "1.2.3.4");
+        assert_eq!(app.port, "7777");
+
+        app.delete_selected_profile();
+        assert!(app.config.profiles.is_empty());
+        assert_eq!(app.selected_profile, None);
+    }
+
+    #[test]
+    fn test_colorblind_mode_avoids_red_green() {
+        let (good, bad) = status_colors(true);
+        for color in [good, bad] {
+            assert_ne!(color, egui::Color32::GREEN);
+            assert_ne!(color, egui::Color32::RED);
+        }
+        assert_ne!(good, bad);
+        assert_eq!(
+            status_colors(false),
+            (egui::Color32::GREEN, egui::Color32::RED)
+        );
+    }
+
+    #[test]
+    fn test_colorblind_mode_persists() {
+        let (mut app, _action_rx) = test_app();
+
+        app.load_config(Config {
+            ui_colorblind_mode: true,
+            ..Config::default()
+        });
+        assert!(app.colorblind_mode);
+        assert!(app.build_config().ui_colorblind_mode);
+    }
+
+    #[test]
+    fn test_idle_in_control_goes_observer() {
+        let (mut app, action_rx) = test_app();
+        let start = Instant::now();
+        app.afk_observer_mins = Some(5);
+        app.last_activity = start;
+
+        // Not in control, nothing to hand over
+        app.check_idle(start + Duration::from_secs(600));
+        assert_eq!(app.afk_prompt_since, None);
+
+        app.in_control = true;
+        app.check_idle(start + Duration::from_secs(299));
+        assert_eq!(app.afk_prompt_since, None);
+
+        let prompted = start + Duration::from_secs(300);
+        app.check_idle(prompted);
+        assert_eq!(app.afk_prompt_since, Some(prompted));
+        assert!(action_rx.try_recv().is_err());
+
+        app.check_idle(prompted + AFK_PROMPT_TIMEOUT);
+        assert_eq!(app.afk_prompt_since, None);
+        assert!(matches!(action_rx.try_recv(), Ok(AppMessage::GoObserver)));
+    }
+
+    #[test]
+    fn test_unsupported_aircraft_blocks_starting() {
+        let (mut app, _action_rx) = test_app();
+        app.handle_event(UiEvent::SimConnected(true));
+        assert_eq!(app.start_blocked_reason(), None);
+
+        app.handle_event(UiEvent::DetectedAircraft(
+            "Homebuilt Gyrocopter".to_string(),
+        ));
+        app.handle_event(UiEvent::UnsupportedAircraft(
+            "Homebuilt Gyrocopter".to_string(),
+        ));
+        assert!(app
+            .start_blocked_reason()
+            .unwrap()
+            .contains("Homebuilt Gyrocopter"));
+
+        // Loading a different definition clears it
+        app.aircraft_list = vec!["Asobo Studio - Cessna 152.yaml".to_string()];
+        app.select_aircraft(0);
+        assert_eq!(app.start_blocked_reason(), None);
+
+        // So does one loaded by path, once the app confirms it
+        app.handle_event(UiEvent::UnsupportedAircraft(
+            "Homebuilt Gyrocopter".to_string(),
+        ));
+        app.handle_event(UiEvent::AircraftLoaded(
+            "Custom - Gyrocopter.yaml".to_string(),
+        ));
+        assert_eq!(app.start_blocked_reason(), None);
+        assert_eq!(
+            app.loaded_aircraft.as_deref(),
+            Some("Custom - Gyrocopter.yaml")
+        );
+
+        // Ending a session clears it too
+        for fail in [
+            UiEvent::ClientFail("closed".to_string()),
+            UiEvent::ServerFail("closed".to_string()),
+        ] {
+            app.handle_event(UiEvent::UnsupportedAircraft(
+                "Homebuilt Gyrocopter".to_string(),
+            ));
+            app.handle_event(fail);
+            assert_eq!(app.start_blocked_reason(), None);
+        }
+    }
+
+    #[test]
+    fn test_session_tab_shown_once_connected() {
+        let (mut app, _action_rx) = test_app();
+        assert_eq!(app.current_tab, Tab::Connect);
+
+        app.handle_event(UiEvent::ServerStarted);
+        assert_eq!(app.current_tab, Tab::Session);
+
+        app.current_tab = Tab::Connect;
+        app.handle_event(UiEvent::Connected);
+        assert_eq!(app.current_tab, Tab::Session);
+    }
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("2.9.0", "2.8.5"));
+        assert!(is_newer("v2.10.0", "2.9.0"));
+        assert!(!is_newer("2.9.0", "2.9.0"));
+        assert!(!is_newer("2.8.5", "2.9.0"));
+        // Pre-releases come before the release
+        assert!(!is_newer("2.9.0-beta.1", "2.9.0"));
+        assert!(!is_newer("unknown", "2.9.0"));
+    }
+
+    #[test]
+    fn test_newer_peer_version() {
+        let (backend, event_rx) = test_backend();
+        let (mut app, _action_rx) = test_app();
+        let join = |app: &mut YourControlsApp, name: &str, version: Option<&str>| {
+            backend.new_connection_detailed(name, None, version);
+            app.handle_event(event_rx.try_recv().unwrap());
+        };
+
+        join(&mut app, "Alice", Some(LOCAL_VERSION));
+        join(&mut app, "Bob", None);
+        assert_eq!(app.newer_peer_version(), None);
+
+        join(&mut app, "Carol", Some("998.0.0"));
+        join(&mut app, "Dave", Some("999.1.0"));
+        join(&mut app, "Eve", Some("999.0.0"));
+        assert_eq!(app.clients.len(), 5);
+        assert_eq!(app.newer_peer_version(), Some("999.1.0"));
+    }
+
+    #[test]
+    fn test_filter_sync_values() {
+        let values = vec![
+            ("GEAR HANDLE POSITION".to_string(), "true".to_string()),
+            ("L:XMLVAR_Baro1".to_string(), "1.0000".to_string()),
+            ("PLANE ALTITUDE".to_string(), "1500.0000".to_string()),
+        ];
+
+        assert_eq!(filter_sync_values(&values, "").count(), 3);
+        let matched: Vec<_> = filter_sync_values(&values, " baro ").collect();
+        assert_eq!(matched, vec![&values[1]]);
+        assert_eq!(filter_sync_values(&values, "flaps").count(), 0);
+    }
+
+    #[test]
+    fn test_metrics_ignored_while_disconnected() {
+        let (mut app, _action_rx) = test_app();
+        let metrics = || UiEvent::SendMetrics {
+            sent_packets: 10,
+            received_packets: 10,
+            sent_kbps: 12.0,
+            receive_kbps: 34.0,
+            packet_loss: 0.5,
+            ping: 80.0,
+        };
+
+        app.handle_event(UiEvent::ServerStarted);
+        app.handle_event(metrics());
+        assert_eq!(app.ping, 80.0);
+
+        app.handle_event(UiEvent::ServerFail("closed".to_string()));
+        assert_eq!(app.ping, 0.0);
+
+        // Arrives after the connection closed
+        app.handle_event(metrics());
+        app.handle_event(UiEvent::ServerStarted);
+        assert_eq!(app.ping, 0.0);
+        assert_eq!(app.download_bandwidth, 0.0);
+        assert_eq!(app.packet_loss, 0.0);
+        assert_eq!(app.status_state, StatusState::Connected);
+    }
+
+    #[test]
+    fn test_onboarding_dismissal_is_saved() {
+        let (mut app, action_rx) = test_app();
+        assert!(!app.first_run);
+
+        app.load_config(Config::default());
+        assert!(app.first_run);
+
+        app.dismiss_onboarding();
+        assert!(!app.first_run);
+        match action_rx.try_recv() {
+            Ok(AppMessage::UpdateConfig { new_config }) => assert!(!new_config.first_run),
+            _ => panic!("expected the config to be saved"),
+        }
+    }
+
+    #[test]
+    fn test_set_spectator() {
+        let (mut app, _action_rx) = test_app();
+
+        app.handle_event(UiEvent::NewConnection("Bob".to_string()));
+        app.handle_event(UiEvent::SetSpectator("Bob".to_string()));
+        assert!(app.clients[0].is_spectator);
+        assert!(app.clients[0].is_observer);
+
+        // Unknown names are ignored
+        app.handle_event(UiEvent::SetSpectator("Alice".to_string()));
+        assert_eq!(app.clients.len(), 1);
+    }
+
+    #[test]
+    fn test_server_bind_failed_suggests_port() {
+        let (mut app, _action_rx) = test_app();
+        app.port = "7777".to_string();
+
+        app.handle_event(UiEvent::ServerBindFailed {
+            addr: "0.0.0.0:7777".to_string(),
+            reason: "port in use".to_string(),
+            suggested_port: Some(7778),
+        });
+        assert!(app.connection_failed);
+        assert_eq!(app.port, "7778");
+        assert_eq!(
+            app.status_message,
+            "Couldn't bind 0.0.0.0:7777 — port in use. Try port 7778 instead."
+        );
+
+        app.handle_event(UiEvent::ServerBindFailed {
+            addr: "0.0.0.0:7778".to_string(),
+            reason: "port in use".to_string(),
+            suggested_port: None,
+        });
+        assert_eq!(app.port, "7778");
+        assert!(app.status_message.ends_with("Try another port."));
+    }
+
+    #[test]
+    fn test_session_summary() {
+        let (mut app, _action_rx) = test_app();
+        let metrics = |ping| UiEvent::SendMetrics {
+            sent_packets: 0,
+            received_packets: 0,
+            sent_kbps: 0.0,
+            receive_kbps: 0.0,
+            packet_loss: 0.0,
+            ping,
+        };
+
+        app.handle_event(UiEvent::ServerStarted);
+        app.handle_event(UiEvent::NewConnection("Alice".to_string()));
+        app.handle_event(UiEvent::NewConnection("Bob".to_string()));
+        app.handle_event(UiEvent::LostConnection("Bob".to_string()));
+        app.handle_event(metrics(40.0));
+        app.handle_event(metrics(60.0));
+        app.session_stats.sent_kb = 12.5;
+
+        let summary = app.session_summary();
+        assert_eq!(summary.peak_clients, 2);
+        assert_eq!(summary.sent_kb, 12.5);
+        assert_eq!(summary.average_ping, Some(50.0));
+
+        app.handle_event(UiEvent::ServerFail("closed".to_string()));
+        let recap = app.last_session.clone().unwrap();
+        assert_eq!(recap.peak_clients, 2);
+        assert_eq!(recap.average_ping, Some(50.0));
+        assert_eq!(app.session_summary().average_ping, None);
+
+        // Failing to start isn't a session worth recapping
+        app.last_session = None;
+        app.handle_event(UiEvent::ServerFail("bind".to_string()));
+        assert!(app.last_session.is_none());
+    }
+
+    #[test]
+    fn test_format_session_duration() {
+        assert_eq!(format_session_duration(Duration::from_secs(0)), "0:00:00");
+        assert_eq!(
+            format_session_duration(Duration::from_secs(3725)),
+            "1:02:05"
+        );
+    }
+
+    #[test]
+    fn test_sound_events_persist() {
+        let (mut app, _action_rx) = test_app();
+
+        app.load_config(Config::default());
+        assert!(app.sound_events.contains(&SoundEvent::Disconnected));
+        assert!(!app.sound_events.contains(&SoundEvent::NewConnection));
+
+        app.sound_events.insert(SoundEvent::NewConnection);
+        app.sound_events.remove(&SoundEvent::Disconnected);
+        let config = app.build_config();
+        assert!(config.sound_events.contains(&SoundEvent::NewConnection));
+        assert!(!config.sound_events.contains(&SoundEvent::Disconnected));
+    }
+
+    #[test]
+    fn test_relay_fallback_session_id() {
+        let (mut app, _action_rx) = test_app();
+        app.client_connection_method = ConnectionMethod::Direct;
+        assert_eq!(app.relay_fallback_session_id(), None);
+
+        app.session_code = " ABC123 ".to_string();
+        assert_eq!(app.relay_fallback_session_id(), Some("ABC123".to_string()));
+
+        app.auto_fallback_relay = false;
+        assert_eq!(app.relay_fallback_session_id(), None);
+
+        // Cloud joins already use the session code directly
+        app.auto_fallback_relay = true;
+        app.client_connection_method = ConnectionMethod::CloudServer;
+        assert_eq!(app.relay_fallback_session_id(), None);
+
+        app.handle_event(UiEvent::RelayFallback);
+        assert_eq!(app.status_state, StatusState::Connecting);
+        assert_eq!(app.status_message, "Direct failed, trying relay...");
+    }
+
+    #[test]
+    fn test_set_target_update_rate() {
+        let (mut app, action_rx) = test_app();
+
+        app.set_target_update_rate(30);
+        assert_eq!(app.target_update_rate, 30);
+        assert!(matches!(
+            action_rx.try_recv(),
+            Ok(AppMessage::SetUpdateRate { hz: 30 })
+        ));
+
+        app.set_target_update_rate(1000);
+        assert!(matches!(
+            action_rx.try_recv(),
+            Ok(AppMessage::SetUpdateRate {
+                hz: MAX_UPDATE_RATE
+            })
+        ));
+
+        // Unchanged values aren't resent
+        app.set_target_update_rate(MAX_UPDATE_RATE);
+        assert!(action_rx.try_recv().is_err());
+        assert_eq!(app.build_config().update_rate_hz, MAX_UPDATE_RATE);
+    }
+
+    #[test]
+    fn test_invite_text() {
+        let (mut app, _action_rx) = test_app();
+        app.server_connection_method = ConnectionMethod::CloudServer;
+
+        app.handle_event(UiEvent::SessionCode("ABC123".to_string()));
+        assert_eq!(app.invite_text(), None);
+
+        app.handle_event(UiEvent::ServerStarted);
+        assert_eq!(
+            app.invite_text().as_deref(),
+            Some("Join my YourControls session! Code: ABC123 (Cloud)")
+        );
+
+        app.streamer_mode = true;
+        assert_eq!(
+            app.invite_text().as_deref(),
+            Some("Join my YourControls session! DM me for the code (Cloud)")
+        );
+
+        app.streamer_mode = false;
+        app.server_connection_method = ConnectionMethod::Direct;
+        assert_eq!(app.invite_text(), None);
+        app.handle_event(UiEvent::PublicEndpoint("1.2.3.4:7777".to_string()));
+        assert_eq!(
+            app.invite_text().as_deref(),
+            Some("Join my YourControls session! Address: 1.2.3.4:7777 (Direct)")
+        );
+
+        app.handle_event(UiEvent::ServerFail("closed".to_string()));
+        assert_eq!(app.invite_text(), None);
+    }
+
+    #[test]
+    fn test_different_aircraft() {
+        let (mut app, _action_rx) = test_app();
+        let client = |aircraft: Option<&str>| ClientInfo {
+            name: "Bob".to_string(),
+            aircraft: aircraft.map(String::from),
+            ..ClientInfo::default()
+        };
+
+        // Nothing to compare against yet
+        assert_eq!(app.different_aircraft(&client(Some("Asobo - A320"))), None);
+
+        app.loaded_aircraft = Some("Asobo - C172.yaml".to_string());
+        assert_eq!(app.different_aircraft(&client(None)), None);
+        assert_eq!(app.different_aircraft(&client(Some("Asobo - C172"))), None);
+        assert_eq!(
+            app.different_aircraft(&client(Some("Asobo - A320"))),
+            Some("Asobo - A320")
+        );
+    }
+
+    #[test]
+    fn test_joiner_on_different_aircraft_is_flagged() {
+        let (backend, event_rx) = test_backend();
+        let (mut app, _action_rx) = test_app();
+        app.loaded_aircraft = Some("Asobo - C172.yaml".to_string());
+
+        backend.new_connection_detailed("Alice", Some("Asobo - C172"), Some(LOCAL_VERSION));
+        backend.new_connection_detailed("Bob", Some("Asobo - A320"), Some(LOCAL_VERSION));
+        while let Ok(event) = event_rx.try_recv() {
+            app.handle_event(event);
+        }
+
+        assert_eq!(app.different_aircraft(&app.clients[0]), None);
+        assert_eq!(
+            app.different_aircraft(&app.clients[1]),
+            Some("Asobo - A320")
+        );
+    }
+
+    #[test]
+    fn test_control_changed_updates_roster() {
+        let (mut app, _action_rx) = test_app();
+
+        app.handle_event(UiEvent::NewConnection("Alice".to_string()));
+        app.handle_event(UiEvent::NewConnection("Bob".to_string()));
+        app.handle_event(UiEvent::SetInControl("Alice".to_string()));
+        app.handle_event(UiEvent::ControlChanged {
+            new: "Bob".to_string(),
+            previous: Some("Alice".to_string()),
+        });
+
+        assert!(!app.clients[0].has_control);
+        assert!(app.clients[1].has_control);
+    }
+
+    #[test]
+    fn test_aircraft_switch_confirmed_while_connected() {
+        let (mut app, action_rx) = test_app();
+        app.aircraft_list = vec!["C152.yaml".to_string(), "C172.yaml".to_string()];
+
+        // Not connected, loads straight away
+        app.request_select_aircraft(1);
+        assert!(matches!(
+            action_rx.try_recv(),
+            Ok(AppMessage::LoadAircraft { .. })
+        ));
+        assert_eq!(app.pending_aircraft, None);
+
+        app.handle_event(UiEvent::Connected);
+        app.request_select_aircraft(0);
+        assert_eq!(app.pending_aircraft.as_deref(), Some("C152.yaml"));
+        assert!(action_rx.try_recv().is_err());
+        assert_eq!(app.selected_aircraft, 1);
+
+        app.confirm_pending_aircraft();
+        assert_eq!(app.pending_aircraft, None);
+        assert_eq!(app.selected_aircraft, 0);
+        match action_rx.try_recv() {
+            Ok(AppMessage::LoadAircraft { config_file_name }) => {
+                assert_eq!(config_file_name, "C152.yaml")
+            }
+            other => panic!("Unexpected message {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_reachability_verdict() {
+        assert_eq!(
+            reachability_verdict("7777", Some(true)),
+            ("Port 7777 appears open".to_string(), Some(true))
+        );
+        assert_eq!(reachability_verdict("7777", Some(false)).1, Some(false));
+        assert_eq!(reachability_verdict("7777", None).1, None);
+    }
+
+    #[test]
+    fn test_tray_actions() {
+        let (mut app, action_rx) = test_app();
+        assert!(!app.should_hide_on_close());
+
+        app.minimize_to_tray = true;
+        app.hidden_to_tray = true;
+        assert!(app.should_hide_on_close());
+
+        let commands = app.handle_tray_action(TrayAction::Show);
+        assert!(!app.hidden_to_tray);
+        assert!(matches!(commands[0], egui::ViewportCommand::Visible(true)));
+
+        assert!(app.handle_tray_action(TrayAction::Disconnect).is_empty());
+        assert!(matches!(action_rx.try_recv(), Ok(AppMessage::Disconnect)));
+
+        // Quitting lets the close go through
+        let commands = app.handle_tray_action(TrayAction::Quit);
+        assert!(matches!(commands[0], egui::ViewportCommand::Close));
+        assert!(!app.should_hide_on_close());
+    }
+
+    #[test]
+    fn test_force_resync() {
+        let (mut app, action_rx) = test_app();
+        assert!(!app.can_request_resync());
+
+        app.is_connected = true;
+        assert!(app.can_request_resync());
+        app.request_resync();
+        assert!(matches!(
+            action_rx.try_recv(),
+            Ok(AppMessage::RequestResync)
+        ));
+
+        // Already resyncing
+        app.handle_event(UiEvent::Syncing(true));
+        assert!(!app.can_request_resync());
+        app.handle_event(UiEvent::Syncing(false));
+        assert!(app.can_request_resync());
+
+        app.in_control = true;
+        assert!(!app.can_request_resync());
+    }
+
+    #[test]
+    fn test_invalid_timeout_blocks_connecting() {
+        let (mut app, _action_rx) = test_app();
+        app.handle_event(UiEvent::SimConnected(true));
+        app.connection_timeout = 45;
+        assert_eq!(app.start_blocked_reason(), None);
+        assert_eq!(app.build_config().conn_timeout, 45);
+
+        // Out of range values are flagged rather than quietly replaced
+        app.connection_timeout = 0;
+        assert!(app.start_blocked_reason().unwrap().contains("timeout"));
+        assert_eq!(app.build_config().conn_timeout, app.config.conn_timeout);
+
+        app.connection_timeout = 601;
+        assert!(app.start_blocked_reason().is_some());
+    }
+
+    #[test]
+    fn test_server_capabilities() {
+        use yourcontrols_net::CAPABILITY_SPECTATORS;
+
+        let (mut app, _action_rx) = test_app();
+        assert!(app.server_supports(CAPABILITY_CHAT));
+
+        app.handle_event(UiEvent::ServerCapabilities(vec![
+            CAPABILITY_CHAT.to_string(),
+            CAPABILITY_SPECTATORS.to_string(),
+        ]));
+        assert!(app.server_supports(CAPABILITY_CHAT));
+        assert!(!app.server_supports(CAPABILITY_SESSION_LOCK));
+
+        // Forgotten with the connection
+        app.handle_event(UiEvent::ClientFail("timeout".to_string()));
+        assert!(app.server_supports(CAPABILITY_SESSION_LOCK));
+    }
+
+    #[test]
+    fn test_status_summary() {
+        assert_eq!(status_summary("Connected"), ("Connected", false));
+        assert_eq!(status_summary("Connected\n"), ("Connected", false));
+        assert_eq!(status_summary(""), ("", false));
+        assert_eq!(
+            status_summary("Config rejected:\nPort must be between 1 and 65535"),
+            ("Config rejected:", true)
+        );
+    }
+
+    #[test]
+    fn test_connection_test_uses_join_fields() {
+        let (mut app, action_rx) = test_app();
+
+        app.client_connection_method = ConnectionMethod::Direct;
+        app.ip_input = "1.2.3.4".to_string();
+        app.port = "7777".to_string();
+        app.request_connection_test();
+        assert!(matches!(app.connection_test, Some(None)));
+        match action_rx.try_recv() {
+            Ok(AppMessage::TestConnection { ip, port, .. }) => {
+                assert_eq!(ip, "1.2.3.4".parse().ok());
+                assert_eq!(port, Some(7777));
             }
+            _ => panic!("expected TestConnection"),
         }
+
+        app.handle_event(UiEvent::TestConnectionResult {
+            success: false,
+            detail: "Couldn't reach 1.2.3.4:7777".to_string(),
+        });
+        assert_eq!(
+            app.connection_test,
+            Some(Some((false, "Couldn't reach 1.2.3.4:7777".to_string())))
+        );
     }
-}
 
-impl eframe::App for YourControlsApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Process events from backend
-        self.process_events();
+    #[test]
+    fn test_sync_caveats_cleared_on_aircraft_change() {
+        let (mut app, _action_rx) = test_app();
+        app.handle_event(UiEvent::SyncCaveats(vec![
+            "Autopilot not synced on this aircraft".to_string(),
+        ]));
+        assert_eq!(app.sync_caveats.len(), 1);
 
-        // Request repaint for real-time updates
-        ctx.request_repaint();
+        app.aircraft_list = vec!["Asobo Studio - Cessna 152.yaml".to_string()];
+        app.select_aircraft(0);
+        assert!(app.sync_caveats.is_empty());
+    }
 
-        // Apply theme
-        if self.dark_theme {
-            ctx.set_visuals(egui::Visuals::dark());
-        } else {
-            ctx.set_visuals(egui::Visuals::light());
+    #[test]
+    fn test_roster_counts() {
+        let (mut app, _action_rx) = test_app();
+        assert_eq!(roster_counts(&app.clients), (0, 0));
+
+        for name in ["Alice", "Bob", "Carol"] {
+            app.handle_event(UiEvent::NewConnection(name.to_string()));
         }
+        app.handle_event(UiEvent::SetObserving {
+            name: "Bob".to_string(),
+            observing: true,
+        });
+        app.handle_event(UiEvent::SetSpectator("Carol".to_string()));
+        assert_eq!(roster_counts(&app.clients), (1, 2));
 
-        egui::CentralPanel::default().show(ctx, |ui| {
-            // Status bar
-            ui.horizontal(|ui| {
-                let (color, icon) = if self.is_connected {
-                    (egui::Color32::GREEN, "●")
-                } else {
-                    (egui::Color32::RED, "●")
-                };
-                ui.colored_label(color, icon);
-                ui.label(&self.status_message);
-            });
+        app.handle_event(UiEvent::SetObserving {
+            name: "Bob".to_string(),
+            observing: false,
+        });
+        assert_eq!(roster_counts(&app.clients), (2, 1));
+    }
 
-            ui.separator();
+    #[test]
+    fn test_password_value() {
+        assert_eq!(password_value(""), None);
+        assert_eq!(password_value("   "), None);
+        assert_eq!(password_value(" hunter2 "), Some("hunter2".to_string()));
+    }
 
-            // Main content - two columns
-            ui.columns(2, |columns| {
-                // LEFT COLUMN: Server
-                columns[0].group(|ui| {
-                    ui.heading("🖥 Host");
-                    ui.add_space(5.0);
+    #[test]
+    fn test_auto_copy_session_code() {
+        let (mut app, _action_rx) = test_app();
+        app.handle_event(UiEvent::SessionCode("ABC123".to_string()));
+        assert_eq!(app.pending_copy, None);
 
-                    ui.horizontal(|ui| {
-                        ui.label("Port:");
-                        ui.text_edit_singleline(&mut self.port);
-                    });
+        app.auto_copy_code = true;
+        app.handle_event(UiEvent::SessionCode("ABC123".to_string()));
+        assert_eq!(app.pending_copy.as_deref(), Some("ABC123"));
+        assert!(app.toast.is_some());
 
-                    ui.horizontal(|ui| {
-                        ui.radio_value(
-                            &mut self.server_connection_method,
-                            ConnectionMethod::CloudServer,
-                            "Cloud P2P",
-                        );
-                        ui.radio_value(
-                            &mut self.server_connection_method,
-                            ConnectionMethod::Relay,
-                            "Cloud Host",
-                        );
-                        ui.radio_value(
-                            &mut self.server_connection_method,
-                            ConnectionMethod::Direct,
-                            "Direct",
-                        );
-                    });
+        app.pending_copy = None;
+        app.streamer_mode = true;
+        app.handle_event(UiEvent::SessionCode("DEF456".to_string()));
+        assert_eq!(app.pending_copy, None);
+    }
 
-                    ui.checkbox(&mut self.is_ipv6, "Use IPv6");
+    #[test]
+    fn test_client_input_active() {
+        let (mut app, _action_rx) = test_app();
+        app.handle_event(UiEvent::NewConnection("Bob".to_string()));
+        assert!(app.clients[0].input_active);
 
-                    if ui
-                        .button(if self.is_connected {
-                            "Stop Server"
-                        } else {
-                            "Start Server"
-                        })
-                        .clicked()
-                    {
-                        if self.is_connected {
-                            self.action_tx.send(AppMessage::Disconnect).ok();
-                        } else {
-                            self.action_tx
-                                .send(AppMessage::StartServer {
-                                    username: self.username.clone(),
-                                    port: self.port.parse().unwrap_or(7777),
-                                    is_ipv6: self.is_ipv6,
-                                    use_upnp: true,
-                                    method: self.server_connection_method,
-                                })
-                                .ok();
-                        }
-                    }
-                });
+        app.handle_event(UiEvent::ClientInputActive {
+            name: "Bob".to_string(),
+            active: false,
+        });
+        assert!(!app.clients[0].input_active);
 
-                // RIGHT COLUMN: Client
-                columns[1].group(|ui| {
-                    ui.heading("🔌 Join");
-                    ui.add_space(5.0);
+        // Unknown names are ignored
+        app.handle_event(UiEvent::ClientInputActive {
+            name: "Alice".to_string(),
+            active: true,
+        });
+        assert!(!app.clients[0].input_active);
 
-                    // Connection method radio buttons
-                    ui.horizontal(|ui| {
-                        ui.radio_value(
-                            &mut self.client_connection_method,
-                            ConnectionMethod::CloudServer,
-                            "Cloud Server",
-                        );
-                        ui.radio_value(
-                            &mut self.client_connection_method,
-                            ConnectionMethod::Direct,
-                            "Direct",
-                        );
-                    });
+        app.handle_event(UiEvent::ClientInputActive {
+            name: "Bob".to_string(),
+            active: true,
+        });
+        assert!(app.clients[0].input_active);
+    }
 
-                    ui.add_space(5.0);
+    #[test]
+    fn test_inactive_column() {
+        let (mut app, _action_rx) = test_app();
+        assert_eq!(app.inactive_column(), None);
 
-                    // Show different fields based on connection method
-                    if self.client_connection_method == ConnectionMethod::Direct {
-                        // Direct connection: IP + Port
-                        ui.horizontal(|ui| {
-                            ui.label("IP Address:");
-                            ui.text_edit_singleline(&mut self.ip_input);
-                        });
-                        ui.horizontal(|ui| {
-                            ui.label("Port:");
-                            ui.text_edit_singleline(&mut self.port);
-                        });
-                    } else {
-                        // Cloud connection: Session Code
-                        ui.horizontal(|ui| {
-                            ui.label("Session Code:");
-                            ui.text_edit_singleline(&mut self.session_code);
-                        });
-                    }
+        app.handle_event(UiEvent::ServerStarted);
+        assert_eq!(app.inactive_column(), Some(ConnectColumn::Join));
 
-                    ui.checkbox(&mut self.is_ipv6, "Use IPv6");
+        app.show_inactive_column = true;
+        app.handle_event(UiEvent::ServerFail("Stopped.".to_string()));
+        assert_eq!(app.inactive_column(), None);
 
-                    if ui
-                        .button(if self.is_connected {
-                            "Disconnect"
-                        } else {
-                            "Connect"
-                        })
-                        .clicked()
-                    {
-                        if self.is_connected {
-                            self.action_tx.send(AppMessage::Disconnect).ok();
-                        } else {
-                            let (session_id, ip, port) =
-                                if self.client_connection_method == ConnectionMethod::Direct {
-                                    // Direct: use IP and port
-                                    let parsed_ip = self.ip_input.parse().ok();
-                                    let parsed_port = self.port.parse().ok();
-                                    (None, parsed_ip, parsed_port)
-                                } else {
-                                    // Cloud: use session code
-                                    (Some(self.session_code.clone()), None, None)
-                                };
+        // The override doesn't carry over to the next session
+        app.handle_event(UiEvent::Connected);
+        assert_eq!(app.inactive_column(), Some(ConnectColumn::Host));
+        assert!(!app.show_inactive_column);
+    }
 
-                            self.action_tx
-                                .send(AppMessage::Connect {
-                                    username: self.username.clone(),
-                                    session_id,
-                                    isipv6: self.is_ipv6,
-                                    ip,
-                                    hostname: None,
-                                    port,
-                                    method: self.client_connection_method,
-                                })
-                                .ok();
-                        }
-                    }
-                });
-            });
+    #[test]
+    fn test_quality_score() {
+        assert_eq!(quality_score(40.0, 0.0, 20.0), Quality::Good);
+        assert_eq!(quality_score(100.0, 0.01, 500.0), Quality::Good);
 
-            ui.separator();
+        assert_eq!(quality_score(101.0, 0.0, 20.0), Quality::Fair);
+        assert_eq!(quality_score(40.0, 0.02, 20.0), Quality::Fair);
+        assert_eq!(quality_score(40.0, 0.0, 600.0), Quality::Fair);
+        assert_eq!(quality_score(250.0, 0.05, 20.0), Quality::Fair);
 
-            // Bottom section - two columns
-            ui.columns(2, |columns| {
-                // LEFT: Client list
-                columns[0].group(|ui| {
-                    ui.heading("👥 Connected Clients");
-                    egui::ScrollArea::vertical()
-                        .max_height(200.0)
-                        .show(ui, |ui| {
-                            for client in &self.clients {
-                                ui.horizontal(|ui| {
-                                    let icon = if client.has_control {
-                                        "✓"
-                                    } else if client.is_observer {
-                                        "👁"
-                                    } else {
-                                        "○"
-                                    };
-                                    ui.label(format!("{} {}", icon, client.name));
-
-                                    if !client.has_control
-                                        && ui.small_button("Give Control").clicked()
-                                    {
-                                        self.action_tx
-                                            .send(AppMessage::TransferControl {
-                                                target: client.name.clone(),
-                                            })
-                                            .ok();
-                                    }
-                                });
-                            }
-                        });
-                });
+        assert_eq!(quality_score(251.0, 0.0, 20.0), Quality::Poor);
+        assert_eq!(quality_score(40.0, 0.06, 20.0), Quality::Poor);
+    }
 
-                // RIGHT: Settings
-                columns[1].group(|ui| {
-                    ui.heading("⚙ Settings");
+    #[test]
+    fn test_send_quick_message() {
+        let (mut app, action_rx) = test_app();
+        app.load_config(Config::default());
+        assert_eq!(app.quick_messages.len(), QUICK_MESSAGE_KEYS.len());
 
-                    ui.horizontal(|ui| {
-                        ui.label("Username:");
-                        ui.text_edit_singleline(&mut self.username);
-                    });
+        // Nobody to send it to
+        assert!(!app.send_quick_message(0));
 
-                    ui.horizontal(|ui| {
-                        ui.label("Aircraft:");
-                        egui::ComboBox::new("aircraft", "")
-                            .selected_text(&self.aircraft_list[self.selected_aircraft])
-                            .show_ui(ui, |ui| {
-                                for (i, aircraft) in self.aircraft_list.iter().enumerate() {
-                                    if ui
-                                        .selectable_value(&mut self.selected_aircraft, i, aircraft)
-                                        .clicked()
-                                    {
-                                        self.action_tx
-                                            .send(AppMessage::LoadAircraft {
-                                                config_file_name: aircraft.clone(),
-                                            })
-                                            .ok();
-                                    }
-                                }
-                            });
-                    });
+        app.is_connected = true;
+        app.quick_messages[1] = "  Taking off  ".to_string();
+        app.quick_messages[2].clear();
+        assert!(app.send_quick_message(1));
+        match action_rx.try_recv() {
+            Ok(AppMessage::BroadcastMessage { text }) => assert_eq!(text, "Taking off"),
+            other => panic!("Unexpected message {:?}", other),
+        }
 
-                    ui.checkbox(&mut self.instructor_mode, "Instructor Mode");
-                    ui.checkbox(&mut self.streamer_mode, "Streamer Mode");
-                    ui.checkbox(&mut self.sound_muted, "Mute Sound");
-                    ui.checkbox(&mut self.dark_theme, "Dark Theme");
+        assert!(!app.send_quick_message(2));
+        assert!(!app.send_quick_message(QUICK_MESSAGE_KEYS.len()));
+        assert!(action_rx.try_recv().is_err());
+    }
 
-                    if ui.button("💾 Save Settings").clicked() {
-                        // Save settings logic here
-                    }
-                });
-            });
+    #[test]
+    fn test_no_aircraft_found() {
+        let (mut app, _action_rx) = test_app();
 
-            // Network stats (if connected)
-            if self.is_connected {
-                ui.separator();
-                ui.horizontal(|ui| {
-                    ui.label(format!("↓ {:.2} KB/s", self.download_bandwidth));
-                    ui.separator();
-                    ui.label(format!("↑ {:.2} KB/s", self.upload_bandwidth));
-                    ui.separator();
-                    ui.label(format!("Loss: {:.1}%", self.packet_loss * 100.0));
-                    ui.separator();
-                    ui.label(format!("Ping: {:.0}ms", self.ping));
-                });
+        app.handle_event(UiEvent::ScanningAircraft(true));
+        app.handle_event(UiEvent::NoAircraftFound(
+            "definitions/aircraft/".to_string(),
+        ));
+        app.handle_event(UiEvent::ScanningAircraft(false));
+        assert_eq!(
+            app.no_aircraft_path.as_deref(),
+            Some("definitions/aircraft/")
+        );
+
+        // A rescan that finds something clears it
+        app.handle_event(UiEvent::AddAircraft("C172.yaml".to_string()));
+        assert_eq!(app.no_aircraft_path, None);
+        assert_eq!(app.aircraft_list, vec!["C172.yaml".to_string()]);
+    }
+
+    #[test]
+    fn test_set_smoothing() {
+        let (mut app, action_rx) = test_app();
+
+        app.set_smoothing(1000);
+        assert!(matches!(
+            action_rx.try_recv(),
+            Ok(AppMessage::SetSmoothing {
+                ms: MAX_SMOOTHING_MS
+            })
+        ));
+
+        // Unchanged values aren't resent
+        app.set_smoothing(MAX_SMOOTHING_MS);
+        assert!(action_rx.try_recv().is_err());
+
+        app.handle_event(UiEvent::SmoothingChanged(120));
+        assert_eq!(app.smoothing_ms, 120);
+        assert_eq!(app.build_config().smoothing_ms, 120);
+    }
+
+    #[test]
+    fn test_rolling_average() {
+        assert_eq!(rolling_average(None, 40.0), 40.0);
+        assert_eq!(rolling_average(Some(40.0), 40.0), 40.0);
+        // One spike only moves it part of the way
+        let spiked = rolling_average(Some(40.0), 140.0);
+        assert!(spiked > 40.0 && spiked < 100.0);
+    }
+
+    #[test]
+    fn test_bandwidth_warning() {
+        let (mut app, _action_rx) = test_app();
+        app.is_connected = true;
+        app.bandwidth_warn_kbps = Some(100.0);
+
+        let metrics = |kbps: f32| UiEvent::SendMetrics {
+            sent_packets: 0,
+            received_packets: 0,
+            sent_kbps: kbps / 2.0,
+            receive_kbps: kbps / 2.0,
+            packet_loss: 0.0,
+            ping: 20.0,
+        };
+
+        app.handle_event(metrics(50.0));
+        app.handle_event(metrics(250.0));
+        assert!(!app.bandwidth_warning);
+
+        for _ in 0..10 {
+            app.handle_event(metrics(150.0));
+        }
+        assert!(app.bandwidth_warning);
+
+        // Dipping just under the cap doesn't clear it
+        for _ in 0..10 {
+            app.handle_event(metrics(95.0));
+        }
+        assert!(app.bandwidth_warning);
+
+        for _ in 0..10 {
+            app.handle_event(metrics(40.0));
+        }
+        assert!(!app.bandwidth_warning);
+    }
+
+    #[test]
+    fn test_client_seat() {
+        let (mut app, _action_rx) = test_app();
+        for name in ["Alice", "Bob"] {
+            app.handle_event(UiEvent::NewConnection(name.to_string()));
+        }
+
+        let seat = |name: &str, seat: Option<&str>| UiEvent::ClientSeat {
+            name: name.to_string(),
+            seat: seat.map(String::from),
+        };
+        let seats = |app: &YourControlsApp| -> Vec<Option<String>> {
+            app.clients.iter().map(|c| c.seat.clone()).collect()
+        };
+
+        app.handle_event(seat("Alice", Some("Pilot")));
+        app.handle_event(seat("Bob", Some("Copilot")));
+        assert_eq!(
+            seats(&app),
+            vec![Some("Pilot".to_string()), Some("Copilot".to_string())]
+        );
+
+        // Taking a held seat moves its holder out
+        app.handle_event(seat("Bob", Some("Pilot")));
+        assert_eq!(seats(&app), vec![None, Some("Pilot".to_string())]);
+
+        app.handle_event(seat("Bob", None));
+        assert_eq!(seats(&app), vec![None, None]);
+    }
+
+    #[test]
+    fn test_overlay_mode_persisted() {
+        let (mut app, action_rx) = test_app();
+
+        app.load_config(Config {
+            overlay_opacity: 5.0,
+            overlay_position: Some([40.0, 60.0]),
+            ..Config::default()
+        });
+        assert_eq!(app.overlay_opacity, 1.0);
+
+        app.set_overlay_mode(true);
+        match action_rx.try_recv() {
+            Ok(AppMessage::UpdateConfig { new_config }) => {
+                assert!(new_config.ui_overlay_mode);
+                assert_eq!(new_config.overlay_position, Some([40.0, 60.0]));
             }
+            other => panic!("Unexpected message {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_clock_offset() {
+        let (mut app, _action_rx) = test_app();
+        app.handle_event(UiEvent::NewConnection("Bob".to_string()));
+        assert_eq!(app.clients[0].clock_offset, None);
+
+        app.handle_event(UiEvent::ClockOffset {
+            name: "Bob".to_string(),
+            offset: 250.0,
+        });
+        assert_eq!(app.clients[0].clock_offset, Some(250.0));
+
+        // Unknown clients are ignored
+        app.handle_event(UiEvent::ClockOffset {
+            name: "Carol".to_string(),
+            offset: 1.0,
+        });
+        assert_eq!(app.clients.len(), 1);
+    }
+
+    #[test]
+    fn test_watch_sync_values() {
+        let (mut app, action_rx) = test_app();
+
+        // Closed to start with, nothing to tell the app
+        app.watch_sync_values(false);
+        assert!(action_rx.try_recv().is_err());
+
+        app.watch_sync_values(true);
+        assert!(matches!(
+            action_rx.try_recv(),
+            Ok(AppMessage::WatchSyncValues { watching: true })
+        ));
+        // Only sent when it changes
+        app.watch_sync_values(true);
+        assert!(action_rx.try_recv().is_err());
+
+        app.handle_event(UiEvent::SyncValues(vec![(
+            "PLANE ALTITUDE".to_string(),
+            "1500.0000".to_string(),
+        )]));
+        app.watch_sync_values(false);
+        assert!(matches!(
+            action_rx.try_recv(),
+            Ok(AppMessage::WatchSyncValues { watching: false })
+        ));
+        assert!(app.sync_values.is_empty());
+    }
+
+    #[test]
+    fn test_update_confirm() {
+        let (mut app, action_rx) = test_app();
+
+        // Nothing to answer yet
+        app.confirm_update(true);
+        assert!(action_rx.try_recv().is_err());
+
+        app.handle_event(UiEvent::Version("2.9.0".to_string()));
+        app.handle_event(UiEvent::UpdateConfirm {
+            files: vec!["YourControls.zip".to_string()],
+            size: 3 * 1024 * 1024,
+        });
+        assert_eq!(format_size(3 * 1024 * 1024), "3.0 MB");
+
+        app.confirm_update(false);
+        assert!(matches!(
+            action_rx.try_recv(),
+            Ok(AppMessage::ConfirmUpdate { accepted: false })
+        ));
+        assert!(app.update_confirm.is_none());
+        assert!(app.update_available.is_some());
+
+        app.handle_event(UiEvent::UpdateConfirm {
+            files: vec![],
+            size: 0,
+        });
+        app.confirm_update(true);
+        assert!(matches!(
+            action_rx.try_recv(),
+            Ok(AppMessage::ConfirmUpdate { accepted: true })
+        ));
+        assert!(app.update_available.is_none());
+    }
+
+    #[test]
+    fn test_parse_shortcut() {
+        assert_eq!(
+            parse_shortcut("Ctrl+O"),
+            Some(egui::KeyboardShortcut::new(
+                egui::Modifiers::CTRL,
+                egui::Key::O
+            ))
+        );
+        assert_eq!(
+            parse_shortcut("shift + alt + F5"),
+            Some(egui::KeyboardShortcut::new(
+                egui::Modifiers::SHIFT | egui::Modifiers::ALT,
+                egui::Key::F5
+            ))
+        );
+        assert_eq!(parse_shortcut("Hyper+O"), None);
+        assert_eq!(parse_shortcut("Ctrl+"), None);
+        assert_eq!(parse_shortcut(""), None);
+    }
+
+    #[test]
+    fn test_toggle_self_observer() {
+        let (mut app, action_rx) = test_app();
+
+        // Not in a session
+        app.toggle_self_observer();
+        assert!(action_rx.try_recv().is_err());
+
+        app.is_connected = true;
+        app.toggle_self_observer();
+        assert!(matches!(action_rx.try_recv(), Ok(AppMessage::GoObserver)));
+
+        app.handle_event(UiEvent::Observing(true));
+        app.toggle_self_observer();
+        assert!(matches!(
+            action_rx.try_recv(),
+            Ok(AppMessage::LeaveObserver)
+        ));
+    }
+
+    #[test]
+    fn test_required_aircraft_mismatch() {
+        let (mut app, _action_rx) = test_app();
+        app.loaded_aircraft = Some("Asobo - C172.yaml".to_string());
+        assert_eq!(app.required_aircraft_mismatch(), None);
+
+        app.handle_event(UiEvent::RequiredAircraft("Asobo - C172".to_string()));
+        assert_eq!(app.required_aircraft_mismatch(), None);
+
+        app.handle_event(UiEvent::DetectedAircraft("Boeing 747-8i Asobo".to_string()));
+        assert_eq!(
+            app.required_aircraft_mismatch().as_deref(),
+            Some("You are flying Boeing 747-8i Asobo")
+        );
+
+        app.loaded_aircraft = Some("Asobo - B747.yaml".to_string());
+        assert_eq!(
+            app.required_aircraft_mismatch().as_deref(),
+            Some("You have Asobo - B747 loaded")
+        );
+
+        app.handle_event(UiEvent::ClientFail("Disconnected".to_string()));
+        assert_eq!(app.required_aircraft_mismatch(), None);
+    }
+
+    #[test]
+    fn test_update_retry_limit() {
+        let (mut app, action_rx) = test_app();
+
+        let fail = |retryable| UiEvent::UpdateFailedDetail {
+            reason: "HTTP request failed".to_string(),
+            retryable,
+        };
+
+        app.handle_event(fail(false));
+        assert!(!app.can_retry_update());
+        app.retry_update();
+        assert!(action_rx.try_recv().is_err());
+
+        for _ in 0..MAX_UPDATE_RETRIES {
+            app.handle_event(fail(true));
+            app.retry_update();
+            assert!(matches!(action_rx.try_recv(), Ok(AppMessage::RunUpdater)));
+        }
+
+        app.handle_event(fail(true));
+        assert!(!app.can_retry_update());
+        app.retry_update();
+        assert!(action_rx.try_recv().is_err());
+
+        // A successful download starts the count over
+        app.handle_event(UiEvent::UpdateConfirm {
+            files: vec![],
+            size: 0,
         });
+        app.handle_event(fail(true));
+        assert!(app.can_retry_update());
     }
 }
@@ -0,0 +1,134 @@
+// Headless UI Backend
+//
+// This module provides a UI backend without a window, for running as a dedicated
+// host on machines without a display. AppMessages are read from stdin as JSON lines
+// and UI updates are written to stdout as JSON, using the same message protocol as
+// the WebView backend.
+
+use super::{AppMessage, UIBackend};
+use crossbeam_channel::{unbounded, Receiver, Sender, TryRecvError};
+use log::warn;
+use std::io::{self, BufRead};
+use std::sync::{
+    atomic::{AtomicBool, Ordering::SeqCst},
+    Arc,
+};
+use std::thread;
+
+/// Headless backend driven over stdin/stdout
+pub struct HeadlessBackend {
+    exited: Arc<AtomicBool>,
+    rx: Receiver<AppMessage>,
+}
+
+impl UIBackend for HeadlessBackend {
+    fn setup(_title: String) -> Self {
+        let (tx, rx) = unbounded();
+
+        let exited = Arc::new(AtomicBool::new(false));
+        let exited_clone = exited.clone();
+
+        thread::spawn(move || {
+            read_messages(io::stdin().lock(), &tx);
+            // EOF, nothing more will be sent
+            exited_clone.store(true, SeqCst);
+        });
+
+        Self { exited, rx }
+    }
+
+    fn exited(&self) -> bool {
+        self.exited.load(SeqCst)
+    }
+
+    fn get_next_message(&self) -> Result<AppMessage, TryRecvError> {
+        let message = self.rx.try_recv();
+        if let Err(TryRecvError::Disconnected) = message {
+            self.exited.store(true, SeqCst);
+        }
+        message
+    }
+
+    fn invoke(&self, type_string: &str, data: Option<&str>) {
+        println!(
+            "{}",
+            serde_json::json!({"type": type_string, "data": data.unwrap_or_default()})
+        );
+    }
+}
+
+/// Parse a single JSON line into a message, ignoring blank lines
+fn parse_message_line(line: &str) -> Option<AppMessage> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+
+    match serde_json::from_str(line) {
+        Ok(message) => Some(message),
+        Err(e) => {
+            warn!("[UI] Could not parse message {}: {}", line, e);
+            None
+        }
+    }
+}
+
+/// Forward every message read from `reader` until EOF
+fn read_messages<R: BufRead>(reader: R, tx: &Sender<AppMessage>) {
+    for line in reader.lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+
+        if let Some(message) = parse_message_line(&line) {
+            if tx.send(message).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_parse_start_server() {
+        let line = r#"{"type":"startServer","username":"Bob","is_ipv6":false,"use_upnp":true,"port":7777,"method":"direct"}"#;
+
+        match parse_message_line(line) {
+            Some(AppMessage::StartServer {
+                username,
+                port,
+                use_upnp,
+                ..
+            }) => {
+                assert_eq!(username, "Bob");
+                assert_eq!(port, 7777);
+                assert!(use_upnp);
+            }
+            other => panic!("Unexpected message {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid_and_blank_lines() {
+        assert!(parse_message_line("").is_none());
+        assert!(parse_message_line("   ").is_none());
+        assert!(parse_message_line("not json").is_none());
+    }
+
+    #[test]
+    fn test_read_messages_until_eof() {
+        let input = "{\"type\":\"startup\"}\n\ngarbage\n{\"type\":\"disconnect\"}\n";
+        let (tx, rx) = unbounded();
+
+        read_messages(Cursor::new(input), &tx);
+
+        assert!(matches!(rx.try_recv(), Ok(AppMessage::Startup)));
+        assert!(matches!(rx.try_recv(), Ok(AppMessage::Disconnect)));
+        assert!(rx.try_recv().is_err());
+    }
+}
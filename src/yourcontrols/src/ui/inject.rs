@@ -0,0 +1,163 @@
+// Message Injection
+//
+// Debug-only hook for driving the app from scripts. When the file or named pipe in
+// YOURCONTROLS_INJECT exists, AppMessages are read from it as JSON lines and handed
+// to the app alongside the ones coming from the UI. Only compiled with the
+// `debug-inject` feature in debug builds, release builds never read the file.
+
+use super::{AppMessage, UIBackend};
+use crossbeam_channel::{unbounded, Receiver, TryRecvError};
+use log::{info, warn};
+use std::env;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::thread;
+
+pub const INJECT_ENV_VAR: &str = "YOURCONTROLS_INJECT";
+
+/// Read newline-delimited messages from `path` on a thread until EOF
+fn spawn_reader(path: PathBuf) -> Receiver<AppMessage> {
+    let (tx, rx) = unbounded();
+
+    thread::spawn(move || {
+        // Opening a named pipe blocks until something writes to it
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                warn!(
+                    "[UI] Could not open {} for injection: {}",
+                    path.display(),
+                    e
+                );
+                return;
+            }
+        };
+
+        for line in BufReader::new(file).lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            match serde_json::from_str(line) {
+                Ok(message) => {
+                    if tx.send(message).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => warn!("[UI] Could not parse injected message {}: {}", line, e),
+            }
+        }
+    });
+
+    rx
+}
+
+/// Backend decorator that also takes messages from an injection file
+pub struct InjectBackend<B: UIBackend> {
+    inner: B,
+    injected: Option<Receiver<AppMessage>>,
+}
+
+impl<B: UIBackend> InjectBackend<B> {
+    pub fn with_source(inner: B, path: &Path) -> Self {
+        info!("[UI] Reading injected messages from {}", path.display());
+        Self {
+            inner,
+            injected: Some(spawn_reader(path.to_path_buf())),
+        }
+    }
+}
+
+impl<B: UIBackend> UIBackend for InjectBackend<B> {
+    fn setup(title: String) -> Self {
+        let inner = B::setup(title);
+        match env::var_os(INJECT_ENV_VAR) {
+            Some(path) => Self::with_source(inner, Path::new(&path)),
+            None => Self {
+                inner,
+                injected: None,
+            },
+        }
+    }
+
+    fn exited(&self) -> bool {
+        self.inner.exited()
+    }
+
+    fn is_alive(&self) -> bool {
+        self.inner.is_alive()
+    }
+
+    fn setup_error(&self) -> Option<String> {
+        self.inner.setup_error()
+    }
+
+    fn get_next_message(&self) -> Result<AppMessage, TryRecvError> {
+        if let Some(message) = self.injected.as_ref().and_then(|rx| rx.try_recv().ok()) {
+            return Ok(message);
+        }
+        self.inner.get_next_message()
+    }
+
+    fn invoke(&self, type_string: &str, data: Option<&str>) {
+        self.inner.invoke(type_string, data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::time::{Duration, Instant};
+
+    struct IdleBackend;
+
+    impl UIBackend for IdleBackend {
+        fn setup(_title: String) -> Self {
+            Self
+        }
+
+        fn exited(&self) -> bool {
+            false
+        }
+
+        fn get_next_message(&self) -> Result<AppMessage, TryRecvError> {
+            Err(TryRecvError::Empty)
+        }
+
+        fn invoke(&self, _type_string: &str, _data: Option<&str>) {}
+    }
+
+    #[test]
+    fn test_injects_disconnect() {
+        let path =
+            env::temp_dir().join(format!("yourcontrols_inject_{}.jsonl", std::process::id()));
+        let mut file = File::create(&path).unwrap();
+        writeln!(file, "garbage\n\n{{\"type\":\"disconnect\"}}").unwrap();
+        drop(file);
+
+        let backend = InjectBackend::with_source(IdleBackend, &path);
+
+        let started = Instant::now();
+        let message = loop {
+            match backend.get_next_message() {
+                Ok(message) => break message,
+                Err(_) if started.elapsed() < Duration::from_secs(5) => {
+                    thread::sleep(Duration::from_millis(10))
+                }
+                Err(e) => panic!("Nothing injected: {:?}", e),
+            }
+        };
+        assert!(matches!(message, AppMessage::Disconnect));
+        assert!(backend.get_next_message().is_err());
+
+        std::fs::remove_file(&path).ok();
+    }
+}
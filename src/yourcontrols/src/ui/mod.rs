@@ -15,6 +15,24 @@ pub mod webview;
 #[cfg(feature = "egui-ui")]
 pub mod egui_backend;
 
+#[cfg(feature = "egui-ui")]
+pub mod tray;
+
+#[cfg(feature = "headless-ui")]
+pub mod headless;
+
+#[cfg(not(any(feature = "webview-ui", feature = "egui-ui", feature = "headless-ui")))]
+pub mod null;
+
+#[cfg(any(feature = "webview-ui", feature = "egui-ui"))]
+pub mod ratelimit;
+
+pub mod deeplink;
+#[cfg(all(feature = "debug-inject", debug_assertions))]
+pub mod inject;
+pub mod multi;
+pub mod recording;
+
 // Re-export the active backend
 #[cfg(feature = "webview-ui")]
 pub use webview::WebViewBackend as ActiveBackend;
@@ -22,6 +40,24 @@ pub use webview::WebViewBackend as ActiveBackend;
 #[cfg(feature = "egui-ui")]
 pub use egui_backend::EguiBackend as ActiveBackend;
 
+// Only used when no windowed backend is enabled
+#[cfg(all(
+    feature = "headless-ui",
+    not(any(feature = "webview-ui", feature = "egui-ui"))
+))]
+pub use headless::HeadlessBackend as ActiveBackend;
+
+// Keeps the crate building without any UI feature
+#[cfg(not(any(feature = "webview-ui", feature = "egui-ui", feature = "headless-ui")))]
+pub use null::NullBackend as ActiveBackend;
+
+// Scripted messages can be injected into debug builds with the debug-inject feature
+#[cfg(all(feature = "debug-inject", debug_assertions))]
+pub type AppBackend = inject::InjectBackend<ActiveBackend>;
+
+#[cfg(not(all(feature = "debug-inject", debug_assertions)))]
+pub type AppBackend = ActiveBackend;
+
 /// Connection method for server/client
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -31,8 +67,19 @@ pub enum ConnectionMethod {
     CloudServer,
 }
 
+/// Overall connection state shown by the status indicator
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum StatusState {
+    Disconnected,
+    Connecting,
+    Connected,
+    Degraded,
+    Error,
+}
+
 /// Messages sent FROM the UI TO the application
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum AppMessage {
     /// Start a server
@@ -42,6 +89,11 @@ pub enum AppMessage {
         use_upnp: bool,
         port: u16,
         method: ConnectionMethod,
+        /// Most clients that may join, unlimited if None
+        max_clients: Option<usize>,
+        /// Password joiners must enter, None for an open session
+        #[serde(default)]
+        password: Option<String>,
     },
     /// Connect to a server
     Connect {
@@ -52,27 +104,71 @@ pub enum AppMessage {
         hostname: Option<String>,
         port: Option<u16>,
         method: ConnectionMethod,
+        /// Join only to watch, never taking control
+        #[serde(default)]
+        spectator: bool,
+        /// Session code to retry with through the cloud server if a direct connection fails
+        #[serde(default)]
+        relay_session_id: Option<String>,
+        /// Password for sessions that need one
+        #[serde(default)]
+        password: Option<String>,
+    },
+    /// Check the address or cloud server can be reached without joining
+    TestConnection {
+        session_id: Option<String>,
+        isipv6: bool,
+        ip: Option<IpAddr>,
+        hostname: Option<String>,
+        port: Option<u16>,
+        method: ConnectionMethod,
     },
     /// Transfer control to another client
     TransferControl { target: String },
+    /// Transfer control to another client, seating them at a multi-crew position
+    TransferControlSeat { target: String, seat: String },
     /// Set observer mode for a client
     SetObserver { target: String, is_observer: bool },
     /// Load an aircraft configuration
     LoadAircraft { config_file_name: String },
+    /// Load an aircraft configuration from a path outside the definitions folder
+    LoadAircraftPath { path: String },
     /// Disconnect from server/stop server
     Disconnect,
     /// Application startup event
     Startup,
-    /// Run the updater
+    /// Run the updater, the UI is asked to confirm before anything is downloaded
     RunUpdater,
+    /// Answer to an update confirmation, the installer only runs when accepted
+    ConfirmUpdate { accepted: bool },
     /// Force take control
     ForceTakeControl,
+    /// Stop or allow new clients joining, host only
+    SetSessionLocked { locked: bool },
+    /// Stop or resume sending our aircraft state while staying connected
+    SetSyncPaused { paused: bool },
+    /// Change how many aircraft state updates are sent per second
+    SetUpdateRate { hz: u32 },
+    /// Change how long interpolated values are smoothed over, in milliseconds
+    SetSmoothing { ms: u32 },
+    /// Ask whoever is in control to resend their full aircraft state
+    RequestResync,
+    /// Start or stop sending live sync values, sent while the Sync Inspector is open
+    WatchSyncValues { watching: bool },
+    /// Take control from a student without their confirmation, host instructors only
+    InstructorTakeControl { target: String },
     /// Update configuration
     UpdateConfig {
         new_config: crate::simconfig::Config,
     },
     /// Go into observer mode
     GoObserver,
+    /// Leave observer mode, spectators stay observers
+    LeaveObserver,
+    /// Tell the other clients whether we're ready to go
+    SetReady { ready: bool },
+    /// Send a chat message to all clients
+    BroadcastMessage { text: String },
 }
 
 /// UI Backend trait - all UI implementations must implement this
@@ -100,6 +196,18 @@ pub trait UIBackend: Send {
     /// `true` if the user closed the window, `false` otherwise
     fn exited(&self) -> bool;
 
+    /// Check if the UI can still send messages
+    ///
+    /// Callers should stop polling once this returns `false`.
+    fn is_alive(&self) -> bool {
+        !self.exited()
+    }
+
+    /// Why the UI failed to start, if it gave up before showing anything
+    fn setup_error(&self) -> Option<String> {
+        None
+    }
+
     /// Poll for the next message from the UI
     ///
     /// This is called in the main event loop to receive user actions.
@@ -107,7 +215,9 @@ pub trait UIBackend: Send {
     /// # Returns
     /// - `Ok(AppMessage)` if a message is available
     /// - `Err(TryRecvError::Empty)` if no messages are pending
-    /// - `Err(TryRecvError::Disconnected)` if the UI thread terminated
+    /// - `Err(TryRecvError::Disconnected)` if the UI thread terminated. Backends
+    ///   must also report `exited()` from then on, even if the thread died
+    ///   without flagging it.
     fn get_next_message(&self) -> Result<AppMessage, TryRecvError>;
 
     // ============================================================================
@@ -135,6 +245,11 @@ pub trait UIBackend: Send {
         self.invoke("attempt", None);
     }
 
+    /// Show that a direct connection failed and the cloud server is being tried instead
+    fn relay_fallback(&self) {
+        self.invoke("relay_fallback", None);
+    }
+
     /// Show "connected to server" status (client side)
     fn connected(&self) {
         self.invoke("connected", None);
@@ -145,11 +260,201 @@ pub trait UIBackend: Send {
         self.invoke("server_fail", Some(reason));
     }
 
+    /// Show that the server couldn't bind its port, with a free port to try instead if one was found
+    fn server_bind_failed(&self, addr: &str, reason: &str, suggested_port: Option<u16>) {
+        use serde_json::json;
+        let data = json!({
+            "addr": addr,
+            "reason": reason,
+            "suggestedPort": suggested_port,
+        });
+        self.invoke("server_bind_failed", Some(&data.to_string()));
+    }
+
     /// Show client connection failure
     fn client_fail(&self, reason: &str) {
         self.invoke("client_fail", Some(reason));
     }
 
+    /// Outcome of a Test Connection request
+    fn test_connection_result(&self, success: bool, detail: &str) {
+        use serde_json::json;
+        let data = json!({
+            "success": success,
+            "detail": detail,
+        });
+        self.invoke("test_connection_result", Some(&data.to_string()));
+    }
+
+    /// Report whether the UPnP port mapping for a direct server succeeded
+    fn upnp_result(&self, success: bool, external_port: Option<u16>) {
+        use serde_json::json;
+        let data = json!({
+            "success": success,
+            "externalPort": external_port,
+        });
+        self.invoke("upnp_result", Some(&data.to_string()));
+    }
+
+    /// Show which relay the session goes through, with its latency once measured
+    fn set_relay_region(&self, region: &str, ping_ms: Option<f32>) {
+        use serde_json::json;
+        let data = json!({
+            "region": region,
+            "ping": ping_ms,
+        });
+        self.invoke("relay_region", Some(&data.to_string()));
+    }
+
+    /// Set the name the local user is known by in the session
+    fn set_self_name(&self, name: &str) {
+        self.invoke("self_name", Some(name));
+    }
+
+    /// Notify UI that the aircraft definitions folder is being scanned
+    fn scanning_aircraft(&self, active: bool) {
+        self.invoke(
+            if active {
+                "scanning_aircraft"
+            } else {
+                "scanning_aircraft_done"
+            },
+            None,
+        );
+    }
+
+    /// Known sync limitations of the loaded aircraft, empty when there are none
+    fn set_sync_caveats(&self, caveats: &[String]) {
+        use serde_json::json;
+        self.invoke("sync_caveats", Some(&json!(caveats).to_string()));
+    }
+
+    /// Show whether remote aircraft state is predicted rather than authoritative
+    fn set_prediction(&self, predicting: bool) {
+        self.invoke(
+            if predicting {
+                "predicting"
+            } else {
+                "stop_predicting"
+            },
+            None,
+        );
+    }
+
+    /// Fill in the join details from a yourcontrols:// link
+    fn handle_deeplink(&self, url: &str) {
+        self.invoke("deeplink", Some(url));
+    }
+
+    /// Show whether the session accepts new joiners
+    fn session_locked(&self, locked: bool) {
+        self.invoke(
+            if locked {
+                "session_locked"
+            } else {
+                "session_unlocked"
+            },
+            None,
+        );
+    }
+
+    /// Show whether we're waiting on the full aircraft state after joining or resyncing
+    fn syncing(&self, syncing: bool) {
+        self.invoke(if syncing { "syncing" } else { "synced" }, None);
+    }
+
+    /// Features the connected server supports, used to hide controls it can't honor
+    fn set_server_capabilities(&self, caps: &[String]) {
+        use serde_json::json;
+        self.invoke("server_capabilities", Some(&json!(caps).to_string()));
+    }
+
+    /// Show whether sending our aircraft state is paused
+    fn sync_paused(&self, paused: bool) {
+        self.invoke(
+            if paused {
+                "sync_paused"
+            } else {
+                "sync_resumed"
+            },
+            None,
+        );
+    }
+
+    /// Show how many clients are connected out of the most allowed, 0 meaning no limit
+    fn set_capacity(&self, current: usize, max: usize) {
+        use serde_json::json;
+        let data = json!({
+            "current": current,
+            "max": max,
+        });
+        self.invoke("capacity", Some(&data.to_string()));
+    }
+
+    /// Show whether the simulator is running, sessions can only start once it is
+    fn sim_connected(&self, connected: bool) {
+        self.invoke(
+            if connected {
+                "sim_connected"
+            } else {
+                "sim_unavailable"
+            },
+            None,
+        );
+    }
+
+    /// Show the title of the aircraft loaded in the sim
+    fn set_detected_aircraft(&self, title: &str) {
+        self.invoke("detected_aircraft", Some(title));
+    }
+
+    /// Set the aircraft the host is flying, which the session expects everyone to load
+    fn set_required_aircraft(&self, name: &str) {
+        self.invoke("required_aircraft", Some(name));
+    }
+
+    /// Warn that no definition exists for the aircraft loaded in the sim
+    fn unsupported_aircraft(&self, title: &str) {
+        self.invoke("unsupported_aircraft", Some(title));
+    }
+
+    /// Confirm a definition loaded by path was read, by its file name
+    fn aircraft_loaded(&self, config_file_name: &str) {
+        self.invoke("aircraft_loaded", Some(config_file_name));
+    }
+
+    /// Notify UI that the SimConnect link to the simulator was lost
+    fn sim_disconnected(&self) {
+        self.invoke("sim_disconnected", None);
+    }
+
+    /// Notify UI that the update rate is reduced, or back to normal when `active` is false
+    fn quality_reduced(&self, active: bool, reason: &str) {
+        use serde_json::json;
+        let data = json!({
+            "active": active,
+            "reason": reason,
+        });
+        self.invoke("quality_reduced", Some(&data.to_string()));
+    }
+
+    /// Show why a configuration update was rejected
+    fn config_errors(&self, errors: &[String]) {
+        use serde_json::json;
+        self.invoke("config_errors", Some(&json!(errors).to_string()));
+    }
+
+    /// Reset the status text to the default for the current connection state
+    fn clear_status(&self) {
+        self.invoke("clear_status", None);
+    }
+
+    /// Set the status indicator state
+    fn set_status(&self, state: StatusState) {
+        let data = serde_json::to_string(&state).unwrap();
+        self.invoke("status", Some(&data));
+    }
+
     // --- Control State ---
 
     /// Notify UI that we gained control of the aircraft
@@ -162,6 +467,16 @@ pub trait UIBackend: Send {
         self.invoke("lostcontrol", None);
     }
 
+    /// Notify UI that the local user made a control input
+    fn note_activity(&self) {
+        self.invoke("input_activity", None);
+    }
+
+    /// Flash the window/taskbar to draw the user's attention
+    fn request_attention(&self) {
+        self.invoke("request_attention", None);
+    }
+
     // --- Server State ---
 
     /// Notify UI that server started successfully
@@ -179,6 +494,33 @@ pub trait UIBackend: Send {
         self.invoke("host", None);
     }
 
+    /// Show the public address joiners should use for direct connections
+    ///
+    /// An empty address means the public IP could not be determined.
+    fn set_public_endpoint(&self, addr: &str) {
+        self.invoke("public_endpoint", Some(addr));
+    }
+
+    /// Show our public IP as a direct host and whether others can likely reach our port
+    fn public_ip_check(&self, ip: Option<String>, reachable: Option<bool>) {
+        use serde_json::json;
+        let data = json!({
+            "ip": ip,
+            "reachable": reachable,
+        });
+        self.invoke("public_ip_check", Some(&data.to_string()));
+    }
+
+    /// Report how a cloud connection reached the host
+    fn nat_result(&self, punched: bool, relay_fallback: bool) {
+        use serde_json::json;
+        let data = json!({
+            "punched": punched,
+            "relayFallback": relay_fallback,
+        });
+        self.invoke("nat_result", Some(&data.to_string()));
+    }
+
     // --- Connection Management ---
 
     /// Notify UI that a new client connected
@@ -186,6 +528,29 @@ pub trait UIBackend: Send {
         self.invoke("newconnection", Some(name));
     }
 
+    /// Notify UI that a client connected, along with what we know about them
+    fn new_connection_detailed(&self, name: &str, aircraft: Option<&str>, version: Option<&str>) {
+        use serde_json::json;
+        let data = json!({
+            "name": name,
+            "aircraft": aircraft,
+            "version": version,
+        });
+        self.invoke("newconnection_detailed", Some(&data.to_string()));
+    }
+
+    /// Update where a client's aircraft is, for the minimap
+    fn set_client_position(&self, name: &str, lat: f64, lon: f64, heading: f32) {
+        use serde_json::json;
+        let data = json!({
+            "name": name,
+            "lat": lat,
+            "lon": lon,
+            "heading": heading,
+        });
+        self.invoke("client_position", Some(&data.to_string()));
+    }
+
     /// Notify UI that a client disconnected
     fn lost_connection(&self, name: &str) {
         self.invoke("lostconnection", Some(name));
@@ -202,6 +567,15 @@ pub trait UIBackend: Send {
         }
     }
 
+    /// Mark whether updates are still arriving from the client in control
+    fn set_client_input_active(&self, name: &str, active: bool) {
+        if active {
+            self.invoke("client_input_active", Some(name));
+        } else {
+            self.invoke("client_input_stalled", Some(name));
+        }
+    }
+
     /// Set another client's observer mode state
     fn set_observing(&self, name: &str, observing: bool) {
         if observing {
@@ -211,11 +585,75 @@ pub trait UIBackend: Send {
         }
     }
 
+    /// Mark another client as a spectator
+    fn set_spectator(&self, name: &str) {
+        self.invoke("set_spectator", Some(name));
+    }
+
+    /// Set another client's ready status
+    fn set_client_ready(&self, name: &str, ready: bool) {
+        if ready {
+            self.invoke("set_ready", Some(name));
+        } else {
+            self.invoke("set_not_ready", Some(name));
+        }
+    }
+
+    /// Set which multi-crew seat a client holds, None when they hold none
+    fn set_client_seat(&self, name: &str, seat: Option<&str>) {
+        use serde_json::json;
+        let data = json!({
+            "name": name,
+            "seat": seat,
+        });
+        self.invoke("client_seat", Some(&data.to_string()));
+    }
+
+    /// Set how far a client's clock is ahead of ours, negative when behind
+    fn set_clock_offset(&self, name: &str, offset_ms: f32) {
+        use serde_json::json;
+        let data = json!({
+            "name": name,
+            "offset": offset_ms,
+        });
+        self.invoke("clock_offset", Some(&data.to_string()));
+    }
+
     /// Set which client is in control
     fn set_incontrol(&self, name: &str) {
         self.invoke("set_incontrol", Some(name));
     }
 
+    /// Notify UI that control moved to someone else, for following the pilot in control
+    fn control_changed(&self, new_controller: &str, previous: Option<&str>) {
+        use serde_json::json;
+        let data = json!({
+            "new": new_controller,
+            "previous": previous,
+        });
+        self.invoke("control_changed", Some(&data.to_string()));
+    }
+
+    /// Notify UI that a control transfer was rejected or the target went away
+    fn control_transfer_failed(&self, target: &str, reason: &str) {
+        use serde_json::json;
+        let data = json!({
+            "target": target,
+            "reason": reason,
+        });
+        self.invoke("control_transfer_failed", Some(&data.to_string()));
+    }
+
+    /// Show a countdown until control is automatically handed to `next`
+    fn control_countdown(&self, seconds_remaining: u32, next: &str) {
+        use serde_json::json;
+        let data = json!({
+            "secondsRemaining": seconds_remaining,
+            "next": next,
+        });
+        self.invoke("control_countdown", Some(&data.to_string()));
+    }
+
     // --- Configuration ---
 
     /// Add an aircraft to the selection list
@@ -223,16 +661,54 @@ pub trait UIBackend: Send {
         self.invoke("add_aircraft", Some(name));
     }
 
+    /// The scan found no aircraft configs in `searched_path`
+    fn no_aircraft_found(&self, searched_path: &str) {
+        self.invoke("no_aircraft_found", Some(searched_path));
+    }
+
     /// Notify UI of available update version
     fn version(&self, version: &str) {
         self.invoke("version", Some(version));
     }
 
+    /// Ask the user to approve an update, listing where the installer writes and its download size in bytes
+    fn update_confirm(&self, files: &[String], size: u64) {
+        use serde_json::json;
+        let data = json!({
+            "files": files,
+            "size": size,
+        });
+        self.invoke("update_confirm", Some(&data.to_string()));
+    }
+
+    /// Notify UI that the program was reopened by the installer after updating
+    fn update_ready(&self, version: &str) {
+        self.invoke("update_ready", Some(version));
+    }
+
+    /// Show the current value of every synced variable, as (name, value), only sent after
+    /// `AppMessage::WatchSyncValues`
+    fn set_sync_values(&self, pairs: &[(String, String)]) {
+        use serde_json::json;
+        let data = json!(pairs);
+        self.invoke("sync_values", Some(&data.to_string()));
+    }
+
     /// Notify UI that update download failed
     fn update_failed(&self) {
         self.invoke("update_failed", None);
     }
 
+    /// Notify UI that the update failed and why, `retryable` when trying again could work
+    fn update_failed_detail(&self, reason: &str, retryable: bool) {
+        use serde_json::json;
+        let data = json!({
+            "reason": reason,
+            "retryable": retryable,
+        });
+        self.invoke("update_failed_detail", Some(&data.to_string()));
+    }
+
     /// Send configuration data to UI
     fn send_config(&self, value: &str) {
         self.invoke("config_msg", Some(value));
@@ -253,6 +729,71 @@ pub trait UIBackend: Send {
         });
         self.invoke("metrics", Some(&data.to_string()));
     }
+
+    /// Send every network metric, for the detailed view
+    fn send_network_full(&self, metrics: &Metrics) {
+        use serde_json::json;
+        let data = json!({
+            "sentPackets": metrics.sent_packets,
+            "receivePackets": metrics.received_packets,
+            "ackedPackets": metrics.acked_packets,
+            "sentBandwidth": metrics.sent_kbps,
+            "receiveBandwidth": metrics.receive_kbps,
+            "packetLoss": metrics.packet_loss,
+            "rtt": metrics.rtt,
+        });
+        self.invoke("metrics_full", Some(&data.to_string()));
+    }
+
+    /// Show a chat message from a connected client
+    fn chat_message(&self, from: &str, text: &str) {
+        use serde_json::json;
+        let data = json!({
+            "from": from,
+            "text": text,
+        });
+        self.invoke("chat_message", Some(&data.to_string()));
+    }
+
+    /// Warn that a client is loading a different aircraft, so sync may briefly differ
+    fn client_changing_aircraft(&self, name: &str, to: &str) {
+        use serde_json::json;
+        let data = json!({
+            "name": name,
+            "to": to,
+        });
+        self.invoke("client_changing_aircraft", Some(&data.to_string()));
+    }
+
+    /// Show which variables are out of sync for a client, an empty slice clears it
+    fn set_desync_vars(&self, client: &str, vars: &[String]) {
+        use serde_json::json;
+        let data = json!({
+            "client": client,
+            "vars": vars,
+        });
+        self.invoke("desync_vars", Some(&data.to_string()));
+    }
+
+    /// Send the number of messages waiting in the app's outbound/inbound queues
+    fn set_queue_depth(&self, outbound: usize, inbound: usize) {
+        use serde_json::json;
+        let data = json!({
+            "outbound": outbound,
+            "inbound": inbound,
+        });
+        self.invoke("queue_depth", Some(&data.to_string()));
+    }
+
+    /// Send how many state updates per second are actually being sent
+    fn set_update_rate(&self, hz: f32) {
+        self.invoke("update_rate", Some(&hz.to_string()));
+    }
+
+    /// Interpolation smoothing now in effect, in milliseconds
+    fn smoothing_changed(&self, ms: u32) {
+        self.invoke("smoothing_changed", Some(&ms.to_string()));
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,184 @@
+// Multiplexing UI Backend
+//
+// Drives two backends at once, e.g. the window alongside a headless backend that
+// mirrors everything to stdout for debugging. Not wired up by default.
+
+use super::{AppMessage, UIBackend};
+use crossbeam_channel::TryRecvError;
+use std::sync::atomic::{AtomicBool, Ordering::SeqCst};
+
+/// Forwards UI updates to both backends and takes messages from either
+// Only constructed when debugging or in tests, so the default build never uses it
+#[allow(dead_code)]
+pub struct MultiBackend {
+    primary: Box<dyn UIBackend>,
+    secondary: Box<dyn UIBackend>,
+    // Which backend is polled first next, alternated so neither starves the other
+    poll_secondary_first: AtomicBool,
+}
+
+impl MultiBackend {
+    #[allow(dead_code)]
+    pub fn new(primary: Box<dyn UIBackend>, secondary: Box<dyn UIBackend>) -> Self {
+        Self {
+            primary,
+            secondary,
+            poll_secondary_first: AtomicBool::new(false),
+        }
+    }
+}
+
+impl UIBackend for MultiBackend {
+    /// Can't choose the two backends from a title, use `MultiBackend::new` instead
+    fn setup(_title: String) -> Self {
+        panic!("MultiBackend must be created with MultiBackend::new");
+    }
+
+    fn exited(&self) -> bool {
+        self.primary.exited() || self.secondary.exited()
+    }
+
+    fn setup_error(&self) -> Option<String> {
+        self.primary
+            .setup_error()
+            .or_else(|| self.secondary.setup_error())
+    }
+
+    fn get_next_message(&self) -> Result<AppMessage, TryRecvError> {
+        let (first, second) = if self.poll_secondary_first.fetch_xor(true, SeqCst) {
+            (&self.secondary, &self.primary)
+        } else {
+            (&self.primary, &self.secondary)
+        };
+
+        let first_result = first.get_next_message();
+        if first_result.is_ok() {
+            return first_result;
+        }
+
+        let second_result = second.get_next_message();
+        if second_result.is_ok() {
+            return second_result;
+        }
+
+        // Either closing counts as the UI closing
+        if let Err(TryRecvError::Disconnected) = first_result {
+            first_result
+        } else {
+            second_result
+        }
+    }
+
+    fn invoke(&self, type_string: &str, data: Option<&str>) {
+        self.primary.invoke(type_string, data);
+        self.secondary.invoke(type_string, data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossbeam_channel::{unbounded, Receiver, Sender};
+    use std::sync::{Arc, Mutex};
+
+    // Backend fed from a channel that keeps everything invoked on it
+    struct TestBackend {
+        rx: Receiver<AppMessage>,
+        exited: AtomicBool,
+        invoked: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl UIBackend for TestBackend {
+        fn setup(_title: String) -> Self {
+            unreachable!()
+        }
+
+        fn exited(&self) -> bool {
+            self.exited.load(SeqCst)
+        }
+
+        fn get_next_message(&self) -> Result<AppMessage, TryRecvError> {
+            let message = self.rx.try_recv();
+            if let Err(TryRecvError::Disconnected) = message {
+                self.exited.store(true, SeqCst);
+            }
+            message
+        }
+
+        fn invoke(&self, type_string: &str, _data: Option<&str>) {
+            self.invoked.lock().unwrap().push(type_string.to_string());
+        }
+    }
+
+    fn test_backend() -> (
+        Box<dyn UIBackend>,
+        Sender<AppMessage>,
+        Arc<Mutex<Vec<String>>>,
+    ) {
+        let (tx, rx) = unbounded();
+        let invoked = Arc::new(Mutex::new(Vec::new()));
+        let backend = TestBackend {
+            rx,
+            exited: AtomicBool::new(false),
+            invoked: invoked.clone(),
+        };
+        (Box::new(backend), tx, invoked)
+    }
+
+    fn is_startup(message: Result<AppMessage, TryRecvError>) -> bool {
+        matches!(message, Ok(AppMessage::Startup))
+    }
+
+    fn is_disconnect(message: Result<AppMessage, TryRecvError>) -> bool {
+        matches!(message, Ok(AppMessage::Disconnect))
+    }
+
+    #[test]
+    fn test_invoke_reaches_both() {
+        let (primary, _primary_tx, primary_invoked) = test_backend();
+        let (secondary, _secondary_tx, secondary_invoked) = test_backend();
+        let multi = MultiBackend::new(primary, secondary);
+
+        multi.connected();
+
+        assert_eq!(*primary_invoked.lock().unwrap(), vec!["connected"]);
+        assert_eq!(*secondary_invoked.lock().unwrap(), vec!["connected"]);
+    }
+
+    #[test]
+    fn test_messages_alternate() {
+        let (primary, primary_tx, _) = test_backend();
+        let (secondary, secondary_tx, _) = test_backend();
+        let multi = MultiBackend::new(primary, secondary);
+
+        for _ in 0..2 {
+            primary_tx.send(AppMessage::Startup).unwrap();
+            secondary_tx.send(AppMessage::Disconnect).unwrap();
+        }
+
+        assert!(is_startup(multi.get_next_message()));
+        assert!(is_disconnect(multi.get_next_message()));
+        assert!(is_startup(multi.get_next_message()));
+        assert!(is_disconnect(multi.get_next_message()));
+        assert_eq!(multi.get_next_message().err(), Some(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn test_either_exiting_exits() {
+        let (primary, _primary_tx, _) = test_backend();
+        let (secondary, secondary_tx, _) = test_backend();
+        let multi = MultiBackend::new(primary, secondary);
+        assert!(!multi.exited());
+
+        secondary_tx.send(AppMessage::Startup).unwrap();
+        drop(secondary_tx);
+
+        // Still delivers what was sent before closing
+        assert!(is_startup(multi.get_next_message()));
+        assert_eq!(
+            multi.get_next_message().err(),
+            Some(TryRecvError::Disconnected)
+        );
+        assert!(multi.exited());
+    }
+}
@@ -0,0 +1,43 @@
+// Null UI Backend
+//
+// Used when no UI feature is compiled in, so the crate still builds. Nothing is
+// shown and no messages ever arrive; enable `webview-ui`, `egui-ui` or
+// `headless-ui` for a usable app.
+
+use super::{AppMessage, UIBackend};
+use crossbeam_channel::TryRecvError;
+use log::warn;
+
+/// Backend that ignores every update and never sends a message
+pub struct NullBackend;
+
+impl UIBackend for NullBackend {
+    fn setup(_title: String) -> Self {
+        warn!("[UI] No UI backend compiled in, running without a UI");
+        Self
+    }
+
+    fn exited(&self) -> bool {
+        false
+    }
+
+    fn get_next_message(&self) -> Result<AppMessage, TryRecvError> {
+        Err(TryRecvError::Empty)
+    }
+
+    fn invoke(&self, _type_string: &str, _data: Option<&str>) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_null_backend_stays_idle() {
+        let backend = NullBackend::setup("YourControls".to_string());
+        backend.connected();
+
+        assert_eq!(backend.get_next_message().err(), Some(TryRecvError::Empty));
+        assert!(backend.is_alive());
+    }
+}
@@ -0,0 +1,127 @@
+// UI Message Rate Limiting
+//
+// A buggy page or a runaway loop in the UI could send messages faster than the app
+// handles them, filling the unbounded channel. Messages past the limit are dropped.
+
+use crossbeam_channel::Sender;
+use log::warn;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Most messages the UI may send to the app per second
+pub const MAX_UI_MESSAGES_PER_SEC: u32 = 100;
+
+/// Token bucket allowing bursts of up to `per_sec` messages
+pub struct RateLimiter {
+    per_sec: f64,
+    tokens: f64,
+    last_refill: Instant,
+    // Messages dropped since the last one let through
+    dropped: u32,
+}
+
+impl RateLimiter {
+    pub fn new(per_sec: u32) -> Self {
+        Self {
+            per_sec: f64::from(per_sec),
+            tokens: f64::from(per_sec),
+            last_refill: Instant::now(),
+            dropped: 0,
+        }
+    }
+
+    /// Whether another message may be sent right now
+    pub fn try_acquire(&mut self) -> bool {
+        self.try_acquire_at(Instant::now())
+    }
+
+    fn try_acquire_at(&mut self, now: Instant) -> bool {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_sec).min(self.per_sec);
+        self.last_refill = now;
+
+        if self.tokens < 1.0 {
+            // Only log the start of a flood
+            if self.dropped == 0 {
+                warn!(
+                    "[UI] More than {} messages per second, dropping messages",
+                    self.per_sec
+                );
+            }
+            self.dropped += 1;
+            return false;
+        }
+
+        if self.dropped > 0 {
+            warn!("[UI] Dropped {} messages", self.dropped);
+            self.dropped = 0;
+        }
+
+        self.tokens -= 1.0;
+        true
+    }
+}
+
+/// Channel sender that drops messages past `MAX_UI_MESSAGES_PER_SEC`
+pub struct LimitedSender<T> {
+    tx: Sender<T>,
+    limiter: Mutex<RateLimiter>,
+}
+
+impl<T> LimitedSender<T> {
+    pub fn new(tx: Sender<T>) -> Self {
+        Self {
+            tx,
+            limiter: Mutex::new(RateLimiter::new(MAX_UI_MESSAGES_PER_SEC)),
+        }
+    }
+
+    /// Sends the message, giving it back if it was dropped or the channel closed
+    pub fn send(&self, message: T) -> Result<(), T> {
+        if !self.limiter.lock().unwrap().try_acquire() {
+            return Err(message);
+        }
+        self.tx.try_send(message).map_err(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossbeam_channel::unbounded;
+    use std::time::Duration;
+
+    #[test]
+    fn test_burst_past_limit_drops() {
+        let mut limiter = RateLimiter::new(10);
+        let start = Instant::now();
+
+        let allowed = (0..25).filter(|_| limiter.try_acquire_at(start)).count();
+        assert_eq!(allowed, 10);
+
+        // Refills over time, but never past the burst size
+        assert!(!limiter.try_acquire_at(start + Duration::from_millis(50)));
+        assert!(limiter.try_acquire_at(start + Duration::from_millis(100)));
+
+        let later = start + Duration::from_secs(60);
+        let allowed = (0..25).filter(|_| limiter.try_acquire_at(later)).count();
+        assert_eq!(allowed, 10);
+    }
+
+    #[test]
+    fn test_limited_sender() {
+        let (tx, rx) = unbounded();
+        let sender = LimitedSender::new(tx);
+
+        let sent = (0..MAX_UI_MESSAGES_PER_SEC * 2)
+            .filter(|n| sender.send(*n).is_ok())
+            .count();
+
+        // Some tokens may refill while the burst is sent
+        assert!(sent >= MAX_UI_MESSAGES_PER_SEC as usize);
+        assert!(sent < (MAX_UI_MESSAGES_PER_SEC * 2) as usize);
+        assert_eq!(rx.try_iter().count(), sent);
+    }
+}
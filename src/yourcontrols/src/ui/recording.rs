@@ -0,0 +1,264 @@
+// Session Recording
+//
+// Wraps any backend to write every message crossing the UI boundary to a file, so
+// users can attach a trace of what happened to bug reports.
+
+use super::{AppMessage, UIBackend};
+use crossbeam_channel::TryRecvError;
+use log::{info, warn};
+use serde_json::Value;
+use std::fs::File;
+use std::io::{self, Write};
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+const REDACTED: &str = "<redacted>";
+
+// Invoke types whose data contains a session code
+const SESSION_CODE_INVOKES: [&str; 2] = ["session", "deeplink"];
+// Invoke carrying the whole config, which has saved addresses and session codes
+const CONFIG_INVOKE: &str = "config_msg";
+
+struct Recorder {
+    file: File,
+    started: Instant,
+    // Hide session codes, on while in streamer mode
+    redact: bool,
+}
+
+impl Recorder {
+    fn create(redact: bool) -> io::Result<Self> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+        let filename = format!("session_{}.log", timestamp);
+
+        info!("[UI] Recording session to {}", filename);
+
+        Ok(Self {
+            file: File::create(filename)?,
+            started: Instant::now(),
+            redact,
+        })
+    }
+
+    fn write(&mut self, direction: &str, text: &str) {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        if let Err(e) = writeln!(self.file, "[{:>10.3}] {} {}", elapsed, direction, text) {
+            warn!("[UI] Could not write session recording: {}", e);
+        }
+    }
+}
+
+/// Backend decorator that can record the session to a file
+pub struct RecordingBackend<B: UIBackend> {
+    inner: B,
+    recorder: Mutex<Option<Recorder>>,
+}
+
+impl<B: UIBackend> RecordingBackend<B> {
+    /// Start or stop recording, hiding session codes and addresses when `redact` is set
+    pub fn set_recording(&self, enabled: bool, redact: bool) {
+        let mut recorder = self.recorder.lock().unwrap();
+
+        match (enabled, recorder.as_mut()) {
+            (true, Some(recorder)) => recorder.redact = redact,
+            (true, None) => match Recorder::create(redact) {
+                Ok(new_recorder) => *recorder = Some(new_recorder),
+                Err(e) => warn!("[UI] Could not start session recording: {}", e),
+            },
+            (false, _) => *recorder = None,
+        }
+    }
+
+    fn record(&self, direction: &str, text: impl FnOnce(bool) -> String) {
+        if let Some(recorder) = self.recorder.lock().unwrap().as_mut() {
+            let text = text(recorder.redact);
+            recorder.write(direction, &text);
+        }
+    }
+}
+
+// Hides the addresses and session codes a config keeps, including in saved server profiles
+fn redact_config(config: &mut Value) {
+    if let Some(ip) = config.get_mut("ip") {
+        *ip = REDACTED.into();
+    }
+
+    if let Some(profiles) = config.get_mut("profiles").and_then(Value::as_array_mut) {
+        for profile in profiles {
+            for key in ["ip", "session_code"] {
+                if let Some(value) = profile.get_mut(key) {
+                    *value = REDACTED.into();
+                }
+            }
+        }
+    }
+}
+
+// JSON for a message from the UI, without its session code if redacting
+fn message_text(message: &AppMessage, redact: bool) -> String {
+    let mut value = match serde_json::to_value(message) {
+        Ok(value) => value,
+        Err(e) => return format!("<unserializable: {}>", e),
+    };
+
+    if redact {
+        for key in ["session_id", "relay_session_id"] {
+            if let Some(session_id) = value.get_mut(key).filter(|id| !id.is_null()) {
+                *session_id = REDACTED.into();
+            }
+        }
+
+        if let Some(config) = value.get_mut("new_config") {
+            redact_config(config);
+        }
+    }
+
+    // Passwords are never recorded
+    if let Some(password) = value.get_mut("password").filter(|p| !p.is_null()) {
+        *password = REDACTED.into();
+    }
+
+    value.to_string()
+}
+
+// An update sent to the UI, without its session code if redacting
+fn invoke_text(type_string: &str, data: Option<&str>, redact: bool) -> String {
+    match data {
+        Some(_) if redact && SESSION_CODE_INVOKES.contains(&type_string) => {
+            format!("{} {}", type_string, REDACTED)
+        }
+        Some(data) if redact && type_string == CONFIG_INVOKE => {
+            match serde_json::from_str::<Value>(data) {
+                Ok(mut config) => {
+                    redact_config(&mut config);
+                    format!("{} {}", type_string, config)
+                }
+                Err(_) => format!("{} {}", type_string, REDACTED),
+            }
+        }
+        Some(data) => format!("{} {}", type_string, data),
+        None => type_string.to_string(),
+    }
+}
+
+impl<B: UIBackend> UIBackend for RecordingBackend<B> {
+    fn setup(title: String) -> Self {
+        Self {
+            inner: B::setup(title),
+            recorder: Mutex::new(None),
+        }
+    }
+
+    fn exited(&self) -> bool {
+        self.inner.exited()
+    }
+
+    fn is_alive(&self) -> bool {
+        self.inner.is_alive()
+    }
+
+    fn setup_error(&self) -> Option<String> {
+        self.inner.setup_error()
+    }
+
+    fn get_next_message(&self) -> Result<AppMessage, TryRecvError> {
+        let message = self.inner.get_next_message();
+        if let Ok(message) = &message {
+            self.record("UI->APP", |redact| message_text(message, redact));
+        }
+        message
+    }
+
+    fn invoke(&self, type_string: &str, data: Option<&str>) {
+        self.record("APP->UI", |redact| invoke_text(type_string, data, redact));
+        self.inner.invoke(type_string, data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::ConnectionMethod;
+
+    fn connect_message() -> AppMessage {
+        AppMessage::Connect {
+            session_id: Some("ABC123".to_string()),
+            username: "Bob".to_string(),
+            method: ConnectionMethod::CloudServer,
+            ip: None,
+            port: None,
+            isipv6: false,
+            hostname: None,
+            spectator: false,
+            relay_session_id: Some("DEF456".to_string()),
+            password: Some("hunter2".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_message_text_redacts_session_code() {
+        let message = connect_message();
+        assert!(message_text(&message, false).contains("ABC123"));
+
+        let text = message_text(&message, true);
+        assert!(!text.contains("ABC123"));
+        assert!(!text.contains("DEF456"));
+        assert!(text.contains(REDACTED));
+
+        // Even when not redacting session codes
+        assert!(!message_text(&message, false).contains("hunter2"));
+
+        // Nothing to hide
+        assert!(!message_text(&AppMessage::Startup, true).contains(REDACTED));
+    }
+
+    #[test]
+    fn test_config_is_redacted() {
+        use crate::simconfig::{Config, ServerProfile};
+
+        let mut config = Config::default();
+        config.ip = "203.0.113.7".to_string();
+        config.profiles.push(ServerProfile {
+            ip: "198.51.100.2".to_string(),
+            session_code: "ABC123".to_string(),
+            ..Default::default()
+        });
+        let secrets = ["203.0.113.7", "198.51.100.2", "ABC123"];
+
+        let message = AppMessage::UpdateConfig {
+            new_config: config.clone(),
+        };
+        let text = message_text(&message, true);
+        assert!(secrets.iter().all(|secret| !text.contains(secret)));
+        assert!(message_text(&message, false).contains("ABC123"));
+
+        let data = serde_json::to_string(&config).unwrap();
+        let text = invoke_text("config_msg", Some(&data), true);
+        assert!(text.starts_with("config_msg "));
+        assert!(secrets.iter().all(|secret| !text.contains(secret)));
+        assert_eq!(
+            invoke_text("config_msg", Some("not json"), true),
+            "config_msg <redacted>"
+        );
+    }
+
+    #[test]
+    fn test_invoke_text_redacts_session_code() {
+        assert_eq!(
+            invoke_text("session", Some("ABC123"), false),
+            "session ABC123"
+        );
+        assert_eq!(
+            invoke_text("session", Some("ABC123"), true),
+            "session <redacted>"
+        );
+        assert_eq!(
+            invoke_text("deeplink", Some("yourcontrols://join?code=ABC123"), true),
+            "deeplink <redacted>"
+        );
+        assert_eq!(invoke_text("error", Some("oops"), true), "error oops");
+        assert_eq!(invoke_text("connected", None, true), "connected");
+    }
+}
@@ -0,0 +1,94 @@
+// System Tray Icon
+//
+// Lets the egui window hide while a session keeps running. The icon offers Show,
+// Disconnect and Quit; only Quit actually closes the window and ends the app.
+
+use anyhow::Result;
+use crossbeam_channel::{unbounded, Receiver};
+use tray_icon::menu::{Menu, MenuEvent, MenuItem};
+use tray_icon::{MouseButton, TrayIcon, TrayIconBuilder, TrayIconEvent};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrayAction {
+    Show,
+    Disconnect,
+    Quit,
+}
+
+pub struct Tray {
+    _icon: TrayIcon,
+    actions: Receiver<TrayAction>,
+}
+
+impl Tray {
+    /// `wake` runs whenever an action arrives, so a hidden window still gets to handle it
+    pub fn new(tooltip: &str, wake: impl Fn() + Send + Sync + 'static) -> Result<Self> {
+        let show = MenuItem::new("Show", true, None);
+        let disconnect = MenuItem::new("Disconnect", true, None);
+        let quit = MenuItem::new("Quit", true, None);
+
+        let menu = Menu::new();
+        menu.append_items(&[&show, &disconnect, &quit])?;
+
+        let icon = TrayIconBuilder::new()
+            .with_tooltip(tooltip)
+            .with_menu(Box::new(menu))
+            .with_icon(load_icon()?)
+            .build()?;
+
+        let (tx, actions) = unbounded();
+        let wake = std::sync::Arc::new(wake);
+
+        let (show_id, disconnect_id, quit_id) = (
+            show.id().clone(),
+            disconnect.id().clone(),
+            quit.id().clone(),
+        );
+        let menu_tx = tx.clone();
+        let menu_wake = wake.clone();
+        MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+            let action = if event.id == show_id {
+                TrayAction::Show
+            } else if event.id == disconnect_id {
+                TrayAction::Disconnect
+            } else if event.id == quit_id {
+                TrayAction::Quit
+            } else {
+                return;
+            };
+            menu_tx.send(action).ok();
+            menu_wake();
+        }));
+
+        // Double clicking the icon brings the window back
+        TrayIconEvent::set_event_handler(Some(move |event: TrayIconEvent| {
+            if let TrayIconEvent::DoubleClick {
+                button: MouseButton::Left,
+                ..
+            } = event
+            {
+                tx.send(TrayAction::Show).ok();
+                wake();
+            }
+        }));
+
+        Ok(Self {
+            _icon: icon,
+            actions,
+        })
+    }
+
+    pub fn try_action(&self) -> Option<TrayAction> {
+        self.actions.try_recv().ok()
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn load_icon() -> Result<tray_icon::Icon> {
+    Ok(tray_icon::Icon::from_path("assets/icon.ico", None)?)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn load_icon() -> Result<tray_icon::Icon> {
+    anyhow::bail!("Tray icons are only supported on Windows")
+}
@@ -3,6 +3,7 @@
 // This module wraps the existing WebView-based UI to implement the UIBackend trait.
 // It maintains backward compatibility with the original implementation.
 
+use super::ratelimit::LimitedSender;
 use super::{AppMessage, UIBackend};
 use base64::Engine;
 use crossbeam_channel::{unbounded, Receiver, TryRecvError};
@@ -18,12 +19,15 @@ use std::thread;
 pub struct WebViewBackend {
     app_handle: Arc<Mutex<Option<web_view::Handle<i32>>>>,
     exited: Arc<AtomicBool>,
+    // Set when the window couldn't be created, e.g. without the WebView2 runtime
+    setup_error: Arc<Mutex<Option<String>>>,
     rx: Receiver<AppMessage>,
 }
 
 impl UIBackend for WebViewBackend {
     fn setup(title: String) -> Self {
         let (tx, rx) = unbounded();
+        let tx = LimitedSender::new(tx);
 
         let mut logo = vec![];
         File::open("assets/logo.png")
@@ -35,6 +39,8 @@ impl UIBackend for WebViewBackend {
         let handle_clone = handle.clone();
         let exited = Arc::new(AtomicBool::new(false));
         let exited_clone = exited.clone();
+        let setup_error = Arc::new(Mutex::new(None));
+        let setup_error_clone = setup_error.clone();
 
         thread::spawn(move || {
             let webview = web_view::builder()
@@ -70,14 +76,25 @@ impl UIBackend for WebViewBackend {
                     logo = base64::engine::general_purpose::STANDARD_NO_PAD.encode(logo.as_slice())
                 )))
                 .invoke_handler(move |_, arg| {
-                    tx.try_send(serde_json::from_str(arg).unwrap()).ok();
+                    tx.send(serde_json::from_str(arg).unwrap()).ok();
                     Ok(())
                 })
                 .user_data(0)
                 .resizable(true)
                 .size(1000, 800)
-                .build()
-                .unwrap();
+                .build();
+
+            let webview = match webview {
+                Ok(webview) => webview,
+                Err(e) => {
+                    *setup_error_clone.lock().unwrap() = Some(format!(
+                        "Could not create the window ({}). On Windows this needs the WebView2 runtime.",
+                        e
+                    ));
+                    exited_clone.store(true, SeqCst);
+                    return;
+                }
+            };
 
             let mut handle = handle_clone.lock().unwrap();
             *handle = Some(webview.handle());
@@ -90,6 +107,7 @@ impl UIBackend for WebViewBackend {
         Self {
             app_handle: handle,
             exited,
+            setup_error,
             rx,
         }
     }
@@ -98,8 +116,16 @@ impl UIBackend for WebViewBackend {
         self.exited.load(SeqCst)
     }
 
+    fn setup_error(&self) -> Option<String> {
+        self.setup_error.lock().unwrap().clone()
+    }
+
     fn get_next_message(&self) -> Result<AppMessage, TryRecvError> {
-        self.rx.try_recv()
+        let message = self.rx.try_recv();
+        if let Err(TryRecvError::Disconnected) = message {
+            self.exited.store(true, SeqCst);
+        }
+        message
     }
 
     fn invoke(&self, type_string: &str, data: Option<&str>) {
@@ -4,13 +4,21 @@ use std::env;
 use std::{
     fs,
     io::{copy, Cursor},
+    path::Path,
+    process::Command,
 };
 
 const RELEASE_DIRECT_URL: &str =
     "https://github.com/sequal32/yourcontrolsinstaller/releases/latest/download/installer.zip";
+const INSTALLER_RELEASE_URL: &str =
+    "https://api.github.com/repos/sequal32/yourcontrolsinstaller/releases/latest";
+const INSTALLER_ASSET_NAME: &str = "installer.zip";
 const PROGRAM_RELEASE_URL: &str =
     "https://api.github.com/repos/sequal32/yourcontrols/releases/latest";
 
+/// Passed to the program when the installer relaunches it
+pub const UPDATED_ARG: &str = "--updated";
+
 const USER_AGENT: &str =
     "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:53.0) Gecko/20100101 Firefox/53.0";
 
@@ -34,8 +42,59 @@ impl std::fmt::Display for DownloadInstallerError {
     }
 }
 
+impl DownloadInstallerError {
+    /// Whether trying again could succeed, network and disk problems are often temporary
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            DownloadInstallerError::RequestFailed(_) | DownloadInstallerError::IOError(_)
+        )
+    }
+}
+
+/// Where the installer writes and how much is downloaded, shown so the user can approve the update
+pub struct UpdateContents {
+    pub files: Vec<String>,
+    /// Download size in bytes
+    pub size: u64,
+}
+
+impl UpdateContents {
+    // Sized from the installer release's own asset, the installer then replaces the program folder
+    // (aircraft definitions included) and the sim's community package
+    fn from_installer_release(
+        json: &Value,
+        program_dir: &Path,
+    ) -> Result<Self, DownloadInstallerError> {
+        let size = json["assets"]
+            .as_array()
+            .ok_or(DownloadInstallerError::MissingFieldJSON)?
+            .iter()
+            .find(|asset| asset["name"].as_str() == Some(INSTALLER_ASSET_NAME))
+            .and_then(|asset| asset["size"].as_u64())
+            .ok_or(DownloadInstallerError::MissingFieldJSON)?;
+
+        Ok(UpdateContents {
+            files: vec![
+                program_dir.display().to_string(),
+                program_dir
+                    .join("definitions")
+                    .join("aircraft")
+                    .display()
+                    .to_string(),
+                Path::new("Community")
+                    .join("YourControls")
+                    .display()
+                    .to_string(),
+            ],
+            size,
+        })
+    }
+}
+
 pub struct Updater {
     latest_version: Option<Version>,
+    latest_contents: Option<UpdateContents>,
     latest_installer_bytes: Option<Vec<u8>>,
 }
 
@@ -43,6 +102,7 @@ impl Updater {
     pub fn new() -> Self {
         Self {
             latest_version: None,
+            latest_contents: None,
             latest_installer_bytes: None,
         }
     }
@@ -81,16 +141,33 @@ impl Updater {
         }
     }
 
-    pub fn run_installer(&mut self) -> Result<(), DownloadInstallerError> {
+    fn installer_archive(
+        &mut self,
+    ) -> Result<zip::ZipArchive<Cursor<&Vec<u8>>>, DownloadInstallerError> {
         let installer_bytes = match self.latest_installer_bytes.as_ref() {
             Some(bytes) => bytes,
             None => self.download_installer()?,
         };
 
-        let mut zip = match zip::ZipArchive::new(Cursor::new(installer_bytes)) {
-            Ok(zip) => zip,
-            Err(e) => return Err(DownloadInstallerError::ZipError(e)),
-        };
+        zip::ZipArchive::new(Cursor::new(installer_bytes)).map_err(DownloadInstallerError::ZipError)
+    }
+
+    /// Lists what the installer will change from its release metadata, without downloading it
+    pub fn update_contents(&mut self) -> Result<&UpdateContents, DownloadInstallerError> {
+        if self.latest_contents.is_none() {
+            let exe = env::current_exe().map_err(DownloadInstallerError::IOError)?;
+            let program_dir = exe.parent().unwrap_or_else(|| Path::new("."));
+            let json = self.get_json_from_url(INSTALLER_RELEASE_URL)?;
+            self.latest_contents =
+                Some(UpdateContents::from_installer_release(&json, program_dir)?);
+        }
+        Ok(self.latest_contents.as_ref().unwrap())
+    }
+
+    /// Starts the installer, which reopens the program once it succeeds. The caller should exit
+    /// so the installer can replace its files
+    pub fn run_installer(&mut self) -> Result<(), DownloadInstallerError> {
+        let mut zip = self.installer_archive()?;
         // Write files
         let mut dir = env::temp_dir();
         dir.push("YourControlsInstaller");
@@ -110,7 +187,7 @@ impl Updater {
         // Run exe
         dir.push("installer.exe");
 
-        let mut process = std::process::Command::new(dir.as_os_str());
+        let mut process = installer_command(&dir.to_string_lossy())?;
         process
             .stderr(std::process::Stdio::null())
             .stdout(std::process::Stdio::null())
@@ -150,3 +227,68 @@ impl Updater {
         Version::parse(env!("CARGO_PKG_VERSION")).unwrap()
     }
 }
+
+// Runs the installer through cmd so the program is started again once it has finished
+#[cfg(target_os = "windows")]
+fn installer_command(installer_path: &str) -> Result<Command, DownloadInstallerError> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let exe = env::current_exe().map_err(DownloadInstallerError::IOError)?;
+
+    let mut command = Command::new("cmd");
+    command.creation_flags(CREATE_NO_WINDOW).raw_arg(format!(
+        "/C \"\"{}\" && start \"\" \"{}\" {}\"",
+        installer_path,
+        exe.display(),
+        UPDATED_ARG
+    ));
+    Ok(command)
+}
+
+// The installer only ships for Windows, so there's no relaunch to chain elsewhere
+#[cfg(not(target_os = "windows"))]
+fn installer_command(installer_path: &str) -> Result<Command, DownloadInstallerError> {
+    Ok(Command::new(installer_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_update_contents_from_installer_release() {
+        let program_dir = Path::new("YourControls");
+        let release = json!({
+            "tag_name": "1.2.0",
+            "assets": [
+                { "name": "source.zip", "size": 9000 },
+                { "name": "installer.zip", "size": 3000 },
+            ],
+        });
+        let contents = UpdateContents::from_installer_release(&release, program_dir)
+            .ok()
+            .unwrap();
+        assert_eq!(
+            contents.files,
+            vec![
+                program_dir.display().to_string(),
+                program_dir
+                    .join("definitions")
+                    .join("aircraft")
+                    .display()
+                    .to_string(),
+                Path::new("Community")
+                    .join("YourControls")
+                    .display()
+                    .to_string(),
+            ]
+        );
+        assert_eq!(contents.size, 3000);
+
+        let missing_installer = json!({ "assets": [{ "name": "source.zip", "size": 9000 }] });
+        assert!(UpdateContents::from_installer_release(&missing_installer, program_dir).is_err());
+        assert!(UpdateContents::from_installer_release(&json!({}), program_dir).is_err());
+    }
+}
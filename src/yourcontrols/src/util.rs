@@ -1,6 +1,12 @@
 use serde::{Deserialize, Serialize};
-use std::{net::IpAddr, ops::Add, ops::Sub};
-use yourcontrols_types::Error;
+use std::net::{IpAddr, SocketAddr, UdpSocket};
+use std::{io, ops::Add, ops::Sub, time::Duration};
+use yourcontrols_types::{AllNeedSync, Error, VarMap, VarReaderTypes};
+
+const PUBLIC_IP_URL: &str = "https://api.ipify.org";
+const PUBLIC_IP_URL_V6: &str = "https://api64.ipify.org";
+// The lookup only fills in the invite address, so give up quickly
+const PUBLIC_IP_TIMEOUT: Duration = Duration::from_secs(5);
 
 pub fn get_hostname_ip(hostname: &str, isipv6: bool) -> Result<IpAddr, Error> {
     match dns_lookup::lookup_host(hostname)?
@@ -12,6 +18,81 @@ pub fn get_hostname_ip(hostname: &str, isipv6: bool) -> Result<IpAddr, Error> {
     }
 }
 
+pub fn get_public_ip(isipv6: bool) -> Option<IpAddr> {
+    let url = if isipv6 {
+        PUBLIC_IP_URL_V6
+    } else {
+        PUBLIC_IP_URL
+    };
+
+    attohttpc::get(url)
+        .timeout(PUBLIC_IP_TIMEOUT)
+        .send()
+        .ok()?
+        .text()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Shows an error in a native dialog, release builds have no console to print to
+#[cfg(target_os = "windows")]
+pub fn show_error_box(title: &str, text: &str) {
+    use std::ffi::c_void;
+
+    const MB_ICONERROR: u32 = 0x10;
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn MessageBoxW(hwnd: *mut c_void, text: *const u16, caption: *const u16, kind: u32) -> i32;
+    }
+
+    let wide = |s: &str| s.encode_utf16().chain(Some(0)).collect::<Vec<u16>>();
+    let (title, text) = (wide(title), wide(text));
+    // SAFETY: both strings are null terminated and outlive the call
+    unsafe {
+        MessageBoxW(
+            std::ptr::null_mut(),
+            text.as_ptr(),
+            title.as_ptr(),
+            MB_ICONERROR,
+        );
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn show_error_box(title: &str, text: &str) {
+    eprintln!("{}: {}", title, text);
+}
+
+// Sends a datagram and waits briefly for an answer. UDP can't confirm anything is listening,
+// so Ok(false) only means nothing refused it, while a refusal comes back as an error
+pub fn probe_udp(target: SocketAddr, timeout: Duration) -> io::Result<bool> {
+    let bind: SocketAddr = if target.is_ipv6() {
+        "[::]:0".parse().unwrap()
+    } else {
+        "0.0.0.0:0".parse().unwrap()
+    };
+    let socket = UdpSocket::bind(bind)?;
+    socket.connect(target)?;
+    socket.set_read_timeout(Some(timeout))?;
+    socket.send(&[0])?;
+
+    match socket.recv(&mut [0; 64]) {
+        Ok(_) => Ok(true),
+        Err(e)
+            if matches!(
+                e.kind(),
+                io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+            ) =>
+        {
+            Ok(false)
+        }
+        Err(e) => Err(e),
+    }
+}
+
 pub fn wrap_diff(from: f64, to: f64, max: f64) -> f64 {
     let threshold = max * 0.5;
     if (from - to).abs() > threshold {
@@ -109,10 +190,104 @@ impl Default for Vector3 {
     }
 }
 
+// Aircraft position as (lat, lon, heading) if the synced vars include it
+pub fn get_position(avars: &VarMap) -> Option<(f64, f64, f32)> {
+    let lat = avars.get("PLANE LATITUDE")?.get_as_f64();
+    let lon = avars.get("PLANE LONGITUDE")?.get_as_f64();
+    let heading = avars
+        .get("PLANE HEADING DEGREES TRUE")
+        .map_or(0.0, |heading| heading.get_as_f64());
+    Some((lat, lon, heading as f32))
+}
+
+// Every synced variable as (name, value) sorted by name, local vars prefixed with L:
+pub fn sync_value_pairs(all: &AllNeedSync) -> Vec<(String, String)> {
+    let format_value = |value: &VarReaderTypes| match value {
+        VarReaderTypes::Bool(value) => value.to_string(),
+        VarReaderTypes::I32(value) => value.to_string(),
+        VarReaderTypes::I64(value) => value.to_string(),
+        VarReaderTypes::F64(value) => format!("{:.4}", value),
+    };
+
+    let mut pairs: Vec<(String, String)> = all
+        .avars
+        .iter()
+        .map(|(name, value)| (name.clone(), format_value(value)))
+        .chain(
+            all.lvars
+                .iter()
+                .map(|(name, value)| (format!("L:{}", name), format_value(value))),
+        )
+        .collect();
+
+    pairs.sort();
+    pairs
+}
+
+// Strips control characters and caps the length of user supplied text
+pub fn sanitize_text(text: &str, max_len: usize) -> String {
+    text.chars()
+        .filter(|c| !c.is_control())
+        .take(max_len)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
 pub fn float_eq(lhs: &f64, rhs: &f64) -> bool {
     (rhs - lhs).abs() < f64::EPSILON
 }
 
+// Loose check that a sim TITLE is for the aircraft a definition file ("Developer - Model.yaml") is made for.
+// When both name a model number ("152", "172SP") they must share one, otherwise any word of the model will do
+pub fn aircraft_matches_config(title: &str, config_file_name: &str) -> bool {
+    let words = |text: &str| -> Vec<String> {
+        text.split(|c: char| !c.is_alphanumeric())
+            .filter(|word| word.len() >= 3)
+            .map(str::to_lowercase)
+            .collect()
+    };
+    let numbers = |text: &str| -> Vec<String> {
+        text.split(|c: char| !c.is_ascii_digit())
+            .filter(|number| !number.is_empty())
+            .map(String::from)
+            .collect()
+    };
+
+    let model = config_file_name.trim_end_matches(".yaml");
+    let model = model.split(" - ").last().unwrap_or(model);
+
+    let (title_numbers, model_numbers) = (numbers(title), numbers(model));
+    if !title_numbers.is_empty() && !model_numbers.is_empty() {
+        return model_numbers
+            .iter()
+            .any(|number| title_numbers.contains(number));
+    }
+
+    let title_words = words(title);
+    words(model).iter().any(|word| title_words.contains(word))
+}
+
+// Whether control can be handed between two definition files ("Developer - Model", with or without a path or .yaml),
+// either the same file or listed together in one of the configured families
+pub fn aircraft_compatible(a: &str, b: &str, families: &[Vec<String>]) -> bool {
+    let normalize = |name: &str| {
+        let name = name.trim();
+        let name = name
+            .rsplit(|c: char| c == '/' || c == '\\')
+            .next()
+            .unwrap_or(name);
+        name.trim_end_matches(".yaml").to_lowercase()
+    };
+    let (a, b) = (normalize(a), normalize(b));
+
+    a == b
+        || families.iter().any(|family| {
+            let family: Vec<String> = family.iter().map(|name| normalize(name)).collect();
+            family.contains(&a) && family.contains(&b)
+        })
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -127,10 +302,133 @@ mod test {
         assert_eq!(digits.get(3), 0);
     }
 
+    #[test]
+    fn test_probe_udp() {
+        let timeout = Duration::from_millis(200);
+
+        let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let target = listener.local_addr().unwrap();
+        // Listening but silent
+        assert!(!probe_udp(target, timeout).unwrap());
+
+        // Fresh listener, the silent probe's datagram is still queued on the first one
+        let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let target = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let mut buf = [0; 64];
+            let (_, from) = listener.recv_from(&mut buf).unwrap();
+            listener.send_to(&[1], from).unwrap();
+        });
+        assert!(probe_udp(target, timeout).unwrap());
+    }
+
     #[test]
     fn test_wrap_diff() {
         assert!(float_eq(&wrap_diff(0.0, 10.0, 360.0), &10.0));
         assert!(float_eq(&wrap_diff(350.0, 10.0, 360.0), &20.0));
         assert!(float_eq(&wrap_diff(10.0, 350.0, 360.0), &-20.0));
     }
+
+    #[test]
+    fn test_sanitize_text() {
+        assert_eq!(sanitize_text("  look\u{7}\nleft ", 32), "lookleft");
+        assert_eq!(sanitize_text("abcdef", 3), "abc");
+    }
+
+    #[test]
+    fn test_get_position() {
+        use yourcontrols_types::VarReaderTypes;
+
+        let mut avars = VarMap::new();
+        avars.insert("PLANE LATITUDE".to_string(), VarReaderTypes::F64(47.5));
+        assert_eq!(get_position(&avars), None);
+
+        avars.insert("PLANE LONGITUDE".to_string(), VarReaderTypes::F64(-122.3));
+        assert_eq!(get_position(&avars), Some((47.5, -122.3, 0.0)));
+
+        avars.insert(
+            "PLANE HEADING DEGREES TRUE".to_string(),
+            VarReaderTypes::F64(90.0),
+        );
+        assert_eq!(get_position(&avars), Some((47.5, -122.3, 90.0)));
+    }
+
+    #[test]
+    fn test_aircraft_matches_config() {
+        assert!(aircraft_matches_config(
+            "Cessna Skyhawk G1000 Asobo",
+            "Asobo Studio - Cessna 172SP Skyhawk (G1000).yaml"
+        ));
+        assert!(!aircraft_matches_config(
+            "Boeing 747-8i Asobo",
+            "Asobo Studio - Cessna 152.yaml"
+        ));
+        // The developer alone isn't enough
+        assert!(!aircraft_matches_config(
+            "Diamond DA62 Asobo",
+            "Asobo Studio - Icon A5.yaml"
+        ));
+        // Nor is the manufacturer when the model differs
+        assert!(!aircraft_matches_config(
+            "Cessna 152 Asobo",
+            "Asobo Studio - Cessna 172SP"
+        ));
+        assert!(aircraft_matches_config(
+            "Cessna 152 Asobo",
+            "Asobo Studio - Cessna 152.yaml"
+        ));
+        // Without a model number in the title, a shared word still counts
+        assert!(aircraft_matches_config(
+            "Cessna Skyhawk Asobo",
+            "Asobo Studio - Cessna 172SP Skyhawk.yaml"
+        ));
+    }
+
+    #[test]
+    fn test_sync_value_pairs() {
+        let mut all = AllNeedSync::new();
+        all.avars
+            .insert("PLANE ALTITUDE".to_string(), VarReaderTypes::F64(1500.25));
+        all.avars.insert(
+            "GEAR HANDLE POSITION".to_string(),
+            VarReaderTypes::Bool(true),
+        );
+        all.lvars
+            .insert("XMLVAR_Baro1".to_string(), VarReaderTypes::F64(1.0));
+
+        assert_eq!(
+            sync_value_pairs(&all),
+            vec![
+                ("GEAR HANDLE POSITION".to_string(), "true".to_string()),
+                ("L:XMLVAR_Baro1".to_string(), "1.0000".to_string()),
+                ("PLANE ALTITUDE".to_string(), "1500.2500".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_aircraft_compatible() {
+        assert!(aircraft_compatible(
+            "Asobo - C172",
+            "definitions/aircraft/Asobo - C172.yaml",
+            &[]
+        ));
+        assert!(aircraft_compatible("asobo - c172", "Asobo - C172", &[]));
+        assert!(!aircraft_compatible("Asobo - C172", "Asobo - A320", &[]));
+
+        let families = vec![vec![
+            "Asobo - C172".to_string(),
+            "Asobo - C172 Classic.yaml".to_string(),
+        ]];
+        assert!(aircraft_compatible(
+            "Asobo - C172",
+            "Asobo - C172 Classic",
+            &families
+        ));
+        assert!(!aircraft_compatible(
+            "Asobo - C172",
+            "Asobo - A320",
+            &families
+        ));
+    }
 }
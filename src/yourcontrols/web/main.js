@@ -328,6 +328,10 @@ function MessageReceived(data) {
             connectionList.add(data["data"]);
             setTheme(settings.ui_dark_theme);
             break;
+        case "newconnection_detailed":
+            connectionList.add(JSON.parse(data["data"]).name);
+            setTheme(settings.ui_dark_theme);
+            break;
         case "lostconnection":
             connectionList.remove(data["data"]);
             break;
@@ -361,9 +365,22 @@ function MessageReceived(data) {
             $("#updateModal").modal();
             version_alert_text.innerHTML = "New Version is available " + data["data"];
             break;
+        case "update_ready":
+            alert.updatetext("success", "Updated to version " + data["data"]);
+            break;
         case "update_failed":
             updateFailed();
             break;
+        case "update_failed_detail": {
+            const failure = JSON.parse(data["data"]);
+            updateFailed();
+            version_alert_text.textContent = "Update failed: " + failure.reason;
+            version_alert_button.disabled = !failure.retryable;
+            break;
+        }
+        case "update_confirm":
+            updateConfirm(JSON.parse(data["data"]));
+            break;
         case "config_msg":
             LoadSettings(JSON.parse(data["data"]));
             break;
@@ -624,6 +641,23 @@ function updateFailed() {
     version_alert_button.disabled = false;
 }
 
+function updateConfirm(contents) {
+    const megabytes = (contents.size / (1024 * 1024)).toFixed(1);
+    const accepted = confirm(
+        "The installer will download and install (" + megabytes + " MB):\n\n" +
+            contents.files.join("\n") +
+            "\n\nYourControls closes while it installs and reopens afterwards. Install now?"
+    );
+    invoke({
+        type: "confirmUpdate",
+        accepted: accepted,
+    });
+    if (!accepted) {
+        version_alert_button.innerHTML = "Update It Now";
+        version_alert_button.disabled = false;
+    }
+}
+
 version_alert_button.onclick = update;
 
 aircraftList.addAircraft = function (aircraftName) {